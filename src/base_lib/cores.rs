@@ -1,12 +1,15 @@
 //! 底层基建
 //!
 //! - 抽象类型
+//! - 属性数值类型
 //! - 计时器
 
 pub mod design_patterns;
 
 pub mod unify_types;
 
+pub mod attr_nums;
+
 pub mod timers;
 
 pub mod tiny_tags;
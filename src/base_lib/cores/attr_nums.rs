@@ -0,0 +1,211 @@
+//! 属性的数值类型
+//!
+//! 属性默认使用 [`f64`] ，需要确定性（联机同步）或精确整数显示（整数血量）时可替换为 [`i64`] 或定点数 [`Fixed`]
+//!
+//! - 效果值、百分比、曲线等参数始终为 [`f64`]
+//! - 修改器各阶段的中间计算以 [`f64`] 进行，阶段结束时按 [`RoundingMode`] 落回属性的数值类型，
+//!   舍入规则见 [`crate::base_lib::eff_attr::modifiers::ModifierRounding`]
+//! - 浮点类型不做舍入，保持原有精度
+//! - 属性系统（[`crate::base_lib::eff_attr::attr_systems`] 的刷新与提交、复合属性的逐层传递、多资源消耗）
+//!   均对数值类型泛型；`common_impl` 的战斗属性固定为 [`f64`]
+
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+/// 从 [`f64`] 转换为属性数值类型时的舍入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// 四舍五入，恰好一半时远离零
+    #[default]
+    Nearest,
+    /// 向负无穷取整
+    Floor,
+    /// 向正无穷取整
+    Ceil,
+    /// 向零取整（截断）
+    TowardZero,
+}
+
+impl RoundingMode {
+    pub fn round(&self, v: f64) -> f64 {
+        match self {
+            Self::Nearest => v.round(),
+            Self::Floor => v.floor(),
+            Self::Ceil => v.ceil(),
+            Self::TowardZero => v.trunc(),
+        }
+    }
+}
+
+/// 属性的数值类型
+pub trait AttrNum:
+    Copy
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    /// 比较时允许的误差，整数类型为零
+    const DEAD_ZONE: Self;
+
+    /// 超出范围时饱和
+    fn from_f64(v: f64, rounding: RoundingMode) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    fn min_of(self, other: Self) -> Self {
+        if other < self { other } else { self }
+    }
+
+    fn max_of(self, other: Self) -> Self {
+        if other > self { other } else { self }
+    }
+}
+
+impl AttrNum for f64 {
+    const ZERO: Self = 0.0;
+    const DEAD_ZONE: Self = super::unify_types::FLOAT_DEAD_ZONE;
+
+    /// 浮点类型不舍入
+    #[inline(always)]
+    fn from_f64(v: f64, _rounding: RoundingMode) -> Self {
+        v
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl AttrNum for i64 {
+    const ZERO: Self = 0;
+    const DEAD_ZONE: Self = 0;
+
+    #[inline]
+    fn from_f64(v: f64, rounding: RoundingMode) -> Self {
+        // `as` 对浮点转整数是饱和的， NaN 转为 0
+        rounding.round(v) as i64
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// 定点数 Q47.16 ，低 16 位为小数部分
+///
+/// 加减法为整数运算，结果与平台无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 16;
+    const SCALE: f64 = (1_i64 << Self::FRAC_BITS) as f64;
+
+    pub const fn from_raw(raw: i64) -> Self {
+        Self(raw)
+    }
+
+    pub const fn from_int(v: i64) -> Self {
+        Self(v << Self::FRAC_BITS)
+    }
+
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+
+    /// 按舍入方式取整数部分，用于界面显示
+    pub fn to_int(self, rounding: RoundingMode) -> i64 {
+        rounding.round(self.to_f64()) as i64
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl AttrNum for Fixed {
+    const ZERO: Self = Self(0);
+    const DEAD_ZONE: Self = Self(0);
+
+    /// 舍入作用于最小精度（ 1/65536 ）
+    #[inline]
+    fn from_f64(v: f64, rounding: RoundingMode) -> Self {
+        Self(rounding.round(v * Self::SCALE) as i64)
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_modes() {
+        assert_eq!(i64::from_f64(2.5, RoundingMode::Nearest), 3);
+        assert_eq!(i64::from_f64(-2.5, RoundingMode::Nearest), -3);
+        assert_eq!(i64::from_f64(-2.5, RoundingMode::Floor), -3);
+        assert_eq!(i64::from_f64(2.1, RoundingMode::Ceil), 3);
+        assert_eq!(i64::from_f64(-2.9, RoundingMode::TowardZero), -2);
+        assert_eq!(
+            i64::from_f64(f64::INFINITY, RoundingMode::Nearest),
+            i64::MAX
+        );
+        assert_eq!(f64::from_f64(2.5, RoundingMode::Floor), 2.5);
+    }
+
+    #[test]
+    fn test_fixed_exact_arithmetic() {
+        let a = Fixed::from_f64(0.1, RoundingMode::Nearest);
+        let mut sum = Fixed::ZERO;
+        for _ in 0..10 {
+            sum += a;
+        }
+        // 定点数累加结果确定，与真实值的误差在最小精度内
+        assert_eq!(sum, Fixed::from_raw(a.raw() * 10));
+        assert!((sum.to_f64() - 1.0).abs() < 10.0 / 65536.0);
+
+        assert_eq!(Fixed::from_int(3).to_f64(), 3.0);
+        assert_eq!(
+            Fixed::from_f64(1.75, RoundingMode::Nearest).to_int(RoundingMode::Floor),
+            1
+        );
+        assert!(Fixed::from_int(1) < Fixed::from_int(2));
+    }
+}
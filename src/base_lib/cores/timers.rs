@@ -13,3 +13,5 @@ pub mod tick_trigger;
 pub mod static_trigger;
 
 pub mod pause_prefab;
+
+pub mod charge_counters;
@@ -0,0 +1,126 @@
+//! 按次数消耗的生命周期，如“抵挡下一次攻击”“接下来 3 次攻击附带火焰伤害”
+//!
+//! 与计时器满足同样的 [`TimerView`] [`TimerControl`] 约定，可直接作为效果的 `Timer` 泛型，
+//! 由 [`crate::base_lib::eff_attr::attr_systems::clean_expired_element`] 统一清理
+//!
+//! 次数不随时间流逝，只通过伤害与能量系统抛出的 [`ChargeEvent`] 显式消耗
+//!
+//! 作为拦截器的生命周期时，每拦截一个效果消耗一次，见 [`crate::base_lib::eff_attr::interceptors::InterceptCharge`]
+
+use crate::base_lib::cores::{
+    design_patterns::DependCtx,
+    timers::tiny_timer::{Tickable, TimerControl, TimerView},
+    unify_types::time_type,
+};
+
+/// 消耗次数的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeEvent {
+    /// 命中目标，由攻击方消耗
+    OnHit,
+    /// 施法（消耗能量）
+    OnCast,
+    /// 受到伤害，由受击方消耗
+    OnDamageTaken,
+}
+
+impl ChargeEvent {
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// 次数计数器
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChargeCounter {
+    charges: u32,
+    remaining: u32,
+    /// 响应的事件，按位记录
+    consume_mask: u8,
+}
+
+impl ChargeCounter {
+    pub fn new(charges: u32, consume_on: ChargeEvent) -> Self {
+        Self {
+            charges,
+            remaining: charges,
+            consume_mask: consume_on.bit(),
+        }
+    }
+
+    /// 追加响应的事件，如命中或施法均消耗
+    pub fn with_event(mut self, consume_on: ChargeEvent) -> Self {
+        self.consume_mask |= consume_on.bit();
+        self
+    }
+
+    pub fn get_charges(&self) -> u32 {
+        self.charges
+    }
+
+    pub fn get_remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    pub fn is_consumed_by(&self, event: ChargeEvent) -> bool {
+        self.consume_mask & event.bit() != 0
+    }
+
+    /// 响应事件且有剩余次数时消耗一次
+    pub fn try_consume(&mut self, event: ChargeEvent) -> bool {
+        if self.remaining == 0 || !self.is_consumed_by(event) {
+            return false;
+        }
+        self.remaining -= 1;
+        true
+    }
+}
+
+impl Tickable for ChargeCounter {
+    /// 次数不随时间流逝，便于与计时器统一驱动
+    fn tick(&mut self, _delta: time_type::T) {}
+}
+
+impl DependCtx for ChargeCounter {
+    type Ctx<'a> = ();
+}
+
+impl TimerView for ChargeCounter {
+    fn is_completed(&self, _: ()) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl TimerControl for ChargeCounter {
+    fn reset(&mut self, _: ()) {
+        self.remaining = self.charges
+    }
+
+    fn complete(&mut self, _: ()) {
+        self.remaining = 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只响应指定事件，耗尽后完成；tick 不影响次数
+    #[test]
+    fn test_consume_by_event() {
+        let mut counter = ChargeCounter::new(2, ChargeEvent::OnHit).with_event(ChargeEvent::OnCast);
+        assert!(!counter.try_consume(ChargeEvent::OnDamageTaken));
+        counter.tick(time_type::INFINITY);
+        assert_eq!(counter.get_remaining(), 2);
+
+        assert!(counter.try_consume(ChargeEvent::OnHit));
+        assert!(counter.try_consume(ChargeEvent::OnCast));
+        assert!(counter.is_completed(()));
+        assert!(!counter.try_consume(ChargeEvent::OnHit));
+
+        counter.reset(());
+        assert_eq!(counter.get_remaining(), 2);
+        counter.complete(());
+        assert!(counter.is_completed(()));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::base_lib::cores::{
     design_patterns::{DependCtx, Union},
     timers::tiny_timer::{CyclicalTrigger, TimerControl, TimerView},
+    unify_types::time_type,
 };
 
 /// 有限循环预制体，干预 [`TimerView`] [`TimerControl`] [`CyclicalTrigger`]
@@ -83,6 +84,10 @@ impl<T: CyclicalTrigger> CyclicalTrigger for Union<&mut FewShotTimes, &mut T> {
             true
         }
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.1.get_cycle()
+    }
 }
 
 #[cfg(test)]
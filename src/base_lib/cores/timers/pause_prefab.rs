@@ -159,6 +159,10 @@ impl<T: CyclicalTrigger> CyclicalTrigger for Union<&PausePrefab, &mut T> {
     fn try_trigger_once(&mut self, ctx: Self::Ctx<'_>) -> bool {
         self.1.try_trigger_once(ctx)
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.1.get_cycle()
+    }
 }
 
 // endregion
@@ -9,7 +9,7 @@ use crate::base_lib::cores::{
     design_patterns::DependCtx,
     timers::{
         tick_timer::TickTimer,
-        tiny_timer::{TimerControl, TimerProgress, TimerView},
+        tiny_timer::{TimerControl, TimerProgress, TimerRescale, TimerView},
     },
     unify_types::time_type,
 };
@@ -60,7 +60,7 @@ impl StaticTimeline {
 /// // 帧 N+1：业务再读，已过期
 /// assert!(t.is_completed(&timeline));
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct StaticTimer {
     /// 计时器时长
     duration: time_type::T,
@@ -132,6 +132,23 @@ impl TimerControl for StaticTimer {
     }
 }
 
+impl TimerRescale for StaticTimer {
+    fn rescale(&mut self, ctx: &StaticTimeline, ratio: f64) {
+        if self.duration == time_type::INFINITY {
+            return;
+        }
+        // 已完成的计时器保持完成，同时避免时长类型相减溢出
+        let remaining = if self.is_completed(ctx) {
+            time_type::ZERO
+        } else {
+            self.remaining(ctx)
+        };
+        self.duration = time_type::from_f64(time_type::to_f64(self.duration) * ratio);
+        self.end_at =
+            ctx.current_time() + time_type::from_f64(time_type::to_f64(remaining) * ratio);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +76,10 @@ impl CyclicalTrigger for InfiniteStaticTrigger {
             false
         }
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.cycle
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -143,6 +147,10 @@ impl CyclicalTrigger for FewShotStaticTrigger {
     fn try_trigger_once(&mut self, ctx: &StaticTimeline) -> bool {
         self.few_shot.of_cyclical_trigger(&mut self.inf_tg).try_trigger_once(ctx)
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.inf_tg.get_cycle()
+    }
 }
 
 #[cfg(test)]
@@ -5,12 +5,12 @@
 
 use crate::base_lib::cores::{
     design_patterns::DependCtx,
-    timers::tiny_timer::{Tickable, TimerControl, TimerProgress, TimerView},
+    timers::tiny_timer::{Tickable, TimerControl, TimerProgress, TimerRescale, TimerView},
     unify_types::time_type,
 };
 
 /// 简单计时器
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TickTimer {
     elapsed: time_type::T,
     duration: time_type::T,
@@ -77,6 +77,17 @@ impl TimerControl for TickTimer {
     }
 }
 
+impl TimerRescale for TickTimer {
+    fn rescale(&mut self, _: (), ratio: f64) {
+        if self.duration == time_type::INFINITY {
+            return;
+        }
+        // 同比例缩放，已完成的计时器仍然完成
+        self.elapsed = time_type::from_f64(time_type::to_f64(self.elapsed) * ratio);
+        self.duration = time_type::from_f64(time_type::to_f64(self.duration) * ratio);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
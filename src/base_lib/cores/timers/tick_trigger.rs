@@ -81,6 +81,10 @@ impl CyclicalTrigger for InfiniteTickTrigger {
             false
         }
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.cycle
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -150,6 +154,10 @@ impl CyclicalTrigger for FewShotTickTrigger {
     fn try_trigger_once(&mut self, _: ()) -> bool {
         self.few_shot.of_cyclical_trigger(&mut self.inf_trigger).try_trigger_once(())
     }
+
+    fn get_cycle(&self) -> time_type::T {
+        self.inf_trigger.get_cycle()
+    }
 }
 
 #[cfg(test)]
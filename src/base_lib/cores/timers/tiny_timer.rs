@@ -24,7 +24,7 @@
 //! - 无法通过形如定义特征 `fn is_paused(&self, ctx: Self::Ctx<'_>)` 来实现
 //! - 因为特征 [`DependCtx`] 要求一个类型只能关联一种 Ctx 类型，而“可暂停计时器”底层的计时器有多种类型
 //!
-//! trait 面按能力边界拆分，不进行合并。 8 个 trait 对应不同能力(进度模型/完成状态/手动控制/循环触发/暂停读/暂停写/tick/HasTimer)。
+//! trait 面按能力边界拆分，不进行合并。 9 个 trait 对应不同能力(进度模型/完成状态/手动控制/时长缩放/循环触发/暂停读/暂停写/tick/HasTimer)。
 //! - `TimerProgress` (进度) 与 `TimerView` (完成状态) 是两种能力， `TimerControl` 与 `CyclicalTrigger` 同理， `TickTimer`/`StaticTimer` 无循环触发能力
 //! - 合并会强迫仅实现单能力的类型实现无语义方法，并且组合方案生成的临时引用代理要求只读和可变能力需要进行区分，故刻意保持拆分(决议见 `.scratch/interface-deepening/issues/01`)。
 
@@ -95,6 +95,14 @@ pub trait TimerControl: DependCtx {
     fn complete(&mut self, ctx: Self::Ctx<'_>);
 }
 
+/// 计时器【时长缩放】，用于急速等改变推进速率的场景
+pub trait TimerRescale: DependCtx {
+    /// 总时长与剩余时长按同一比例缩放，进度不变，无限时长的计时器不受影响
+    ///
+    /// 缩放后 [`TimerProgress::remaining`] 仍是真实的剩余时间
+    fn rescale(&mut self, ctx: Self::Ctx<'_>, ratio: f64);
+}
+
 /// 计时器【暂停状态】
 pub trait TimerPauseView {
     /// 是否暂停
@@ -114,6 +122,9 @@ pub trait TimerPauseControl {
 pub trait CyclicalTrigger: DependCtx {
     /// 尝试触发一次
     fn try_trigger_once(&mut self, ctx: Self::Ctx<'_>) -> bool;
+
+    /// 触发周期，不依赖上下文，用于构造时校验
+    fn get_cycle(&self) -> time_type::T;
 }
 
 /// 拥有计时器，一个类型只能实现一次该特征
@@ -1,8 +1,11 @@
 //! 标签系统，用标签替代逻辑，实现框架和业务解耦合
 
+use std::marker::PhantomData;
+
 use crate::base_lib::cores::unify_types::FixedName;
 
 /// 包装任意的自定义标签 `PureTag` 赋予其逻辑判断能力
+#[derive(Clone, Debug, PartialEq)]
 pub enum TinyTag<T: FixedName> {
     Always,
     Never,
@@ -41,6 +44,24 @@ pub trait PureTagContainer {
     fn check_condition(&self, pure_tag: &Self::PureTag) -> bool;
 }
 
+/// 空的标签容器，任何标签都不存在，用于不关心标签的场景
+#[derive(Debug)]
+pub struct EmptyTags<T: FixedName>(PhantomData<T>);
+
+impl<T: FixedName> Default for EmptyTags<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: FixedName> PureTagContainer for EmptyTags<T> {
+    type PureTag = T;
+
+    fn check_condition(&self, _pure_tag: &Self::PureTag) -> bool {
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
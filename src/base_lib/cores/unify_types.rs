@@ -31,6 +31,12 @@ pub mod time_type {
         v
     }
 
+    /// 从秒数构造，用于读取配置
+    #[inline(always)]
+    pub const fn from_f64(secs: f64) -> T {
+        secs
+    }
+
     /// WARNING!!! use in test
     #[inline(always)]
     pub const fn unit<const V: u32>() -> T {
@@ -63,6 +69,12 @@ pub mod time_type {
         v.as_secs_f64()
     }
 
+    /// 从秒数构造，用于读取配置（负数或非法值会抛出异常）
+    #[inline(always)]
+    pub fn from_f64(secs: f64) -> T {
+        Duration::from_secs_f64(secs)
+    }
+
     /// WARNING!!! use in test
     #[inline(always)]
     pub const fn unit<const V: u32>() -> T {
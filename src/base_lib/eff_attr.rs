@@ -57,14 +57,24 @@
 
 pub mod upsert_container;
 
+pub mod eff_stores;
+
 pub mod effects;
 
+pub mod effect_specs;
+
+pub mod curve_tables;
+
 pub mod modifiers;
 
+pub mod stack_curves;
+
 pub mod stat_attr_effs;
 
 pub mod stat_attrs;
 
+pub mod stat_attr_hastes;
+
 pub mod bound_attr_effs;
 
 pub mod bound_attrs;
@@ -73,8 +83,25 @@ pub mod bounded_attrs;
 
 pub mod bounded_attr_effs;
 
+pub mod bounded_attr_regens;
+
+pub mod bounded_attr_thresholds;
+
+pub mod bounded_attr_stacks;
+
+pub mod bounded_attr_displays;
+
 pub mod attr_layers;
 
+pub mod interceptors;
+
+pub mod replications;
+
+pub mod validations;
+
+#[cfg(feature = "datadriven")]
+pub mod attr_defs;
+
 // systems
 
 pub mod attr_systems;
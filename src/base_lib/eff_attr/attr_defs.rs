@@ -0,0 +1,1085 @@
+//! 数据驱动的属性与效果定义（需启用 `datadriven` 特性）
+//!
+//! 设计师在数据文件（RON/JSON/TOML）中声明属性和效果模板，无需改代码即可新增属性、调整数值
+//!
+//! - 定义结构（`*Def`）只负责反序列化，运行时类型不依赖 serde
+//! - 加载时统一校验，错误携带出错条目的路径（如 `bounded_attrs[1](health).upper`），便于定位配置
+//! - 所有属性共用一个命名空间，效果模板通过名称指定作用的属性
+//! - 复合属性的层级沿用 [`super::attr_layers`] 的约束：下一层必须为本层 -1
+//!
+//! ```
+//! # use rust_engine_frame::base_lib::eff_attr::attr_defs::{AttrDefs, DefFormat};
+//! let defs = AttrDefs::load(
+//!     r#"{
+//!         "bound_attrs": [{ "name": "health_upper", "origin": 100.0 }],
+//!         "bounded_attrs": [{ "name": "health", "lower": 0.0, "upper": "health_upper" }]
+//!     }"#,
+//!     DefFormat::Json,
+//! )
+//! .unwrap();
+//! let attrs = defs.build_attr_set::<String>().unwrap();
+//! assert_eq!(attrs.bounded_attrs["health"].attr.get_snapshot_value(), 100.0);
+//! ```
+
+use std::fmt::{Debug, Display};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+
+use crate::base_lib::{
+    cores::{
+        timers::static_timer::{StaticTimeline, StaticTimer},
+        unify_types::{FixedName, time_type},
+    },
+    eff_attr::{
+        bound_attr_effs::{BoundAttrCurves, BoundAttrEff, BoundAttrEffType},
+        bound_attrs::BoundAttr,
+        bounded_attrs::BoundedAttr,
+        effects::Effect,
+        stack_curves::StackCurve,
+        stat_attr_effs::{StatAttrCurves, StatAttrEff, StatAttrEffType},
+        stat_attrs::StatAttr,
+        upsert_container::{Upsert, UpsertContainer},
+    },
+};
+
+// region: errors
+
+/// 数据文件格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefFormat {
+    Ron,
+    Json,
+    Toml,
+}
+
+/// 校验失败的原因
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrDefErrorKind {
+    /// 数据文件无法解析，内容为解析器给出的信息（一般包含行列号）
+    Parse(String),
+    /// 名称为空
+    EmptyName,
+    /// 名称重复（属性之间共用命名空间）
+    DuplicateName,
+    /// 引用的属性不存在
+    UnknownAttr(String),
+    /// 数值非法
+    InvalidValue(&'static str),
+    /// 该类型的属性不支持此效果类型
+    UnsupportedEffType,
+    /// 层级关系非法
+    InvalidLayer(&'static str),
+}
+
+/// 定义校验错误，`path` 指向出错的条目
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttrDefError {
+    pub path: String,
+    pub kind: AttrDefErrorKind,
+}
+
+impl AttrDefError {
+    fn new(path: impl Into<String>, kind: AttrDefErrorKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+impl Display for AttrDefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            AttrDefErrorKind::Parse(msg) => write!(f, "parse error: {msg}"),
+            AttrDefErrorKind::EmptyName => write!(f, "{}: empty name", self.path),
+            AttrDefErrorKind::DuplicateName => write!(f, "{}: duplicate name", self.path),
+            AttrDefErrorKind::UnknownAttr(name) => {
+                write!(f, "{}: unknown attr `{name}`", self.path)
+            }
+            AttrDefErrorKind::InvalidValue(reason) => {
+                write!(f, "{}: invalid value, {reason}", self.path)
+            }
+            AttrDefErrorKind::UnsupportedEffType => {
+                write!(f, "{}: eff type unsupported by target attr", self.path)
+            }
+            AttrDefErrorKind::InvalidLayer(reason) => {
+                write!(f, "{}: invalid layer, {reason}", self.path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttrDefError {}
+
+// endregion
+
+// region: defs
+
+/// 聚合曲线的定义，见 [`StackCurve`]
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(tag = "kind")]
+pub enum StackCurveDef {
+    #[default]
+    Linear,
+    Hyperbolic {
+        k: f64,
+    },
+    SoftCap {
+        cap: f64,
+        falloff: f64,
+    },
+    HardCap {
+        cap: f64,
+    },
+}
+
+impl From<StackCurveDef> for StackCurve {
+    fn from(value: StackCurveDef) -> Self {
+        match value {
+            StackCurveDef::Linear => StackCurve::Linear,
+            StackCurveDef::Hyperbolic { k } => StackCurve::Hyperbolic(k),
+            StackCurveDef::SoftCap { cap, falloff } => StackCurve::SoftCap { cap, falloff },
+            StackCurveDef::HardCap { cap } => StackCurve::HardCap(cap),
+        }
+    }
+}
+
+impl StackCurveDef {
+    fn validate(&self, path: &str) -> Result<(), AttrDefError> {
+        let valid = match *self {
+            StackCurveDef::Linear => true,
+            StackCurveDef::Hyperbolic { k } => k.is_finite() && k > 0.0,
+            StackCurveDef::SoftCap { cap, falloff } => {
+                cap.is_finite() && cap >= 0.0 && (0.0..=1.0).contains(&falloff)
+            }
+            StackCurveDef::HardCap { cap } => cap.is_finite() && cap >= 0.0,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(AttrDefError::new(
+                path,
+                AttrDefErrorKind::InvalidValue("curve params out of range"),
+            ))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StatAttrCurvesDef {
+    pub basic_add: StackCurveDef,
+    pub basic_per: StackCurveDef,
+    pub final_per: StackCurveDef,
+    pub final_mul: StackCurveDef,
+}
+
+impl From<StatAttrCurvesDef> for StatAttrCurves {
+    fn from(value: StatAttrCurvesDef) -> Self {
+        Self {
+            basic_add: value.basic_add.into(),
+            basic_per: value.basic_per.into(),
+            final_per: value.final_per.into(),
+            final_mul: value.final_mul.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BoundAttrCurvesDef {
+    pub basic_add: StackCurveDef,
+    pub basic_per: StackCurveDef,
+}
+
+impl From<BoundAttrCurvesDef> for BoundAttrCurves {
+    fn from(value: BoundAttrCurvesDef) -> Self {
+        Self {
+            basic_add: value.basic_add.into(),
+            basic_per: value.basic_per.into(),
+        }
+    }
+}
+
+/// [`StatAttr`] 的定义
+#[derive(Clone, Debug, Deserialize)]
+pub struct StatAttrDef {
+    pub name: String,
+    pub origin: f64,
+    #[serde(default)]
+    pub curves: StatAttrCurvesDef,
+}
+
+/// [`BoundAttr`] 的定义
+#[derive(Clone, Debug, Deserialize)]
+pub struct BoundAttrDef {
+    pub name: String,
+    pub origin: f64,
+    #[serde(default)]
+    pub curves: BoundAttrCurvesDef,
+}
+
+/// 边界：固定数值，或引用某个 [`BoundAttr`] 的名称
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BoundRefDef {
+    Value(f64),
+    Attr(String),
+}
+
+/// [`BoundedAttr`] 的定义
+#[derive(Clone, Debug, Deserialize)]
+pub struct BoundedAttrDef {
+    pub name: String,
+    pub lower: BoundRefDef,
+    pub upper: BoundRefDef,
+    /// 初始值，缺省为上限
+    #[serde(default)]
+    pub init: Option<BoundRefDef>,
+    /// 复合属性的层级，缺省表示不参与复合属性
+    #[serde(default)]
+    pub layer: Option<u8>,
+    /// 复合属性的下一层，缺省表示底层
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// 效果类型的定义，`Override` 的优先级见 [`EffectTemplateDef::priority`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum EffTypeDef {
+    BasicAdd,
+    BasicPer,
+    FinalPer,
+    FinalMul,
+    FloorMin,
+    CapMax,
+    Override,
+}
+
+/// 同名同来源的效果再次施加时的堆叠策略
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum StackPolicy {
+    /// 整体替换（默认），即 [`Upsert::replace`]
+    #[default]
+    Replace,
+    /// 保留旧效果，忽略新效果
+    Keep,
+    /// 效果值累加，持续时间刷新为新效果的
+    AddValue,
+}
+
+impl StackPolicy {
+    /// 按策略施加属性效果
+    pub fn upsert_stat_eff<S: FixedName, Timer>(
+        &self,
+        container: &mut UpsertContainer<StatAttrEff<S, Timer>>,
+        new_eff: StatAttrEff<S, Timer>,
+    ) {
+        self.upsert_with(container, new_eff, StatAttrEff::get_effect_mut);
+    }
+
+    /// 按策略施加属性效果
+    pub fn upsert_bound_eff<S: FixedName, Timer>(
+        &self,
+        container: &mut UpsertContainer<BoundAttrEff<S, Timer>>,
+        new_eff: BoundAttrEff<S, Timer>,
+    ) {
+        self.upsert_with(container, new_eff, BoundAttrEff::get_effect_mut);
+    }
+
+    fn upsert_with<E: Upsert, S>(
+        &self,
+        container: &mut UpsertContainer<E>,
+        new_eff: E,
+        eff_of: fn(&mut E) -> &mut Effect<S>,
+    ) {
+        match self {
+            StackPolicy::Replace => container.upsert_replace(new_eff),
+            StackPolicy::Keep => container.upsert_ele(new_eff, |_, _| {}),
+            StackPolicy::AddValue => container.upsert_ele(new_eff, |old, mut new| {
+                let stacked = eff_of(old).get_effect_value() + eff_of(&mut new).get_effect_value();
+                eff_of(&mut new).set_effect_value(stacked);
+                *old = new;
+            }),
+        }
+    }
+}
+
+/// 效果模板的定义
+#[derive(Clone, Debug, Deserialize)]
+pub struct EffectTemplateDef {
+    /// 效果名称，同时作为生成效果的 `effect_name`
+    pub name: String,
+    /// 作用的属性名称，只能是 [`StatAttr`] 或 [`BoundAttr`]
+    pub target: String,
+    pub eff_type: EffTypeDef,
+    /// 仅 `Override` 使用
+    #[serde(default)]
+    pub priority: u8,
+    pub value: f64,
+    /// 持续秒数，缺省为永久
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub stack: StackPolicy,
+}
+
+/// 数据文件的根结构，各列表均可缺省
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AttrDefs {
+    pub stat_attrs: Vec<StatAttrDef>,
+    pub bound_attrs: Vec<BoundAttrDef>,
+    pub bounded_attrs: Vec<BoundedAttrDef>,
+    pub effects: Vec<EffectTemplateDef>,
+}
+
+// endregion
+
+// region: runtime
+
+/// 解析后的边界
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrBound<S> {
+    Value(f64),
+    Attr(S),
+}
+
+/// 加载的 [`BoundedAttr`] 及其边界、层级关系
+#[derive(Debug)]
+pub struct LoadedBoundedAttr<S: FixedName> {
+    pub attr: BoundedAttr,
+    pub lower: AttrBound<S>,
+    pub upper: AttrBound<S>,
+    pub layer: Option<u8>,
+    pub next: Option<S>,
+}
+
+/// 加载的属性集合
+#[derive(Debug)]
+pub struct AttrSet<S: FixedName> {
+    pub stat_attrs: FxHashMap<S, StatAttr>,
+    pub bound_attrs: FxHashMap<S, BoundAttr>,
+    pub bounded_attrs: FxHashMap<S, LoadedBoundedAttr<S>>,
+}
+
+impl<S: FixedName> AttrSet<S> {
+    /// 读取边界的当前值，引用的属性不存在时为 None （加载的集合不会出现）
+    pub fn resolve_bound(&self, bound: &AttrBound<S>) -> Option<f64> {
+        Self::resolve_bound_in(&self.bound_attrs, bound)
+    }
+
+    fn resolve_bound_in(
+        bound_attrs: &FxHashMap<S, BoundAttr>,
+        bound: &AttrBound<S>,
+    ) -> Option<f64> {
+        match bound {
+            AttrBound::Value(v) => Some(*v),
+            AttrBound::Attr(name) => bound_attrs.get(name).map(BoundAttr::get_current),
+        }
+    }
+
+    /// 按当前边界钳制所有 [`BoundedAttr`] ，在 [`BoundAttr`] 刷新后调用
+    ///
+    /// 边界引用的属性不存在时（如加载后被移除）跳过该属性，返回被跳过的名称
+    pub fn clamp_bounded_attrs(&mut self) -> Vec<S> {
+        let Self {
+            bound_attrs,
+            bounded_attrs,
+            ..
+        } = self;
+        let mut skipped = Vec::new();
+        for (name, loaded) in bounded_attrs.iter_mut() {
+            let lower = Self::resolve_bound_in(bound_attrs, &loaded.lower);
+            let upper = Self::resolve_bound_in(bound_attrs, &loaded.upper);
+            match (lower, upper) {
+                (Some(lower), Some(upper)) => loaded.attr.clamp_by(lower, upper),
+                _ => skipped.push(name.clone()),
+            }
+        }
+        skipped
+    }
+}
+
+/// 效果模板作用的属性类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectTemplateKind {
+    Stat(StatAttrEffType),
+    Bound(BoundAttrEffType),
+}
+
+/// 效果模板，运行时按来源实例化为效果
+#[derive(Clone, Debug)]
+pub struct EffectTemplate<S: FixedName> {
+    pub name: S,
+    pub target: S,
+    pub kind: EffectTemplateKind,
+    pub value: f64,
+    /// None 表示永久
+    pub duration: Option<time_type::T>,
+    pub stack: StackPolicy,
+}
+
+impl<S: FixedName> EffectTemplate<S> {
+    fn gen_timer(&self, timeline: &StaticTimeline) -> StaticTimer {
+        match self.duration {
+            Some(duration) => StaticTimer::new(timeline, duration),
+            None => StaticTimer::inf(),
+        }
+    }
+
+    /// 实例化为 [`StatAttrEff`] ，模板作用于 [`BoundAttr`] 时为 None
+    pub fn build_stat_eff(
+        &self,
+        from_name: S,
+        timeline: &StaticTimeline,
+    ) -> Option<StatAttrEff<S, StaticTimer>> {
+        let EffectTemplateKind::Stat(eff_type) = self.kind else {
+            return None;
+        };
+        Some(StatAttrEff::new(
+            eff_type,
+            Effect::new(from_name, self.name.clone(), self.value),
+            self.gen_timer(timeline),
+        ))
+    }
+
+    /// 实例化为 [`BoundAttrEff`] ，模板作用于 [`StatAttr`] 时为 None
+    pub fn build_bound_eff(
+        &self,
+        from_name: S,
+        timeline: &StaticTimeline,
+    ) -> Option<BoundAttrEff<S, StaticTimer>> {
+        let EffectTemplateKind::Bound(eff_type) = self.kind else {
+            return None;
+        };
+        Some(BoundAttrEff::new(
+            eff_type,
+            Effect::new(from_name, self.name.clone(), self.value),
+            self.gen_timer(timeline),
+        ))
+    }
+}
+
+// endregion
+
+// region: load and validate
+
+fn entry_path(list: &str, idx: usize, name: &str) -> String {
+    format!("{list}[{idx}]({name})")
+}
+
+fn check_finite(path: &str, v: f64) -> Result<(), AttrDefError> {
+    if v.is_finite() {
+        Ok(())
+    } else {
+        Err(AttrDefError::new(
+            path,
+            AttrDefErrorKind::InvalidValue("not a finite number"),
+        ))
+    }
+}
+
+impl AttrDefs {
+    /// 解析并校验
+    pub fn load(text: &str, format: DefFormat) -> Result<Self, AttrDefError> {
+        let parsed = match format {
+            DefFormat::Ron => ron::from_str::<Self>(text).map_err(|e| e.to_string()),
+            DefFormat::Json => serde_json::from_str::<Self>(text).map_err(|e| e.to_string()),
+            DefFormat::Toml => toml::from_str::<Self>(text).map_err(|e| e.to_string()),
+        };
+        let defs = parsed.map_err(|msg| AttrDefError::new("", AttrDefErrorKind::Parse(msg)))?;
+        defs.validate()?;
+        Ok(defs)
+    }
+
+    /// 校验所有条目，返回遇到的第一个错误
+    pub fn validate(&self) -> Result<(), AttrDefError> {
+        let mut names = FxHashSet::default();
+        let mut check_name = |path: &str, name: &str| {
+            if name.is_empty() {
+                Err(AttrDefError::new(path, AttrDefErrorKind::EmptyName))
+            } else if !names.insert(name.to_string()) {
+                Err(AttrDefError::new(path, AttrDefErrorKind::DuplicateName))
+            } else {
+                Ok(())
+            }
+        };
+
+        for (idx, def) in self.stat_attrs.iter().enumerate() {
+            let path = entry_path("stat_attrs", idx, &def.name);
+            check_name(&path, &def.name)?;
+            check_finite(&format!("{path}.origin"), def.origin)?;
+            let curves = &def.curves;
+            for (field, curve) in [
+                ("basic_add", curves.basic_add),
+                ("basic_per", curves.basic_per),
+                ("final_per", curves.final_per),
+                ("final_mul", curves.final_mul),
+            ] {
+                curve.validate(&format!("{path}.curves.{field}"))?;
+            }
+        }
+
+        for (idx, def) in self.bound_attrs.iter().enumerate() {
+            let path = entry_path("bound_attrs", idx, &def.name);
+            check_name(&path, &def.name)?;
+            check_finite(&format!("{path}.origin"), def.origin)?;
+            let curves = &def.curves;
+            for (field, curve) in [
+                ("basic_add", curves.basic_add),
+                ("basic_per", curves.basic_per),
+            ] {
+                curve.validate(&format!("{path}.curves.{field}"))?;
+            }
+        }
+
+        for (idx, def) in self.bounded_attrs.iter().enumerate() {
+            let path = entry_path("bounded_attrs", idx, &def.name);
+            check_name(&path, &def.name)?;
+            self.validate_bounded_attr(&path, def)?;
+        }
+
+        let mut eff_names = FxHashSet::default();
+        for (idx, def) in self.effects.iter().enumerate() {
+            let path = entry_path("effects", idx, &def.name);
+            if def.name.is_empty() {
+                return Err(AttrDefError::new(path, AttrDefErrorKind::EmptyName));
+            }
+            if !eff_names.insert(def.name.as_str()) {
+                return Err(AttrDefError::new(path, AttrDefErrorKind::DuplicateName));
+            }
+            self.effect_kind(&path, def)?;
+            check_finite(&format!("{path}.value"), def.value)?;
+            if let Some(duration) = def.duration
+                && !(duration.is_finite() && duration >= 0.0)
+            {
+                return Err(AttrDefError::new(
+                    format!("{path}.duration"),
+                    AttrDefErrorKind::InvalidValue("duration must be non-negative"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 按初始值解析边界，用于校验上下限关系
+    fn resolve_origin(&self, path: &str, bound: &BoundRefDef) -> Result<f64, AttrDefError> {
+        match bound {
+            BoundRefDef::Value(v) => {
+                check_finite(path, *v)?;
+                Ok(*v)
+            }
+            BoundRefDef::Attr(name) => self
+                .bound_attrs
+                .iter()
+                .find(|def| def.name == *name)
+                .map(|def| def.origin)
+                .ok_or_else(|| {
+                    AttrDefError::new(path, AttrDefErrorKind::UnknownAttr(name.clone()))
+                }),
+        }
+    }
+
+    fn validate_bounded_attr(&self, path: &str, def: &BoundedAttrDef) -> Result<(), AttrDefError> {
+        let lower = self.resolve_origin(&format!("{path}.lower"), &def.lower)?;
+        let upper = self.resolve_origin(&format!("{path}.upper"), &def.upper)?;
+        if lower > upper {
+            return Err(AttrDefError::new(
+                path,
+                AttrDefErrorKind::InvalidValue("lower greater than upper"),
+            ));
+        }
+        if let Some(init) = &def.init {
+            let init_path = format!("{path}.init");
+            let init = self.resolve_origin(&init_path, init)?;
+            if init < lower || init > upper {
+                return Err(AttrDefError::new(
+                    init_path,
+                    AttrDefErrorKind::InvalidValue("init out of bounds"),
+                ));
+            }
+        }
+
+        let Some(next) = &def.next else {
+            return Ok(());
+        };
+        let next_path = format!("{path}.next");
+        let Some(layer) = def.layer else {
+            return Err(AttrDefError::new(
+                next_path,
+                AttrDefErrorKind::InvalidLayer("next requires layer"),
+            ));
+        };
+        let Some(next_def) = self.bounded_attrs.iter().find(|d| d.name == *next) else {
+            return Err(AttrDefError::new(
+                next_path,
+                AttrDefErrorKind::UnknownAttr(next.clone()),
+            ));
+        };
+        if next_def
+            .layer
+            .is_none_or(|next_layer| next_layer + 1 != layer)
+        {
+            return Err(AttrDefError::new(
+                next_path,
+                AttrDefErrorKind::InvalidLayer("next layer must be current layer - 1"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn effect_kind(
+        &self,
+        path: &str,
+        def: &EffectTemplateDef,
+    ) -> Result<EffectTemplateKind, AttrDefError> {
+        if self.stat_attrs.iter().any(|d| d.name == def.target) {
+            let eff_type = match def.eff_type {
+                EffTypeDef::BasicAdd => StatAttrEffType::BasicAdd,
+                EffTypeDef::BasicPer => StatAttrEffType::BasicPer,
+                EffTypeDef::FinalPer => StatAttrEffType::FinalPer,
+                EffTypeDef::FinalMul => StatAttrEffType::FinalMul,
+                EffTypeDef::FloorMin => StatAttrEffType::FloorMin,
+                EffTypeDef::CapMax => StatAttrEffType::CapMax,
+                EffTypeDef::Override => StatAttrEffType::Override(def.priority),
+            };
+            return Ok(EffectTemplateKind::Stat(eff_type));
+        }
+
+        if self.bound_attrs.iter().any(|d| d.name == def.target) {
+            let eff_type = match def.eff_type {
+                EffTypeDef::BasicAdd => BoundAttrEffType::BasicAdd,
+                EffTypeDef::BasicPer => BoundAttrEffType::BasicPer,
+                _ => {
+                    return Err(AttrDefError::new(
+                        format!("{path}.eff_type"),
+                        AttrDefErrorKind::UnsupportedEffType,
+                    ));
+                }
+            };
+            return Ok(EffectTemplateKind::Bound(eff_type));
+        }
+
+        Err(AttrDefError::new(
+            format!("{path}.target"),
+            AttrDefErrorKind::UnknownAttr(def.target.clone()),
+        ))
+    }
+
+    /// 构建属性集合，构建前先校验，直接构造的定义同样能报告错误
+    pub fn build_attr_set<S: FixedName + From<String>>(&self) -> Result<AttrSet<S>, AttrDefError> {
+        self.validate()?;
+        let stat_attrs = self
+            .stat_attrs
+            .iter()
+            .map(|def| {
+                let attr = StatAttr::new_with_curves(def.origin, def.curves.into());
+                (S::from(def.name.clone()), attr)
+            })
+            .collect();
+
+        let bound_attrs = self
+            .bound_attrs
+            .iter()
+            .map(|def| {
+                let attr = BoundAttr::new_with_curves(def.origin, def.curves.into());
+                (S::from(def.name.clone()), attr)
+            })
+            .collect();
+
+        let to_bound = |bound: &BoundRefDef| match bound {
+            BoundRefDef::Value(v) => AttrBound::Value(*v),
+            BoundRefDef::Attr(name) => AttrBound::Attr(S::from(name.clone())),
+        };
+        let bounded_attrs = self
+            .bounded_attrs
+            .iter()
+            .enumerate()
+            .map(|(idx, def)| {
+                let path = entry_path("bounded_attrs", idx, &def.name);
+                let init = match &def.init {
+                    Some(init) => self.resolve_origin(&format!("{path}.init"), init)?,
+                    None => self.resolve_origin(&format!("{path}.upper"), &def.upper)?,
+                };
+                let loaded = LoadedBoundedAttr {
+                    attr: BoundedAttr::new(init),
+                    lower: to_bound(&def.lower),
+                    upper: to_bound(&def.upper),
+                    layer: def.layer,
+                    next: def.next.clone().map(S::from),
+                };
+                Ok((S::from(def.name.clone()), loaded))
+            })
+            .collect::<Result<_, AttrDefError>>()?;
+
+        Ok(AttrSet {
+            stat_attrs,
+            bound_attrs,
+            bounded_attrs,
+        })
+    }
+
+    /// 构建效果模板，以模板名称为键，需先通过校验（[`Self::load`] 已校验）
+    pub fn build_effect_templates<S: FixedName + From<String>>(
+        &self,
+    ) -> FxHashMap<S, EffectTemplate<S>> {
+        self.effects
+            .iter()
+            .filter_map(|def| {
+                let kind = self.effect_kind("", def).ok()?;
+                let template = EffectTemplate {
+                    name: S::from(def.name.clone()),
+                    target: S::from(def.target.clone()),
+                    kind,
+                    value: def.value,
+                    duration: def.duration.map(time_type::from_f64),
+                    stack: def.stack,
+                };
+                Some((template.name.clone(), template))
+            })
+            .collect()
+    }
+}
+
+// endregion
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::tiny_timer::{HasTimer, Tickable, TimerView};
+
+    use super::*;
+
+    const JSON_DEFS: &str = r#"{
+        "stat_attrs": [
+            { "name": "strength", "origin": 10.0 },
+            { "name": "crit", "origin": 0.05,
+              "curves": { "basic_add": { "kind": "Hyperbolic", "k": 1.0 } } }
+        ],
+        "bound_attrs": [
+            { "name": "health_upper", "origin": 100.0 },
+            { "name": "shield_upper", "origin": 50.0 }
+        ],
+        "bounded_attrs": [
+            { "name": "health", "lower": 0.0, "upper": "health_upper", "layer": 0 },
+            { "name": "shield", "lower": 0.0, "upper": "shield_upper", "init": 0.0,
+              "layer": 1, "next": "health" }
+        ],
+        "effects": [
+            { "name": "might", "target": "strength", "eff_type": "BasicAdd", "value": 5.0,
+              "duration": 3.0, "stack": "AddValue" },
+            { "name": "fortify", "target": "health_upper", "eff_type": "BasicPer", "value": 0.2 }
+        ]
+    }"#;
+
+    const RON_DEFS: &str = r#"(
+        stat_attrs: [
+            (name: "strength", origin: 10.0),
+            (name: "crit", origin: 0.05, curves: (basic_add: (kind: "Hyperbolic", k: 1.0))),
+        ],
+        bound_attrs: [
+            (name: "health_upper", origin: 100.0),
+            (name: "shield_upper", origin: 50.0),
+        ],
+        bounded_attrs: [
+            (name: "health", lower: 0.0, upper: "health_upper", layer: Some(0)),
+            (name: "shield", lower: 0.0, upper: "shield_upper", init: Some(0.0),
+             layer: Some(1), next: Some("health")),
+        ],
+        effects: [
+            (name: "might", target: "strength", eff_type: BasicAdd, value: 5.0,
+             duration: Some(3.0), stack: AddValue),
+            (name: "fortify", target: "health_upper", eff_type: BasicPer, value: 0.2),
+        ],
+    )"#;
+
+    const TOML_DEFS: &str = r#"
+        [[stat_attrs]]
+        name = "strength"
+        origin = 10.0
+
+        [[stat_attrs]]
+        name = "crit"
+        origin = 0.05
+        curves.basic_add = { kind = "Hyperbolic", k = 1.0 }
+
+        [[bound_attrs]]
+        name = "health_upper"
+        origin = 100.0
+
+        [[bound_attrs]]
+        name = "shield_upper"
+        origin = 50.0
+
+        [[bounded_attrs]]
+        name = "health"
+        lower = 0.0
+        upper = "health_upper"
+        layer = 0
+
+        [[bounded_attrs]]
+        name = "shield"
+        lower = 0.0
+        upper = "shield_upper"
+        init = 0.0
+        layer = 1
+        next = "health"
+
+        [[effects]]
+        name = "might"
+        target = "strength"
+        eff_type = "BasicAdd"
+        value = 5.0
+        duration = 3.0
+        stack = "AddValue"
+
+        [[effects]]
+        name = "fortify"
+        target = "health_upper"
+        eff_type = "BasicPer"
+        value = 0.2
+    "#;
+
+    fn load_err(text: &str) -> AttrDefError {
+        AttrDefs::load(text, DefFormat::Json).unwrap_err()
+    }
+
+    /// 三种格式加载同一份定义，结果一致
+    #[test]
+    fn test_load_all_formats() {
+        for (text, format) in [
+            (JSON_DEFS, DefFormat::Json),
+            (RON_DEFS, DefFormat::Ron),
+            (TOML_DEFS, DefFormat::Toml),
+        ] {
+            let defs = AttrDefs::load(text, format).unwrap();
+            let attrs = defs.build_attr_set::<String>().unwrap();
+            assert_eq!(attrs.stat_attrs["strength"].get_current(), 10.0);
+            assert_eq!(attrs.bound_attrs["health_upper"].get_current(), 100.0);
+
+            let health = &attrs.bounded_attrs["health"];
+            assert_eq!(health.attr.get_snapshot_value(), 100.0);
+            assert_eq!(health.upper, AttrBound::Attr("health_upper".to_string()));
+            let shield = &attrs.bounded_attrs["shield"];
+            assert_eq!(shield.attr.get_snapshot_value(), 0.0);
+            assert_eq!(shield.next.as_deref(), Some("health"));
+
+            let templates = defs.build_effect_templates::<String>();
+            assert_eq!(
+                templates["might"].kind,
+                EffectTemplateKind::Stat(StatAttrEffType::BasicAdd)
+            );
+            assert_eq!(templates["might"].stack, StackPolicy::AddValue);
+            assert_eq!(
+                templates["fortify"].kind,
+                EffectTemplateKind::Bound(BoundAttrEffType::BasicPer)
+            );
+            assert!(templates["fortify"].duration.is_none());
+        }
+    }
+
+    /// 曲线定义生效
+    #[test]
+    fn test_curves_applied() {
+        let defs = AttrDefs::load(JSON_DEFS, DefFormat::Json).unwrap();
+        let mut attrs = defs.build_attr_set::<String>().unwrap();
+        let timeline = StaticTimeline::new();
+
+        let mut effs = UpsertContainer::default();
+        let eff = StatAttrEff::new(
+            StatAttrEffType::BasicAdd,
+            Effect::new("player".to_string(), "lucky".to_string(), 1.0),
+            StaticTimer::new(&timeline, time_type::unit::<1>()),
+        );
+        effs.upsert_replace(eff);
+
+        let crit = attrs.stat_attrs.get_mut("crit").unwrap();
+        crit.refresh_value(effs.iter_ele());
+        // 0.05 + 1 / (1 + 1)
+        assert!((crit.get_current() - 0.55).abs() < 1e-9);
+    }
+
+    /// 模板实例化与堆叠策略
+    #[test]
+    fn test_template_instantiate_and_stack() {
+        let defs = AttrDefs::load(JSON_DEFS, DefFormat::Json).unwrap();
+        let templates = defs.build_effect_templates::<String>();
+        let mut timeline = StaticTimeline::new();
+
+        let might = &templates["might"];
+        assert!(might.build_bound_eff("p".to_string(), &timeline).is_none());
+
+        let mut effs = UpsertContainer::default();
+        let eff = might.build_stat_eff("p".to_string(), &timeline).unwrap();
+        might.stack.upsert_stat_eff(&mut effs, eff);
+        timeline.0.tick(time_type::unit::<2>());
+        let eff = might.build_stat_eff("p".to_string(), &timeline).unwrap();
+        might.stack.upsert_stat_eff(&mut effs, eff);
+
+        assert_eq!(effs.ele_len(), 1);
+        let stacked = effs.iter_ele().next().unwrap();
+        assert_eq!(stacked.get_effect().get_effect_value(), 10.0);
+        // 持续时间刷新为新效果的
+        timeline.0.tick(time_type::unit::<2>());
+        assert!(!stacked.get_timer().is_completed(&timeline));
+
+        let eff = might.build_stat_eff("p".to_string(), &timeline).unwrap();
+        StackPolicy::Keep.upsert_stat_eff(&mut effs, eff);
+        let kept = effs.iter_ele().next().unwrap();
+        assert_eq!(kept.get_effect().get_effect_value(), 10.0);
+
+        let fortify = &templates["fortify"];
+        let mut bound_effs = UpsertContainer::default();
+        let eff = fortify.build_bound_eff("p".to_string(), &timeline).unwrap();
+        fortify.stack.upsert_bound_eff(&mut bound_effs, eff);
+
+        let mut attrs = defs.build_attr_set::<String>().unwrap();
+        let upper = attrs.bound_attrs.get_mut("health_upper").unwrap();
+        upper.refresh_value(bound_effs.iter_ele());
+        assert_eq!(upper.get_current(), 120.0);
+    }
+
+    /// 边界随上限属性刷新而钳制
+    #[test]
+    fn test_clamp_bounded_attrs() {
+        let defs = AttrDefs::load(JSON_DEFS, DefFormat::Json).unwrap();
+        let mut attrs = defs.build_attr_set::<String>().unwrap();
+
+        let mut effs: UpsertContainer<BoundAttrEff<String, StaticTimer>> =
+            UpsertContainer::default();
+        effs.upsert_replace(BoundAttrEff::new(
+            BoundAttrEffType::BasicAdd,
+            Effect::new("p".to_string(), "curse".to_string(), -40.0),
+            StaticTimer::inf(),
+        ));
+        let upper = attrs.bound_attrs.get_mut("health_upper").unwrap();
+        upper.refresh_value(effs.iter_ele());
+
+        let health = &attrs.bounded_attrs["health"];
+        assert_eq!(attrs.resolve_bound(&health.upper), Some(60.0));
+        assert!(attrs.clamp_bounded_attrs().is_empty());
+        let health = &mut attrs.bounded_attrs.get_mut("health").unwrap().attr;
+        health.commit_pending_value();
+        assert_eq!(health.get_snapshot_value(), 60.0);
+
+        // 引用的上限被移除：跳过而非 panic
+        attrs.bound_attrs.remove("shield_upper");
+        assert_eq!(attrs.clamp_bounded_attrs(), vec!["shield".to_string()]);
+    }
+
+    /// 直接构造的定义未经加载，构建时同样报告错误
+    #[test]
+    fn test_build_validates() {
+        let mut defs = AttrDefs::load(JSON_DEFS, DefFormat::Json).unwrap();
+        defs.bound_attrs.retain(|def| def.name != "shield_upper");
+        let err = defs.build_attr_set::<String>().unwrap_err();
+        assert_eq!(
+            err,
+            AttrDefError::new(
+                "bounded_attrs[1](shield).upper",
+                AttrDefErrorKind::UnknownAttr("shield_upper".to_string())
+            )
+        );
+    }
+
+    /// 解析失败
+    #[test]
+    fn test_parse_error() {
+        let err = load_err(r#"{ "stat_attrs": [{ "name": "a" }] }"#);
+        assert!(matches!(err.kind, AttrDefErrorKind::Parse(_)));
+
+        let err = AttrDefs::load("stat_attrs = 1", DefFormat::Toml).unwrap_err();
+        assert!(matches!(err.kind, AttrDefErrorKind::Parse(_)));
+    }
+
+    /// 校验错误指向出错条目
+    #[test]
+    fn test_validate_errors_point_at_entry() {
+        let err = load_err(
+            r#"{ "stat_attrs": [{ "name": "a", "origin": 1.0 }],
+                 "bound_attrs": [{ "name": "a", "origin": 1.0 }] }"#,
+        );
+        assert_eq!(
+            err,
+            AttrDefError::new("bound_attrs[0](a)", AttrDefErrorKind::DuplicateName)
+        );
+
+        let err = load_err(
+            r#"{ "bounded_attrs": [{ "name": "hp", "lower": 0.0, "upper": "hp_upper" }] }"#,
+        );
+        assert_eq!(
+            err,
+            AttrDefError::new(
+                "bounded_attrs[0](hp).upper",
+                AttrDefErrorKind::UnknownAttr("hp_upper".to_string())
+            )
+        );
+
+        let err = load_err(
+            r#"{ "bounded_attrs": [{ "name": "hp", "lower": 0.0, "upper": 10.0, "init": 20.0 }] }"#,
+        );
+        assert_eq!(err.path, "bounded_attrs[0](hp).init");
+
+        let err = load_err(
+            r#"{ "stat_attrs": [{ "name": "a", "origin": 1.0,
+                 "curves": { "final_mul": { "kind": "SoftCap", "cap": 1.0, "falloff": 2.0 } } }] }"#,
+        );
+        assert_eq!(err.path, "stat_attrs[0](a).curves.final_mul");
+    }
+
+    /// 层级：下一层必须为本层 -1
+    #[test]
+    fn test_validate_layers() {
+        let err = load_err(
+            r#"{ "bounded_attrs": [
+                { "name": "hp", "lower": 0.0, "upper": 10.0, "layer": 0 },
+                { "name": "shield", "lower": 0.0, "upper": 10.0, "layer": 2, "next": "hp" }
+            ] }"#,
+        );
+        assert_eq!(err.path, "bounded_attrs[1](shield).next");
+        assert!(matches!(err.kind, AttrDefErrorKind::InvalidLayer(_)));
+
+        let err = load_err(
+            r#"{ "bounded_attrs": [
+                { "name": "shield", "lower": 0.0, "upper": 10.0, "next": "hp" }
+            ] }"#,
+        );
+        assert!(matches!(err.kind, AttrDefErrorKind::InvalidLayer(_)));
+    }
+
+    /// 效果模板：目标不存在、目标不支持该效果类型
+    #[test]
+    fn test_validate_effects() {
+        let err = load_err(
+            r#"{ "effects": [{ "name": "e", "target": "nope", "eff_type": "BasicAdd", "value": 1.0 }] }"#,
+        );
+        assert_eq!(
+            err,
+            AttrDefError::new(
+                "effects[0](e).target",
+                AttrDefErrorKind::UnknownAttr("nope".to_string())
+            )
+        );
+
+        let err = load_err(
+            r#"{ "bound_attrs": [{ "name": "u", "origin": 1.0 }],
+                 "effects": [{ "name": "e", "target": "u", "eff_type": "FinalMul", "value": 2.0 }] }"#,
+        );
+        assert_eq!(
+            err,
+            AttrDefError::new(
+                "effects[0](e).eff_type",
+                AttrDefErrorKind::UnsupportedEffType
+            )
+        );
+
+        let err = load_err(
+            r#"{ "stat_attrs": [{ "name": "s", "origin": 1.0 }],
+                 "effects": [{ "name": "e", "target": "s", "eff_type": "Override", "value": 0.0,
+                               "duration": -1.0 }] }"#,
+        );
+        assert_eq!(err.path, "effects[0](e).duration");
+    }
+}
@@ -34,6 +34,11 @@
 
 use std::fmt::Debug;
 
+use crate::base_lib::{
+    cores::attr_nums::{AttrNum, RoundingMode},
+    eff_attr::bounded_attrs::BoundedAttr,
+};
+
 /// 自定义复合属性的层级类型
 pub trait AttrLayerType: Debug + Copy + Eq {
     /// 指向自己说明是底层
@@ -50,6 +55,21 @@ pub trait AttrLayerEffTarget: Debug + Copy + Eq {
     fn stop_at(&self) -> Self::Layer;
 }
 
+/// 复合属性的属性池，由业务提供每一层对应的有界属性及其上下限
+///
+/// 用于 [`attr_layer_system::cascade_layer_val`] 等通用的逐层传递计算，新增多层资源（法力护盾、弹匣与备弹）时只需实现此特征
+///
+/// 各层共用同一数值类型 `N` ，默认为 [`f64`]
+pub trait AttrLayerPool<L: AttrLayerType, N: AttrNum = f64> {
+    /// 返回该层的有界属性，以及下限、上限
+    fn get_layer_attr(&mut self, layer: L) -> (&mut BoundedAttr<N>, f64, f64);
+
+    /// 该层的吸收比例，取值 `[0, 1]` ，未被吸收的部分直接传递到下一层（如奥术护盾吸收 70% ，30% 渗透到生命）
+    fn get_absorb_ratio(&self, _layer: L) -> f64 {
+        1.0
+    }
+}
+
 pub struct AttrLayerTypeIter<T: AttrLayerType> {
     current: Option<T>,
 }
@@ -157,4 +177,205 @@ pub mod attr_layer_system {
             b_stop_layer.cmp(&a_stop_layer)
         }
     }
+
+    /// 沿给定的层级路径逐层应用效果值，每层应用后钳制，溢出部分传递到下一层
+    ///
+    /// - `on_delta` 接收每层的实际变化值（包括为零的层）
+    /// - 返回最终未被吸收的溢出值
+    pub fn cascade_layer_val<L, P, N>(
+        pool: &mut P,
+        layers: impl Iterator<Item = L>,
+        val: f64,
+        on_delta: impl FnMut(L, f64),
+    ) -> f64
+    where
+        L: AttrLayerType,
+        P: AttrLayerPool<L, N>,
+        N: AttrNum,
+    {
+        cascade_layer_val_penetrated(pool, layers, val, |_| 0.0, on_delta)
+    }
+
+    /// 同 [`cascade_layer_val`] ，额外考虑每层的吸收比例与效果的穿透比例
+    ///
+    /// 每层实际尝试作用的值为 `剩余值 * (1 - 穿透) * 吸收比例` ，其余部分连同溢出一起传递到下一层；
+    /// 路径的最后一层无处渗透，因此忽略吸收比例（穿透仍然生效）；
+    /// 只改变每层的作用量，不改变层级路径与效果之间的顺序
+    ///
+    /// 每层的作用量按 [`RoundingMode::Nearest`] 落回属性的数值类型，传递到下一层的溢出按该层钳制后的实际变化值计算
+    pub fn cascade_layer_val_penetrated<L, P, N>(
+        pool: &mut P,
+        layers: impl Iterator<Item = L>,
+        val: f64,
+        penetration: impl Fn(L) -> f64,
+        mut on_delta: impl FnMut(L, f64),
+    ) -> f64
+    where
+        L: AttrLayerType,
+        P: AttrLayerPool<L, N>,
+        N: AttrNum,
+    {
+        let mut remaining = val;
+        let mut layers = layers.peekable();
+        while let Some(layer) = layers.next() {
+            let absorb = if layers.peek().is_some() {
+                pool.get_absorb_ratio(layer).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let pierce = penetration(layer).clamp(0.0, 1.0);
+            let attempt = remaining * (1.0 - pierce) * absorb;
+
+            let (attr, lower, upper) = pool.get_layer_attr(layer);
+            let old_val = attr.get_pending_value();
+            attr.apply_eff(N::from_f64(attempt, RoundingMode::Nearest));
+            attr.clamp_by(lower, upper);
+            let diff_val = (attr.get_pending_value() - old_val).to_f64();
+            remaining -= diff_val;
+            on_delta(layer, diff_val);
+        }
+        remaining
+    }
+
+    /// 对一组复合属性效果，按 [`rank_attr_layer_eff`] 排序后逐个传递计算，返回每层的累计变化值（按首次涉及的顺序）
+    ///
+    /// 同类效果应预先合并，理由见模块文档
+    pub fn resolve_layer_effs<T, P, N>(pool: &mut P, effs: &mut [(T, f64)]) -> Vec<(T::Layer, f64)>
+    where
+        T: AttrLayerEffTarget,
+        P: AttrLayerPool<T::Layer, N>,
+        N: AttrNum,
+    {
+        effs.sort_by(|(a, _), (b, _)| rank_attr_layer_eff(a, b));
+
+        let mut deltas: Vec<(T::Layer, f64)> = Vec::new();
+        for (target, val) in effs.iter() {
+            let layers = AttrLayerEffTargetIter::from(*target);
+            cascade_layer_val(pool, layers, *val, |layer, diff_val| {
+                if let Some((_, sum)) = deltas.iter_mut().find(|(l, _)| *l == layer) {
+                    *sum += diff_val;
+                } else {
+                    deltas.push((layer, diff_val));
+                }
+            });
+        }
+        deltas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attr_layer_system::resolve_layer_effs, *};
+
+    /// 法力护盾 → 生命
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum ManaLayer {
+        Life,
+        ManaShield,
+    }
+
+    impl AttrLayerType for ManaLayer {
+        fn get_next(&self) -> Self {
+            Self::Life
+        }
+
+        fn get_layer(&self) -> u8 {
+            match self {
+                Self::Life => 0,
+                Self::ManaShield => 1,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum ManaTarget {
+        /// 先扣护盾再扣生命
+        Normal,
+        /// 只扣护盾
+        ShieldBreak,
+    }
+
+    impl AttrLayerEffTarget for ManaTarget {
+        type Layer = ManaLayer;
+
+        fn start_at(&self) -> Self::Layer {
+            ManaLayer::ManaShield
+        }
+
+        fn stop_at(&self) -> Self::Layer {
+            match self {
+                Self::Normal => ManaLayer::Life,
+                Self::ShieldBreak => ManaLayer::ManaShield,
+            }
+        }
+    }
+
+    struct Pool {
+        life: BoundedAttr,
+        shield: BoundedAttr,
+    }
+
+    impl AttrLayerPool<ManaLayer> for Pool {
+        fn get_layer_attr(&mut self, layer: ManaLayer) -> (&mut BoundedAttr, f64, f64) {
+            match layer {
+                ManaLayer::Life => (&mut self.life, 0.0, 100.0),
+                ManaLayer::ManaShield => (&mut self.shield, 0.0, 50.0),
+            }
+        }
+    }
+
+    /// 按底层排序后传递：破盾效果先算，普通伤害溢出到生命
+    #[test]
+    fn test_resolve_layer_effs() {
+        let mut pool = Pool {
+            life: BoundedAttr::new(100.0),
+            shield: BoundedAttr::new(50.0),
+        };
+        let mut effs = [
+            (ManaTarget::Normal, -40.0),
+            (ManaTarget::ShieldBreak, -30.0),
+        ];
+        let deltas = resolve_layer_effs(&mut pool, &mut effs);
+
+        assert_eq!(effs[0].0, ManaTarget::ShieldBreak);
+        assert_eq!(pool.shield.get_pending_value(), 0.0);
+        assert_eq!(pool.life.get_pending_value(), 80.0);
+        assert_eq!(
+            deltas,
+            vec![(ManaLayer::ManaShield, -50.0), (ManaLayer::Life, -20.0)]
+        );
+    }
+
+    struct IntPool {
+        life: BoundedAttr<i64>,
+        shield: BoundedAttr<i64>,
+    }
+
+    impl AttrLayerPool<ManaLayer, i64> for IntPool {
+        fn get_layer_attr(&mut self, layer: ManaLayer) -> (&mut BoundedAttr<i64>, f64, f64) {
+            match layer {
+                ManaLayer::Life => (&mut self.life, 0.0, 100.0),
+                ManaLayer::ManaShield => (&mut self.shield, 0.0, 50.0),
+            }
+        }
+    }
+
+    /// 整数属性每层按四舍五入作用，溢出按钳制后的实际变化值传递
+    #[test]
+    fn test_cascade_integer_layers() {
+        let mut pool = IntPool {
+            life: BoundedAttr::new(100),
+            shield: BoundedAttr::new(5),
+        };
+        let overflow = attr_layer_system::cascade_layer_val(
+            &mut pool,
+            AttrLayerEffTargetIter::from(ManaTarget::Normal),
+            -7.6,
+            |_, _| {},
+        );
+
+        assert_eq!(pool.shield.get_pending_value(), 0);
+        assert_eq!(pool.life.get_pending_value(), 97);
+        assert!((overflow - 0.4).abs() < 1e-9);
+    }
 }
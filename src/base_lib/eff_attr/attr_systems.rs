@@ -14,27 +14,35 @@
 //! - “根据结果决定是否生效”的效果，在后面计算，并且应该要求他们的顺序是确定的
 //!   - 每次预判断结果时，都应结合上下限考虑，并且生效后应用钳制
 //! - 最后提交有界属性的本次修改，作为下一帧快照值
+//!
+//! 效果容器均对 [`EffStore`] 泛型，可按效果数量选择存储后端；属性均对数值类型 [`AttrNum`] 泛型
 
 use crate::base_lib::{
     cores::{
+        attr_nums::AttrNum,
         timers::{
+            charge_counters::{ChargeCounter, ChargeEvent},
             static_timer::{StaticTimeline, StaticTimer},
-            tiny_timer::{HasTimer, TimerView},
+            tiny_timer::{HasTimer, TimerRescale, TimerView},
         },
+        tiny_tags::PureTagContainer,
         unify_types::{FixedName, time_type},
     },
     eff_attr::{
         bound_attr_effs::BoundAttrEff,
         bound_attrs::BoundAttr,
-        bounded_attrs::BoundedAttr,
+        bounded_attr_thresholds::{AttrThresholds, ThresholdCrossing},
+        bounded_attrs::{BoundSide, BoundValue, BoundedAttr, ReboundEvent, ReboundPolicy},
+        eff_stores::EffStore,
+        effects::ConditionalEffect,
         stat_attr_effs::StatAttrEff,
         stat_attrs::StatAttr,
-        upsert_container::{Upsert, UpsertContainer, UpsertContainerCleaner},
+        upsert_container::{Upsert, UpsertContainerCleaner},
     },
 };
 
 /// 老化过期元素
-pub fn clean_expired_element<'a, E, Ctx>(ll: &mut UpsertContainer<E>, ctx: Ctx)
+pub fn clean_expired_element<'a, E, Ctx>(ll: &mut impl EffStore<E>, ctx: Ctx)
 where
     Ctx: Copy,
     E: Upsert + HasTimer,
@@ -43,10 +51,68 @@ where
     ll.delete_ele(|ele| ele.get_timer().is_completed(ctx));
 }
 
+/// 按事件消耗次数效果，返回消耗的效果个数
+///
+/// 耗尽的效果不在此删除，与计时效果一样由 [`clean_expired_element`] 清理
+pub fn consume_charge_element<E>(ll: &mut impl EffStore<E>, event: ChargeEvent) -> usize
+where
+    E: Upsert + HasTimer<Timer = ChargeCounter>,
+{
+    // 先只读检查，次数变化不影响效果值，避免无事件时置脏
+    let should_consume = |ele: &E| {
+        let counter = ele.get_timer();
+        counter.is_consumed_by(event) && !counter.is_completed(())
+    };
+    if !ll.iter_ele().any(should_consume) {
+        return 0;
+    }
+
+    ll.iter_mut()
+        .map(|ele| ele.get_timer_mut().try_consume(event))
+        .filter(|consumed| *consumed)
+        .count()
+}
+
+/// 按比例缩放效果的持续时间，比例由 [`HasteTracker::try_update`] 给出
+///
+/// 同一急速属性驱动多个容器时，先更新一次再逐个缩放
+///
+/// [`HasteTracker::try_update`]: crate::base_lib::eff_attr::stat_attr_hastes::HasteTracker::try_update
+pub fn rescale_element_timer<'a, E, Ctx>(ll: &mut impl EffStore<E>, ctx: Ctx, ratio: f64)
+where
+    Ctx: Copy,
+    E: Upsert + HasTimer,
+    <E as HasTimer>::Timer: TimerRescale<Ctx<'a> = Ctx>,
+{
+    for ele in ll.iter_mut() {
+        ele.get_timer_mut().rescale(ctx, ratio);
+    }
+}
+
+/// 按标签重新求值效果的生效条件，有条件翻转时更新缓存并置脏
+fn try_flip_conditions<S, E>(effs: &mut impl EffStore<E>, tags: &impl PureTagContainer<PureTag = S>)
+where
+    S: FixedName,
+    E: Upsert + ConditionalEffect<S>,
+{
+    // 先只读检查，避免每帧可变遍历置脏
+    if !effs.iter_ele().any(|ele| ele.is_condition_flipped(tags)) {
+        return;
+    }
+
+    for ele in effs.iter_mut() {
+        if let Some(met) = ele.get_condition().map(|cond| cond.check_condition(tags)) {
+            ele.set_condition_met(met);
+        }
+    }
+}
+
 /// 刷新 [`StatAttr`] 脏属性，应在帧开头触发
-pub fn try_refresh_dirty_stat_attr<S: FixedName, Timer>(
-    attr: &mut StatAttr,
-    effs: &mut UpsertContainer<StatAttrEff<S, Timer>>,
+///
+/// 不求值生效条件，带条件的效果保持上次求值结果，见 [`try_refresh_dirty_stat_attr_with_tags`]
+pub fn try_refresh_dirty_stat_attr<S: FixedName, Timer, T: FixedName, N: AttrNum>(
+    attr: &mut StatAttr<N>,
+    effs: &mut impl EffStore<StatAttrEff<S, Timer, T>>,
 ) {
     if effs.is_changed() {
         effs.reset_changed_flag();
@@ -55,10 +121,24 @@ pub fn try_refresh_dirty_stat_attr<S: FixedName, Timer>(
     }
 }
 
+/// 刷新 [`StatAttr`] 脏属性，应在帧开头触发
+///
+/// 先按单位的标签求值效果的生效条件，条件翻转同样视为脏
+pub fn try_refresh_dirty_stat_attr_with_tags<S: FixedName, Timer, T: FixedName, N: AttrNum>(
+    attr: &mut StatAttr<N>,
+    effs: &mut impl EffStore<StatAttrEff<S, Timer, T>>,
+    tags: &impl PureTagContainer<PureTag = T>,
+) {
+    try_flip_conditions(effs, tags);
+    try_refresh_dirty_stat_attr(attr, effs);
+}
+
 /// 刷新 [`BoundAttr`] 脏属性，应在帧开头触发
-pub fn try_refresh_dirty_bound_attr<S: FixedName, Timer>(
-    attr: &mut BoundAttr,
-    effs: &mut UpsertContainer<BoundAttrEff<S, Timer>>,
+///
+/// 不求值生效条件，带条件的效果保持上次求值结果，见 [`try_refresh_dirty_bound_attr_with_tags`]
+pub fn try_refresh_dirty_bound_attr<S: FixedName, Timer, T: FixedName, N: AttrNum>(
+    attr: &mut BoundAttr<N>,
+    effs: &mut impl EffStore<BoundAttrEff<S, Timer, T>>,
 ) {
     if effs.is_changed() {
         effs.reset_changed_flag();
@@ -67,12 +147,74 @@ pub fn try_refresh_dirty_bound_attr<S: FixedName, Timer>(
     }
 }
 
+/// 刷新 [`BoundAttr`] 脏属性，应在帧开头触发
+///
+/// 先按单位的标签求值效果的生效条件，条件翻转同样视为脏
+pub fn try_refresh_dirty_bound_attr_with_tags<S: FixedName, Timer, T: FixedName, N: AttrNum>(
+    attr: &mut BoundAttr<N>,
+    effs: &mut impl EffStore<BoundAttrEff<S, Timer, T>>,
+    tags: &impl PureTagContainer<PureTag = T>,
+) {
+    try_flip_conditions(effs, tags);
+    try_refresh_dirty_bound_attr(attr, effs);
+}
+
+/// 刷新 [`BoundAttr`] 脏属性，并按策略修正受其约束的 [`BoundedAttr`] ，应在帧开头触发
+///
+/// - `side` 为 `attr` 作为上限还是下限， `opposite` 为另一侧的约束值
+/// - 约束值未变化时不做修正；当前值被修正时返回事件，如护盾到期时护盾值被清除
+pub fn try_refresh_dirty_bound_attr_rebound<S, Timer, T, N, V>(
+    attr: &mut BoundAttr<N>,
+    effs: &mut impl EffStore<BoundAttrEff<S, Timer, T>>,
+    tags: &impl PureTagContainer<PureTag = T>,
+    side: BoundSide,
+    opposite: V,
+    bounded: &mut BoundedAttr<N>,
+    policy: ReboundPolicy,
+) -> Option<ReboundEvent<N>>
+where
+    S: FixedName,
+    T: FixedName,
+    N: AttrNum,
+    BoundValue: From<V>,
+{
+    let old_bound = attr.get_current();
+    try_refresh_dirty_bound_attr_with_tags(attr, effs, tags);
+    let new_bound = attr.get_current();
+    if old_bound == new_bound {
+        return None;
+    }
+
+    let opposite = BoundValue::from(opposite).get_value();
+    let (old_bound, new_bound) = (old_bound.to_f64(), new_bound.to_f64());
+    let (old, new) = match side {
+        BoundSide::Upper => ((opposite, old_bound), (opposite, new_bound)),
+        BoundSide::Lower => ((old_bound, opposite), (new_bound, opposite)),
+    };
+    bounded.rebound_by(policy, old, new)
+}
+
 /// 提交有界属性的修改
 #[inline]
-pub fn do_commit_bounded_attr(attr: &mut BoundedAttr) {
+pub fn do_commit_bounded_attr<N: AttrNum>(attr: &mut BoundedAttr<N>) {
     attr.commit_pending_value();
 }
 
+/// 提交有界属性的修改，返回本帧穿越的阈值（如平衡清空、低血量）
+#[inline]
+pub fn do_commit_bounded_attr_watched<S, N, V>(
+    attr: &mut BoundedAttr<N>,
+    thresholds: &mut AttrThresholds<S>,
+    upper: V,
+) -> Vec<ThresholdCrossing<S>>
+where
+    S: FixedName,
+    N: AttrNum,
+    BoundValue: From<V>,
+{
+    thresholds.commit_watched(attr, upper)
+}
+
 /// 清理容器空洞【规整处理，业务无关】(薄委托于 [`UpsertContainerCleaner::clean_holes`])
 ///
 /// ```
@@ -92,9 +234,9 @@ pub fn do_commit_bounded_attr(attr: &mut BoundedAttr) {
 /// let effs = attr_effs.iter_mut().map(|(_, effs)| &mut **effs); // 无法 Cpoy `&mut` 手动解引用 `&mut *`
 /// try_clean_hole(delta, effs, &mut cleaner);
 /// ```
-pub fn try_clean_hole<'a, E: Upsert + 'a>(
+pub fn try_clean_hole<'a, E: Upsert + 'a, C: EffStore<E> + 'a>(
     delta: time_type::T,
-    ll: impl Iterator<Item = &'a mut UpsertContainer<E>>,
+    ll: impl Iterator<Item = &'a mut C>,
     cleaner: &mut UpsertContainerCleaner,
 ) {
     cleaner.clean_holes(delta, ll);
@@ -116,7 +258,16 @@ pub fn try_reset_timeline<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::base_lib::cores::timers::{tick_timer::TickTimer, tiny_timer::Tickable};
+    use crate::base_lib::{
+        cores::{
+            timers::{tick_timer::TickTimer, tiny_timer::Tickable},
+            tiny_tags::{EmptyTags, TinyTag},
+        },
+        eff_attr::{
+            bound_attr_effs::BoundAttrEffType, effects::Effect, stat_attr_effs::StatAttrEffType,
+            upsert_container::UpsertContainer,
+        },
+    };
 
     use super::*;
 
@@ -134,6 +285,42 @@ mod tests {
         try_refresh_dirty_stat_attr(&mut attr, &mut effs);
     }
 
+    /// 次数效果：只响应指定事件，耗尽后按过期清理
+    #[test]
+    fn test_consume_charge_element() {
+        let mut attr = StatAttr::new(0.0);
+        let mut effs = UpsertContainer::<StatAttrEff<&str, ChargeCounter>>::default();
+        effs.upsert_replace(StatAttrEff::new(
+            StatAttrEffType::BasicAdd,
+            Effect::new("alice", "flame_blade", 10.0),
+            ChargeCounter::new(2, ChargeEvent::OnHit),
+        ));
+        effs.upsert_replace(StatAttrEff::new(
+            StatAttrEffType::BasicAdd,
+            Effect::new("alice", "ward", 5.0),
+            ChargeCounter::new(1, ChargeEvent::OnDamageTaken),
+        ));
+        try_refresh_dirty_stat_attr(&mut attr, &mut effs);
+        assert_eq!(attr.get_current(), 15.0);
+
+        assert_eq!(consume_charge_element(&mut effs, ChargeEvent::OnCast), 0);
+        assert!(!effs.is_changed());
+
+        assert_eq!(consume_charge_element(&mut effs, ChargeEvent::OnHit), 1);
+        assert_eq!(
+            consume_charge_element(&mut effs, ChargeEvent::OnDamageTaken),
+            1
+        );
+        clean_expired_element(&mut effs, ());
+        try_refresh_dirty_stat_attr(&mut attr, &mut effs);
+        assert_eq!(attr.get_current(), 10.0);
+
+        consume_charge_element(&mut effs, ChargeEvent::OnHit);
+        clean_expired_element(&mut effs, ());
+        try_refresh_dirty_stat_attr(&mut attr, &mut effs);
+        assert_eq!(attr.get_current(), 0.0);
+    }
+
     /// 一个面向对象的写法样例
     #[test]
     fn example_process_tick() {
@@ -167,4 +354,218 @@ mod tests {
             .map(|eff| eff.get_timer_mut());
         try_reset_timeline(timeline, timers_iter);
     }
+
+    struct UnitTags(Vec<&'static str>);
+
+    impl PureTagContainer for UnitTags {
+        type PureTag = &'static str;
+
+        fn check_condition(&self, pure_tag: &Self::PureTag) -> bool {
+            self.0.contains(pure_tag)
+        }
+    }
+
+    /// 条件效果：条件翻转时置脏并重新计算，无需移除重加
+    #[test]
+    fn test_conditional_stat_eff_flips() {
+        let mut attr = StatAttr::new(100.0);
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(
+            StatAttrEff::new(
+                StatAttrEffType::FinalPer,
+                Effect::new("player", "last_stand", 0.2),
+                TickTimer::inf(),
+            )
+            .with_condition(TinyTag::Has("low_health")),
+        );
+        let mut tags = UnitTags(vec![]);
+
+        try_refresh_dirty_stat_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 100.0);
+        assert!(!effs.is_changed());
+
+        // 条件未翻转，不置脏
+        try_refresh_dirty_stat_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert!(!effs.is_changed());
+
+        tags.0.push("low_health");
+        try_refresh_dirty_stat_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 120.0);
+        assert_eq!(effs.ele_len(), 1);
+
+        tags.0.clear();
+        try_refresh_dirty_stat_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 100.0);
+    }
+
+    /// 条件效果与无条件效果混合
+    #[test]
+    fn test_conditional_bound_eff_mixed() {
+        let mut attr = BoundAttr::new(50.0);
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(BoundAttrEff::new(
+            BoundAttrEffType::BasicAdd,
+            Effect::new("player", "ring", 10.0),
+            TickTimer::inf(),
+        ));
+        effs.upsert_replace(
+            BoundAttrEff::new(
+                BoundAttrEffType::BasicAdd,
+                Effect::new("player", "shield_wall", 30.0),
+                TickTimer::inf(),
+            )
+            .with_condition(TinyTag::And("blocking", "shield")),
+        );
+
+        let mut tags = UnitTags(vec!["blocking"]);
+        try_refresh_dirty_bound_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 60.0);
+
+        tags.0.push("shield");
+        try_refresh_dirty_bound_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 90.0);
+    }
+
+    /// 条件标签类型可与效果名类型不同
+    #[test]
+    fn test_condition_tag_type_independent() {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        enum UnitState {
+            Blocking,
+        }
+
+        impl FixedName for UnitState {}
+
+        struct StateTags(Vec<UnitState>);
+
+        impl PureTagContainer for StateTags {
+            type PureTag = UnitState;
+
+            fn check_condition(&self, pure_tag: &Self::PureTag) -> bool {
+                self.0.contains(pure_tag)
+            }
+        }
+
+        let mut attr = BoundAttr::new(50.0);
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(
+            BoundAttrEff::new(
+                BoundAttrEffType::BasicAdd,
+                Effect::new("player".to_string(), "guard".to_string(), 20.0),
+                TickTimer::inf(),
+            )
+            .with_condition(TinyTag::Has(UnitState::Blocking)),
+        );
+
+        try_refresh_dirty_bound_attr_with_tags(&mut attr, &mut effs, &StateTags(vec![]));
+        assert_eq!(attr.get_current(), 50.0);
+
+        let tags = StateTags(vec![UnitState::Blocking]);
+        try_refresh_dirty_bound_attr_with_tags(&mut attr, &mut effs, &tags);
+        assert_eq!(attr.get_current(), 70.0);
+
+        // 可变遍历置脏，不带标签刷新时保持上次求值结果
+        effs.iter_mut().for_each(|_| {});
+        try_refresh_dirty_bound_attr(&mut attr, &mut effs);
+        assert_eq!(attr.get_current(), 70.0);
+    }
+
+    /// 护盾上限到期后当前值随之清除，并报告事件
+    #[test]
+    fn test_rebound_on_shield_expiry() {
+        let mut upper = BoundAttr::new(0.0);
+        let mut shield = BoundedAttr::new(0.0);
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(BoundAttrEff::new(
+            BoundAttrEffType::BasicAdd,
+            Effect::new("player", "barrier", 50.0),
+            TickTimer::new(time_type::unit::<1>()),
+        ));
+        let tags = EmptyTags::default();
+        let refresh =
+            |upper: &mut BoundAttr, effs: &mut UpsertContainer<_>, shield: &mut BoundedAttr| {
+                try_refresh_dirty_bound_attr_rebound(
+                    upper,
+                    effs,
+                    &tags,
+                    BoundSide::Upper,
+                    0.0,
+                    shield,
+                    ReboundPolicy::Clamp,
+                )
+            };
+
+        // 上限提升，只钳制的策略不修改当前值
+        assert!(refresh(&mut upper, &mut effs, &mut shield).is_none());
+        assert_eq!(upper.get_current(), 50.0);
+        shield.apply_eff(50.0);
+        shield.commit_pending_value();
+
+        effs.iter_mut()
+            .for_each(|eff| eff.get_timer_mut().tick(time_type::unit::<1>()));
+        clean_expired_element(&mut effs, ());
+        let event = refresh(&mut upper, &mut effs, &mut shield);
+        assert_eq!(
+            event,
+            Some(ReboundEvent {
+                old_value: 50.0,
+                new_value: 0.0
+            })
+        );
+        assert!(refresh(&mut upper, &mut effs, &mut shield).is_none());
+    }
+
+    /// 保持比例与保持差值
+    #[test]
+    fn test_rebound_policies() {
+        let mut health = BoundedAttr::new(50.0);
+        health.rebound_by(ReboundPolicy::KeepRatio, (0.0, 100.0), (0.0, 120.0));
+        assert_eq!(health.get_pending_value(), 60.0);
+
+        let mut health = BoundedAttr::new(80.0);
+        health.rebound_by(ReboundPolicy::KeepMissing, (0.0, 100.0), (0.0, 120.0));
+        assert_eq!(health.get_pending_value(), 100.0);
+        // 下降后不低于下限
+        health.rebound_by(ReboundPolicy::KeepMissing, (0.0, 120.0), (0.0, 10.0));
+        assert_eq!(health.get_pending_value(), 0.0);
+
+        // 下限提升
+        let mut health = BoundedAttr::new(5.0);
+        let event = health.rebound_by(ReboundPolicy::Clamp, (0.0, 100.0), (10.0, 100.0));
+        assert_eq!(event.map(|e| e.new_value), Some(10.0));
+    }
+
+    /// 整数属性刷新上限后按策略修正，修正值四舍五入
+    #[test]
+    fn test_rebound_integer_attr() {
+        let mut upper = BoundAttr::<i64>::new(100);
+        let mut health = BoundedAttr::<i64>::new(50);
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(BoundAttrEff::new(
+            BoundAttrEffType::BasicAdd,
+            Effect::new("ring", "vigor", 25.0),
+            TickTimer::inf(),
+        ));
+
+        let event = try_refresh_dirty_bound_attr_rebound(
+            &mut upper,
+            &mut effs,
+            &EmptyTags::default(),
+            BoundSide::Upper,
+            0.0,
+            &mut health,
+            ReboundPolicy::KeepRatio,
+        );
+        assert_eq!(upper.get_current(), 125);
+        assert_eq!(
+            event,
+            Some(ReboundEvent {
+                old_value: 50,
+                new_value: 63
+            })
+        );
+
+        do_commit_bounded_attr(&mut health);
+        assert_eq!(health.get_snapshot_value(), 63);
+    }
 }
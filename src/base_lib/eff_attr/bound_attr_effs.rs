@@ -1,8 +1,12 @@
 use crate::base_lib::{
-    cores::{timers::tiny_timer::HasTimer, unify_types::FixedName},
+    cores::{
+        attr_nums::AttrNum, timers::tiny_timer::HasTimer, tiny_tags::TinyTag,
+        unify_types::FixedName,
+    },
     eff_attr::{
-        effects::{Effect, EffectMean, EffectMeaning},
-        modifiers::{ADDITION_BASE_LINE, AnchorModifier, PERCENT_BASE_LINE},
+        effects::{ConditionalEffect, Effect, EffectMean, EffectMeaning},
+        modifiers::{ADDITION_BASE_LINE, AnchorModifier, ModifierRounding, PERCENT_BASE_LINE},
+        stack_curves::{CurvedValue, StackCurve},
         upsert_container::Upsert,
     },
 };
@@ -32,14 +36,20 @@ impl BoundAttrEffType {
 /// 若想在修改上限的同时修改实际值，那么需要同时生成【修改上限】的效果和【修改实际值】的效果
 ///
 /// 为了保证两者修改效果一致，限制修改维度只能基于基础值修改（不会被放大缩小产生偏差）
-#[derive(Clone, Debug)]
-pub struct BoundAttrEff<S: FixedName, Timer> {
+///
+/// 生效条件的标签类型 `T` 与效果名类型 `S` 相互独立，默认相同
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundAttrEff<S: FixedName, Timer, T: FixedName = S> {
     /// 效果类型 对应公式变量
     eff_type: BoundAttrEffType,
     /// 效果
     eff: Effect<S>,
     /// 持续时间（可以不用计时器，而是计数器或者BUFF列表，通过空判断是否结束）
     duration: Timer,
+    /// 生效条件，如“血量低于 30% 时”“格挡时”，条件切换时无需移除重加
+    condition: Option<TinyTag<T>>,
+    /// 条件的求值缓存，无条件时恒为 true
+    condition_met: bool,
 }
 
 impl<S: FixedName, Timer> BoundAttrEff<S, Timer> {
@@ -48,15 +58,45 @@ impl<S: FixedName, Timer> BoundAttrEff<S, Timer> {
             eff_type,
             eff,
             duration,
+            condition: None,
+            condition_met: true,
         }
     }
 
+    /// 附加生效条件，在属性刷新时首次求值，求值前不生效
+    ///
+    /// 条件的标签类型可与效果名类型不同，如以枚举标签描述单位状态
+    pub fn with_condition<T: FixedName>(self, condition: TinyTag<T>) -> BoundAttrEff<S, Timer, T> {
+        BoundAttrEff {
+            eff_type: self.eff_type,
+            eff: self.eff,
+            duration: self.duration,
+            condition: Some(condition),
+            condition_met: false,
+        }
+    }
+}
+
+impl<S: FixedName, Timer, T: FixedName> BoundAttrEff<S, Timer, T> {
+    /// 是否参与属性计算
+    pub fn is_active(&self) -> bool {
+        self.condition_met
+    }
+
     pub fn get_type(&self) -> BoundAttrEffType {
         self.eff_type
     }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    pub fn get_effect_mut(&mut self) -> &mut Effect<S> {
+        &mut self.eff
+    }
 }
 
-impl<S: FixedName, Timer> HasTimer for BoundAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> HasTimer for BoundAttrEff<S, Timer, T> {
     type Timer = Timer;
 
     fn get_timer(&self) -> &Self::Timer {
@@ -74,7 +114,7 @@ pub struct BoundAttrEffId<S: FixedName> {
     pub from: S,
 }
 
-impl<S: FixedName, Timer> Upsert for BoundAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> Upsert for BoundAttrEff<S, Timer, T> {
     type Id = BoundAttrEffId<S>;
 
     fn gen_id(&self) -> Self::Id {
@@ -94,7 +134,21 @@ impl<S: FixedName, Timer> Upsert for BoundAttrEff<S, Timer> {
     }
 }
 
-impl<S: FixedName, Timer> EffectMeaning for BoundAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> ConditionalEffect<T> for BoundAttrEff<S, Timer, T> {
+    fn get_condition(&self) -> Option<&TinyTag<T>> {
+        self.condition.as_ref()
+    }
+
+    fn is_condition_met(&self) -> bool {
+        self.condition_met
+    }
+
+    fn set_condition_met(&mut self, met: bool) {
+        self.condition_met = met;
+    }
+}
+
+impl<S: FixedName, Timer, T: FixedName> EffectMeaning for BoundAttrEff<S, Timer, T> {
     fn which_nature(&self) -> EffectMean {
         let eff_value = self.eff.get_effect_value();
         match self.eff_type {
@@ -104,6 +158,16 @@ impl<S: FixedName, Timer> EffectMeaning for BoundAttrEff<S, Timer> {
     }
 }
 
+/// 属性各效果类型的聚合曲线，默认均为线性
+///
+/// 注意：非线性曲线会破坏 [`crate::base_lib::eff_attr::bounded_attr_effs::AttrAlterEff::gen_effs_for_upper_bound`]
+/// “修改上限的同时修改等量实际值”的一致性，应只用于无需同步实际值的上下限
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BoundAttrCurves {
+    pub basic_add: StackCurve,
+    pub basic_per: StackCurve,
+}
+
 /// 属性效果修改器
 ///
 /// 计算公式见 [`BoundAttrModifier::apply_modify`]
@@ -111,7 +175,19 @@ impl<S: FixedName, Timer> EffectMeaning for BoundAttrEff<S, Timer> {
 pub struct BoundAttrModifier(AnchorModifier);
 
 impl BoundAttrModifier {
-    pub fn reduce<S: FixedName, Timer>(&mut self, eff: &BoundAttrEff<S, Timer>) {
+    pub fn new(curves: BoundAttrCurves) -> Self {
+        Self(AnchorModifier::new(curves.basic_add, curves.basic_per))
+    }
+
+    /// 同类效果聚合值在曲线前后的值，用于界面提示
+    pub fn curved_term(&self, eff_type: BoundAttrEffType) -> CurvedValue {
+        match eff_type {
+            BoundAttrEffType::BasicAdd => self.0.addition_term(),
+            BoundAttrEffType::BasicPer => self.0.percent_term(),
+        }
+    }
+
+    pub fn reduce<S: FixedName, Timer, T: FixedName>(&mut self, eff: &BoundAttrEff<S, Timer, T>) {
         let v = eff.eff.get_effect_value();
 
         match eff.eff_type {
@@ -120,9 +196,50 @@ impl BoundAttrModifier {
         }
     }
 
+    /// 计算公式 `base_value * (1 + b_per) + b_add` ，各项为曲线后的值
     pub fn apply_modify(&self, v: f64) -> f64 {
         self.0.apply_modify(v)
     }
+
+    /// 同 [`Self::apply_modify`] ，用于非浮点数值类型的属性
+    pub fn apply_modify_num<N: AttrNum>(&self, v: N, rounding: ModifierRounding) -> N {
+        self.0.apply_modify_num(v, rounding)
+    }
 }
 
-// todo test
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::tick_timer::TickTimer;
+
+    use super::*;
+
+    fn make_eff(eff_type: BoundAttrEffType, eff_value: f64) -> BoundAttrEff<String, TickTimer> {
+        BoundAttrEff::new(
+            eff_type,
+            Effect::new_form("from", "eff", eff_value),
+            TickTimer::inf(),
+        )
+    }
+
+    /// 曲线：基础加法软上限，报告曲线前后的值
+    #[test]
+    fn test_curve_soft_cap_basic_add() {
+        let curves = BoundAttrCurves {
+            basic_add: StackCurve::SoftCap {
+                cap: 50.0,
+                falloff: 0.5,
+            },
+            ..Default::default()
+        };
+        let mut bm = BoundAttrModifier::new(curves);
+        bm.reduce(&make_eff(BoundAttrEffType::BasicAdd, 60.0));
+        bm.reduce(&make_eff(BoundAttrEffType::BasicAdd, 30.0));
+        bm.reduce(&make_eff(BoundAttrEffType::BasicPer, 0.5));
+        // 90 → 50 + 40 * 0.5 = 70
+        assert_eq!(bm.apply_modify(100.0), 220.0);
+
+        let term = bm.curved_term(BoundAttrEffType::BasicAdd);
+        assert_eq!((term.pre, term.post), (90.0, 70.0));
+        assert!(!bm.curved_term(BoundAttrEffType::BasicPer).is_curved());
+    }
+}
@@ -1,47 +1,91 @@
 use crate::base_lib::{
-    cores::unify_types::FixedName,
-    eff_attr::bound_attr_effs::{BoundAttrEff, BoundAttrModifier},
+    cores::{attr_nums::AttrNum, unify_types::FixedName},
+    eff_attr::{
+        bound_attr_effs::{BoundAttrCurves, BoundAttrEff, BoundAttrModifier},
+        modifiers::ModifierRounding,
+        replications::ReplicatedAttr,
+    },
 };
 
 /// 边界约束属性，用于限制某属性的上下界限
 ///
 /// 对应效果的计算只能基于基础值，因此修改值可预测，可用作各种公式计算的锚点
 /// - 如：“增加血量上限的同时增加等量的血量”
+///
+/// 数值类型默认为 [`f64`] ，可替换为其他 [`AttrNum`]
 #[derive(Debug)]
-pub struct BoundAttr {
+pub struct BoundAttr<N: AttrNum = f64> {
     /// 原始值，未经过修改器修改
-    origin: f64,
+    origin: N,
     /// 当前值，经过修改器修改
-    current: f64,
+    current: N,
+    /// 同类效果的聚合曲线
+    curves: BoundAttrCurves,
+    /// 修改器的舍入规则，只有基础阶段
+    rounding: ModifierRounding,
 }
 
-impl BoundAttr {
-    pub fn new(origin: f64) -> Self {
+impl<N: AttrNum> BoundAttr<N> {
+    pub fn new(origin: N) -> Self {
+        Self::new_with_curves(origin, BoundAttrCurves::default())
+    }
+
+    pub fn new_with_curves(origin: N, curves: BoundAttrCurves) -> Self {
         Self {
             origin,
             current: origin,
+            curves,
+            rounding: ModifierRounding::default(),
         }
     }
 
-    pub fn get_origin(&self) -> f64 {
+    pub fn with_rounding(mut self, rounding: ModifierRounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn get_origin(&self) -> N {
         self.origin
     }
 
-    pub fn get_current(&self) -> f64 {
+    pub fn get_current(&self) -> N {
         self.current
     }
 
+    /// 按本属性的曲线聚合效果（跳过条件不满足的效果），也可用于界面提示查询曲线前后的值
+    pub fn build_modifier<'a, S: FixedName + 'a, Timer: 'a, T: FixedName + 'a>(
+        &self,
+        effs: impl Iterator<Item = &'a BoundAttrEff<S, Timer, T>>,
+    ) -> BoundAttrModifier {
+        let mut modifier = BoundAttrModifier::new(self.curves);
+
+        for ele in effs.filter(|ele| ele.is_active()) {
+            modifier.reduce(ele);
+        }
+
+        modifier
+    }
+
     /// 刷新属性，在效果更新后
-    pub fn refresh_value<'a, S: FixedName + 'a, Timer: 'a>(
+    pub fn refresh_value<'a, S: FixedName + 'a, Timer: 'a, T: FixedName + 'a>(
         &mut self,
-        effs: impl Iterator<Item = &'a BoundAttrEff<S, Timer>>,
+        effs: impl Iterator<Item = &'a BoundAttrEff<S, Timer, T>>,
     ) {
-        let mut modifier = BoundAttrModifier::default();
+        let modifier = self.build_modifier(effs);
 
-        for ele in effs {
-            modifier.reduce(ele);
-        }
+        self.current = modifier.apply_modify_num(self.origin, self.rounding);
+    }
+}
+
+impl<N: AttrNum> ReplicatedAttr for BoundAttr<N> {
+    type Num = N;
+
+    fn get_replicated(&self) -> N {
+        self.current
+    }
 
-        self.current = modifier.apply_modify(self.origin);
+    /// 只覆盖当前值，客户端不应再以效果刷新
+    fn set_replicated(&mut self, v: N) {
+        self.current = v;
     }
 }
@@ -0,0 +1,211 @@
+//! [`BoundedAttr`] 的界面显示值，如血条上滞后消退的“近期伤害”段
+//!
+//! 有界属性已有快照值与中间态，显示值是第三个值，只用于界面表现，不参与任何计算：
+//! - 目标值为提交后的快照值，每帧通过 [`BoundedAttrDisplay::sync_from`] 同步
+//! - 目标值变化后等待一段延迟，再按追赶方式向目标值靠拢；延迟期间目标值继续变化会重新计时
+//! - 减少与增加分别配置，如受伤时白条延迟消退、治疗时立即跟上
+//!
+//! 每帧调用 [`Tickable::tick`] 推进，在 UI 线程或表现层的系统中使用
+
+use crate::base_lib::{
+    cores::{
+        attr_nums::AttrNum,
+        timers::{
+            tick_timer::TickTimer,
+            tiny_timer::{Tickable, TimerProgress, TimerView},
+        },
+        unify_types::{FLOAT_DEAD_ZONE, time_type},
+    },
+    eff_attr::bounded_attrs::BoundedAttr,
+};
+
+/// 显示值的追赶方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayEasing {
+    /// 立即到达目标值
+    Instant,
+    /// 匀速，每秒变化的绝对值
+    Linear(f64),
+    /// 指数趋近，每秒缩小差距的速率，如 `5.0` 约 0.6 秒缩小 95% 的差距
+    Exponential(f64),
+}
+
+impl DisplayEasing {
+    /// 经过 `secs` 秒后的显示值，不越过目标值
+    fn step(&self, display: f64, target: f64, secs: f64) -> f64 {
+        let diff = target - display;
+        let moved = match self {
+            Self::Instant => diff,
+            Self::Linear(speed) => diff.signum() * (speed.abs() * secs).min(diff.abs()),
+            Self::Exponential(rate) => diff * (1.0 - (-rate.abs() * secs).exp()),
+        };
+        let v = display + moved;
+        if (target - v).abs() < FLOAT_DEAD_ZONE {
+            target
+        } else {
+            v
+        }
+    }
+}
+
+/// 单一方向（减少或增加）的追赶配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayCatchUp {
+    /// 目标值变化后等待多久开始追赶
+    pub delay: time_type::T,
+    pub easing: DisplayEasing,
+}
+
+impl DisplayCatchUp {
+    pub const INSTANT: Self = Self {
+        delay: time_type::ZERO,
+        easing: DisplayEasing::Instant,
+    };
+
+    pub fn new(delay: time_type::T, easing: DisplayEasing) -> Self {
+        Self { delay, easing }
+    }
+}
+
+/// 有界属性的显示值
+#[derive(Debug, Clone)]
+pub struct BoundedAttrDisplay {
+    display: f64,
+    target: f64,
+    /// 目标值低于显示值时使用
+    on_loss: DisplayCatchUp,
+    /// 目标值高于显示值时使用
+    on_gain: DisplayCatchUp,
+    delay: TickTimer,
+}
+
+impl BoundedAttrDisplay {
+    pub fn new(v: f64, on_loss: DisplayCatchUp, on_gain: DisplayCatchUp) -> Self {
+        Self {
+            display: v,
+            target: v,
+            on_loss,
+            on_gain,
+            delay: TickTimer::new(time_type::ZERO),
+        }
+    }
+
+    pub fn get_display(&self) -> f64 {
+        self.display
+    }
+
+    pub fn get_target(&self) -> f64 {
+        self.target
+    }
+
+    /// 显示值已到达目标值
+    pub fn is_settled(&self) -> bool {
+        self.display == self.target
+    }
+
+    fn get_catch_up(&self) -> &DisplayCatchUp {
+        if self.target < self.display {
+            &self.on_loss
+        } else {
+            &self.on_gain
+        }
+    }
+
+    /// 设置目标值，变化时按方向重新计时延迟
+    pub fn set_target(&mut self, v: f64) {
+        if (v - self.target).abs() < FLOAT_DEAD_ZONE {
+            return;
+        }
+        self.target = v;
+        self.delay = TickTimer::new(self.get_catch_up().delay);
+    }
+
+    /// 以有界属性的快照值作为目标值
+    pub fn sync_from<N: AttrNum>(&mut self, attr: &BoundedAttr<N>) {
+        self.set_target(attr.get_snapshot_value().to_f64());
+    }
+
+    /// 跳过动画，直接到达目标值，如复活、切换场景
+    pub fn snap(&mut self) {
+        self.display = self.target;
+    }
+}
+
+impl Tickable for BoundedAttrDisplay {
+    /// 延迟结束后的剩余时间用于追赶
+    fn tick(&mut self, delta: time_type::T) {
+        if self.is_settled() {
+            return;
+        }
+
+        let remaining = self.delay.remaining(());
+        self.delay.tick(delta);
+        if !self.delay.is_completed(()) {
+            return;
+        }
+
+        let secs = time_type::to_f64(delta) - time_type::to_f64(remaining);
+        let easing = self.get_catch_up().easing;
+        self.display = easing.step(self.display, self.target, secs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health_bar() -> BoundedAttrDisplay {
+        BoundedAttrDisplay::new(
+            100.0,
+            DisplayCatchUp::new(time_type::unit::<1>(), DisplayEasing::Linear(20.0)),
+            DisplayCatchUp::INSTANT,
+        )
+    }
+
+    /// 受伤：延迟后匀速消退，延迟期间再次受伤重新计时
+    #[test]
+    fn test_loss_lags_behind() {
+        let mut bar = health_bar();
+        let mut health = BoundedAttr::new(100.0);
+        health.apply_eff(-50.0);
+        health.commit_pending_value();
+        bar.sync_from(&health);
+        assert_eq!(bar.get_target(), 50.0);
+
+        bar.tick(time_type::unit::<1>());
+        assert_eq!(bar.get_display(), 100.0);
+
+        bar.set_target(40.0);
+        // 重新计时，延迟结束后的 1 秒用于追赶
+        bar.tick(time_type::unit::<2>());
+        assert_eq!(bar.get_display(), 80.0);
+        bar.tick(time_type::unit::<10>());
+        assert_eq!(bar.get_display(), 40.0);
+        assert!(bar.is_settled());
+    }
+
+    /// 治疗：立即跟上；指数趋近最终到达目标
+    #[test]
+    fn test_gain_and_exponential() {
+        let mut bar = health_bar();
+        bar.set_target(20.0);
+        bar.snap();
+        bar.set_target(60.0);
+        bar.tick(time_type::ZERO);
+        assert_eq!(bar.get_display(), 60.0);
+
+        let mut bar = BoundedAttrDisplay::new(
+            100.0,
+            DisplayCatchUp::new(time_type::ZERO, DisplayEasing::Exponential(5.0)),
+            DisplayCatchUp::INSTANT,
+        );
+        bar.set_target(0.0);
+        bar.tick(time_type::unit::<1>());
+        let v = bar.get_display();
+        assert!(v > 0.0 && v < 1.0);
+        for _ in 0..10 {
+            bar.tick(time_type::unit::<1>());
+        }
+        assert!(bar.is_settled());
+    }
+}
@@ -1,7 +1,10 @@
 //! 对 [`BoundedAttr`] 的通用修改描述与修改值计算
 
 use crate::base_lib::{
-    cores::unify_types::FixedName,
+    cores::{
+        attr_nums::{AttrNum, RoundingMode},
+        unify_types::FixedName,
+    },
     eff_attr::{
         bound_attr_effs::{BoundAttrEff, BoundAttrEffType},
         bound_attrs::BoundAttr,
@@ -25,16 +28,16 @@ impl AttrAlterEffType {
     /// 根据效果类型计算绝对值
     ///
     /// 为了内聚 逻辑必须在这里实现 因此需要传入参数
-    pub fn calc_alter_val(
+    pub fn calc_alter_val<N: AttrNum>(
         &self,
         eff_val: f64,
-        bounded_attr: &BoundedAttr,
-        upper_bound: &BoundAttr,
+        bounded_attr: &BoundedAttr<N>,
+        upper_bound: &BoundAttr<N>,
     ) -> f64 {
         match self {
             Self::Val => eff_val,
-            Self::CurPer => eff_val * bounded_attr.get_snapshot_value(),
-            Self::MaxPer => eff_val * upper_bound.get_current(),
+            Self::CurPer => eff_val * bounded_attr.get_snapshot_value().to_f64(),
+            Self::MaxPer => eff_val * upper_bound.get_current().to_f64(),
         }
     }
 }
@@ -61,7 +64,11 @@ impl<S: FixedName> AttrAlterEff<S> {
     }
 
     /// 计算 [`AttrAlterEff`] 的绝对值
-    pub fn calc_alter_val(&self, bounded_attr: &BoundedAttr, upper_bound: &BoundAttr) -> f64 {
+    pub fn calc_alter_val<N: AttrNum>(
+        &self,
+        bounded_attr: &BoundedAttr<N>,
+        upper_bound: &BoundAttr<N>,
+    ) -> f64 {
         let eff_val = self.eff.get_effect_value();
         self.eff_type
             .calc_alter_val(eff_val, bounded_attr, upper_bound)
@@ -96,4 +103,175 @@ impl<S: FixedName> AttrAlterEff<S> {
     }
 }
 
-// todo test
+/// 按资源标识取得有界属性及其上界，用于多资源消耗，如“消耗 20 法力与 10 生命”
+pub trait AttrCostPool<R, N: AttrNum = f64> {
+    fn get_cost_attr(&mut self, resource: R) -> (&mut BoundedAttr<N>, &BoundAttr<N>);
+}
+
+/// 多资源消耗的单项：资源、修改效果、消耗后允许的最低值
+pub type AttrCost<R, S> = (R, AttrAlterEff<S>, f64);
+
+pub mod attr_alter_system {
+    use super::*;
+
+    /// 事务式消耗：全部校验通过才全部应用，否则不做任何修改，返回第一个不足的资源
+    ///
+    /// - 效果值按 [`AttrAlterEff::calc_alter_val`] 折算（百分比参照快照值与上界），
+    ///   再按 [`RoundingMode::Nearest`] 落回属性的数值类型，校验与应用使用同一结果
+    /// - 只校验减少资源的项：同一资源所有项的合计作用到 pending 后不得低于该项的最低值
+    pub fn try_cost_atomic<R, S, P, N>(pool: &mut P, costs: &[AttrCost<R, S>]) -> Result<(), R>
+    where
+        R: Copy + Eq,
+        S: FixedName,
+        P: AttrCostPool<R, N>,
+        N: AttrNum,
+    {
+        let vals: Vec<N> = costs
+            .iter()
+            .map(|(resource, eff, _)| {
+                let (attr, upper) = pool.get_cost_attr(*resource);
+                N::from_f64(eff.calc_alter_val(attr, upper), RoundingMode::Nearest)
+            })
+            .collect();
+
+        for ((resource, _, floor), val) in costs.iter().zip(vals.iter()) {
+            if *val >= N::ZERO {
+                continue;
+            }
+            let sum_val = costs
+                .iter()
+                .zip(vals.iter())
+                .filter(|((r, _, _), _)| r == resource)
+                .fold(N::ZERO, |sum, (_, v)| sum + *v);
+            let (attr, _) = pool.get_cost_attr(*resource);
+            if (attr.get_pending_value() + sum_val).to_f64() < *floor {
+                return Err(*resource);
+            }
+        }
+
+        for ((resource, _, _), val) in costs.iter().zip(vals) {
+            let (attr, _) = pool.get_cost_attr(*resource);
+            attr.apply_eff(val);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attr_alter_system::try_cost_atomic, *};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Res {
+        Health,
+        Magicka,
+    }
+
+    struct Pool {
+        health: BoundedAttr,
+        health_upper: BoundAttr,
+        magicka: BoundedAttr,
+        magicka_upper: BoundAttr,
+    }
+
+    impl AttrCostPool<Res> for Pool {
+        fn get_cost_attr(&mut self, resource: Res) -> (&mut BoundedAttr, &BoundAttr) {
+            match resource {
+                Res::Health => (&mut self.health, &self.health_upper),
+                Res::Magicka => (&mut self.magicka, &self.magicka_upper),
+            }
+        }
+    }
+
+    fn cost(
+        resource: Res,
+        eff_type: AttrAlterEffType,
+        v: f64,
+        floor: f64,
+    ) -> AttrCost<Res, &'static str> {
+        (
+            resource,
+            AttrAlterEff::new(eff_type, Effect::new("self", "blood_magic", v)),
+            floor,
+        )
+    }
+
+    /// 任一资源不足则全部不扣除
+    #[test]
+    fn test_cost_all_or_nothing() {
+        let mut pool = Pool {
+            health: BoundedAttr::new(50.0),
+            health_upper: BoundAttr::new(100.0),
+            magicka: BoundedAttr::new(15.0),
+            magicka_upper: BoundAttr::new(100.0),
+        };
+
+        let costs = [
+            cost(Res::Health, AttrAlterEffType::MaxPer, -0.1, 1.0),
+            cost(Res::Magicka, AttrAlterEffType::Val, -20.0, 0.0),
+        ];
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Err(Res::Magicka));
+        assert_eq!(pool.health.get_pending_value(), 50.0);
+        assert_eq!(pool.magicka.get_pending_value(), 15.0);
+
+        pool.magicka.apply_eff(5.0);
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Ok(()));
+        assert_eq!(pool.health.get_pending_value(), 40.0);
+        assert_eq!(pool.magicka.get_pending_value(), 0.0);
+    }
+
+    /// 同一资源的多项合计校验，生命不能扣到最低值以下
+    #[test]
+    fn test_cost_same_resource_summed() {
+        let mut pool = Pool {
+            health: BoundedAttr::new(30.0),
+            health_upper: BoundAttr::new(100.0),
+            magicka: BoundedAttr::new(0.0),
+            magicka_upper: BoundAttr::new(100.0),
+        };
+
+        let costs = [
+            cost(Res::Health, AttrAlterEffType::Val, -15.0, 1.0),
+            cost(Res::Health, AttrAlterEffType::Val, -15.0, 1.0),
+        ];
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Err(Res::Health));
+        assert_eq!(pool.health.get_pending_value(), 30.0);
+
+        // 获得资源的项不校验
+        let costs = [
+            cost(Res::Health, AttrAlterEffType::Val, -15.0, 1.0),
+            cost(Res::Magicka, AttrAlterEffType::Val, 10.0, 50.0),
+        ];
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Ok(()));
+        assert_eq!(pool.health.get_pending_value(), 15.0);
+        assert_eq!(pool.magicka.get_pending_value(), 10.0);
+    }
+
+    struct IntPool {
+        health: BoundedAttr<i64>,
+        health_upper: BoundAttr<i64>,
+    }
+
+    impl AttrCostPool<Res, i64> for IntPool {
+        fn get_cost_attr(&mut self, _: Res) -> (&mut BoundedAttr<i64>, &BoundAttr<i64>) {
+            (&mut self.health, &self.health_upper)
+        }
+    }
+
+    /// 整数属性先舍入再校验，校验与应用的值一致
+    #[test]
+    fn test_cost_integer_attr() {
+        let mut pool = IntPool {
+            health: BoundedAttr::new(16),
+            health_upper: BoundAttr::new(100),
+        };
+
+        // 15.5 舍入为 16 ，扣到 0 低于最低值 1
+        let costs = [cost(Res::Health, AttrAlterEffType::MaxPer, -0.155, 1.0)];
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Err(Res::Health));
+
+        let costs = [cost(Res::Health, AttrAlterEffType::MaxPer, -0.155, 0.0)];
+        assert_eq!(try_cost_atomic(&mut pool, &costs), Ok(()));
+        assert_eq!(pool.health.get_pending_value(), 0);
+    }
+}
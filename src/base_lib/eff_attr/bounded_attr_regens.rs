@@ -0,0 +1,283 @@
+//! [`BoundedAttr`] 的自然恢复与衰减
+//!
+//! 每个有界属性可挂载一个恢复组件，如：
+//! - 生命值以最大值的百分比持续恢复
+//! - 平衡以固定值恢复，受击后延迟一段时间再继续
+//! - 能量以固定值削减，增长后延迟一段时间再继续
+//!
+//! 恢复值写入 pending ，与伤害等其他修改一同经 [`BoundedAttr::commit_pending_value`] 提交；
+//! 只向方向所指的边界靠拢，不会越过边界，也不会把已越界的值拉回
+
+use crate::base_lib::{
+    cores::{
+        timers::tiny_timer::{TimerControl, TimerView},
+        unify_types::time_type,
+    },
+    eff_attr::{
+        bound_attrs::BoundAttr, bounded_attr_effs::AttrAlterEffType, bounded_attrs::BoundedAttr,
+    },
+};
+
+/// 恢复方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegenDirection {
+    /// 向上界恢复
+    Recover,
+    /// 向下界衰减
+    Decay,
+}
+
+/// 打断恢复（重置延迟）的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegenEvent {
+    /// 受到伤害
+    Damaged,
+    /// 主动消耗
+    Spent,
+    /// 获得资源
+    Gained,
+}
+
+/// 自然恢复组件
+#[derive(Debug, Clone)]
+pub struct BoundedAttrRegen<Timer> {
+    /// 每秒恢复量的计算方式，百分比参照物见 [`AttrAlterEffType::calc_alter_val`]
+    rate_type: AttrAlterEffType,
+    /// 每秒恢复量，取绝对值，符号由方向决定
+    rate: f64,
+    direction: RegenDirection,
+    /// 会重置延迟的事件
+    reset_on: Vec<RegenEvent>,
+    /// 延迟计时器，完成后才开始恢复
+    delay: Timer,
+}
+
+impl<Timer: TimerView + TimerControl> BoundedAttrRegen<Timer> {
+    /// 默认不会被任何事件打断，通过 [`Self::reset_on`] 添加
+    pub fn new(
+        rate_type: AttrAlterEffType,
+        rate: f64,
+        direction: RegenDirection,
+        delay: Timer,
+    ) -> Self {
+        Self {
+            rate_type,
+            rate: rate.abs(),
+            direction,
+            reset_on: Vec::new(),
+            delay,
+        }
+    }
+
+    pub fn reset_on(mut self, event: RegenEvent) -> Self {
+        if !self.reset_on.contains(&event) {
+            self.reset_on.push(event);
+        }
+        self
+    }
+
+    pub fn get_direction(&self) -> RegenDirection {
+        self.direction
+    }
+
+    pub fn get_delay(&self) -> &Timer {
+        &self.delay
+    }
+
+    pub fn set_rate(&mut self, rate_type: AttrAlterEffType, rate: f64) {
+        self.rate_type = rate_type;
+        self.rate = rate.abs();
+    }
+
+    /// 通知事件，若为关注的事件则重置延迟，返回是否重置
+    pub fn notify(&mut self, event: RegenEvent, ctx: Timer::Ctx<'_>) -> bool {
+        if self.reset_on.contains(&event) {
+            self.delay.reset(ctx);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 是否处于恢复中（延迟已结束）
+    pub fn is_regenerating(&self, ctx: Timer::Ctx<'_>) -> bool {
+        self.delay.is_completed(ctx)
+    }
+
+    /// 计算本帧的恢复值（带方向符号），不考虑边界
+    pub fn calc_regen_val(
+        &self,
+        attr: &BoundedAttr,
+        upper: &BoundAttr,
+        delta: time_type::T,
+    ) -> f64 {
+        let per_sec = self.rate_type.calc_alter_val(self.rate, attr, upper);
+        let val = per_sec.abs() * time_type::to_f64(delta);
+        match self.direction {
+            RegenDirection::Recover => val,
+            RegenDirection::Decay => -val,
+        }
+    }
+}
+
+pub mod regen_system {
+    use crate::base_lib::eff_attr::bounded_attrs::BoundValue;
+
+    use super::*;
+
+    /// 延迟结束后将本帧的恢复值写入 pending ，返回实际变化值
+    ///
+    /// 应在同帧其他修改之后、提交之前调用，以便受击等事件先重置延迟
+    pub fn apply_regen<Timer, V>(
+        regen: &BoundedAttrRegen<Timer>,
+        attr: &mut BoundedAttr,
+        lower: V,
+        upper: &BoundAttr,
+        ctx: Timer::Ctx<'_>,
+        delta: time_type::T,
+    ) -> f64
+    where
+        Timer: TimerView + TimerControl,
+        BoundValue: From<V>,
+    {
+        if !regen.is_regenerating(ctx) {
+            return 0.0;
+        }
+
+        let old_val = attr.get_pending_value();
+        let val = regen.calc_regen_val(attr, upper, delta);
+        let new_val = match regen.direction {
+            RegenDirection::Recover => (old_val + val).min(upper.get_current()).max(old_val),
+            RegenDirection::Decay => (old_val + val)
+                .max(BoundValue::from(lower).get_value())
+                .min(old_val),
+        };
+        let diff_val = new_val - old_val;
+        attr.apply_eff(diff_val);
+        diff_val
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::{
+        static_timer::{StaticTimeline, StaticTimer},
+        tiny_timer::Tickable,
+    };
+
+    use super::{regen_system::apply_regen, *};
+
+    fn tick(timeline: &mut StaticTimeline) -> time_type::T {
+        let delta = time_type::unit::<1>();
+        timeline.0.tick(delta);
+        delta
+    }
+
+    /// 按上界百分比恢复，不超过上界
+    #[test]
+    fn test_recover_by_upper_percent() {
+        let mut timeline = StaticTimeline::new();
+        let upper = BoundAttr::new(100.0);
+        let mut health = BoundedAttr::new(50.0);
+        let regen = BoundedAttrRegen::new(
+            AttrAlterEffType::MaxPer,
+            0.3,
+            RegenDirection::Recover,
+            StaticTimer::new(&timeline, time_type::ZERO),
+        );
+
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut health, 0.0, &upper, &timeline, delta),
+            30.0
+        );
+        health.commit_pending_value();
+        assert_eq!(
+            apply_regen(&regen, &mut health, 0.0, &upper, &timeline, delta),
+            20.0
+        );
+        health.commit_pending_value();
+        assert_eq!(health.get_snapshot_value(), 100.0);
+        assert_eq!(
+            apply_regen(&regen, &mut health, 0.0, &upper, &timeline, delta),
+            0.0
+        );
+    }
+
+    /// 受击后重置延迟，延迟结束后继续恢复；未关注的事件不打断
+    #[test]
+    fn test_delay_reset_by_event() {
+        let mut timeline = StaticTimeline::new();
+        let upper = BoundAttr::new(100.0);
+        let mut stamina = BoundedAttr::new(0.0);
+        let mut regen = BoundedAttrRegen::new(
+            AttrAlterEffType::Val,
+            10.0,
+            RegenDirection::Recover,
+            StaticTimer::new(&timeline, time_type::unit::<2>()),
+        )
+        .reset_on(RegenEvent::Damaged);
+
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut stamina, 0.0, &upper, &timeline, delta),
+            0.0
+        );
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut stamina, 0.0, &upper, &timeline, delta),
+            10.0
+        );
+
+        assert!(!regen.notify(RegenEvent::Spent, &timeline));
+        assert!(regen.is_regenerating(&timeline));
+        assert!(regen.notify(RegenEvent::Damaged, &timeline));
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut stamina, 0.0, &upper, &timeline, delta),
+            0.0
+        );
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut stamina, 0.0, &upper, &timeline, delta),
+            10.0
+        );
+        assert_eq!(stamina.get_pending_value(), 20.0);
+    }
+
+    /// 向下界衰减，不低于下界；已越界的值不被拉回
+    #[test]
+    fn test_decay_respects_bounds() {
+        let mut timeline = StaticTimeline::new();
+        let upper = BoundAttr::new(100.0);
+        let mut magicka = BoundedAttr::new(15.0);
+        let regen = BoundedAttrRegen::new(
+            AttrAlterEffType::Val,
+            10.0,
+            RegenDirection::Decay,
+            StaticTimer::new(&timeline, time_type::ZERO),
+        )
+        .reset_on(RegenEvent::Gained);
+
+        let delta = tick(&mut timeline);
+        assert_eq!(
+            apply_regen(&regen, &mut magicka, 0.0, &upper, &timeline, delta),
+            -10.0
+        );
+        assert_eq!(
+            apply_regen(&regen, &mut magicka, 0.0, &upper, &timeline, delta),
+            -5.0
+        );
+        assert_eq!(
+            apply_regen(&regen, &mut magicka, 0.0, &upper, &timeline, delta),
+            0.0
+        );
+
+        let mut overflow = BoundedAttr::new(-5.0);
+        assert_eq!(
+            apply_regen(&regen, &mut overflow, 0.0, &upper, &timeline, delta),
+            0.0
+        );
+        assert_eq!(overflow.get_pending_value(), -5.0);
+    }
+}
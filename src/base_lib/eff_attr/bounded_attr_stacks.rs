@@ -0,0 +1,320 @@
+//! [`super::bounded_attrs::BoundedAttr`] 的来源明细，如同一层护盾由多个施法者提供
+//!
+//! 明细记录每个来源的剩余量与持续时间，有界属性的总量即明细之和：
+//! - 同一来源（效果来源与名称相同）重复添加时刷新，视为最新添加
+//! - 受到伤害时按 [`StackConsumeOrder`] 逐个消耗，并报告每个来源吸收的量
+//! - 到期的明细由 [`BoundedAttrStack::clean_expired`] 移除，总量随之减少该明细的剩余量
+//!
+//! 明细持有来源唯一的计时器，急速缩放与重置持续时间都直接作用于明细（见 [`BoundedAttrStack::iter_mut`]）；
+//! 有界属性及其上限由明细得出，不应再经其他效果修改
+
+use crate::base_lib::{
+    cores::{
+        timers::tiny_timer::{HasTimer, TimerControl, TimerProgress, TimerView},
+        unify_types::{FLOAT_DEAD_ZONE, FixedName, time_type},
+    },
+    eff_attr::effects::Effect,
+};
+
+/// 消耗顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StackConsumeOrder {
+    /// 最早添加的先消耗
+    #[default]
+    OldestFirst,
+    /// 剩余量最少的先消耗
+    WeakestFirst,
+    /// 剩余时间最短的先消耗
+    ExpiringFirst,
+}
+
+/// 单个来源的明细，效果值即剩余量
+#[derive(Debug, Clone)]
+pub struct StackEntry<S: FixedName, Timer> {
+    eff: Effect<S>,
+    duration: Timer,
+}
+
+impl<S: FixedName, Timer> StackEntry<S, Timer> {
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    pub fn get_amount(&self) -> f64 {
+        self.eff.get_effect_value()
+    }
+
+    fn is_same_source(&self, eff: &Effect<S>) -> bool {
+        self.eff.get_from_name() == eff.get_from_name()
+            && self.eff.get_effect_name() == eff.get_effect_name()
+    }
+}
+
+impl<S: FixedName, Timer> HasTimer for StackEntry<S, Timer> {
+    type Timer = Timer;
+
+    fn get_timer(&self) -> &Self::Timer {
+        &self.duration
+    }
+
+    fn get_timer_mut(&mut self) -> &mut Self::Timer {
+        &mut self.duration
+    }
+}
+
+/// 单个来源吸收的量
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackAbsorption<S> {
+    pub from_name: S,
+    pub effect_name: S,
+    /// 吸收的量，为正数
+    pub absorbed: f64,
+}
+
+/// 有界属性的来源明细
+#[derive(Debug, Clone)]
+pub struct BoundedAttrStack<S: FixedName, Timer> {
+    /// 按添加顺序排列
+    entries: Vec<StackEntry<S, Timer>>,
+    order: StackConsumeOrder,
+}
+
+impl<S: FixedName, Timer> Default for BoundedAttrStack<S, Timer> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            order: StackConsumeOrder::default(),
+        }
+    }
+}
+
+impl<S: FixedName, Timer> BoundedAttrStack<S, Timer>
+where
+    Timer: TimerView + TimerProgress,
+{
+    pub fn with_order(mut self, order: StackConsumeOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn get_order(&self) -> StackConsumeOrder {
+        self.order
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StackEntry<S, Timer>> {
+        self.entries.iter()
+    }
+
+    /// 只能修改计时器，如急速缩放 [`crate::base_lib::eff_attr::stat_attr_hastes::HasteTracker::try_rebase`]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut StackEntry<S, Timer>> {
+        self.entries.iter_mut()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 明细的总量
+    pub fn get_total(&self) -> f64 {
+        self.entries.iter().map(|e| e.get_amount()).sum()
+    }
+
+    /// 添加来源，效果值为提供的量；同一来源刷新量与持续时间，并视为最新添加
+    pub fn push(&mut self, eff: Effect<S>, duration: Timer) {
+        self.entries.retain(|e| !e.is_same_source(&eff));
+        if eff.get_effect_value() > FLOAT_DEAD_ZONE {
+            self.entries.push(StackEntry { eff, duration });
+        }
+    }
+
+    /// 按消耗顺序吸收 `val` （正数），返回每个来源吸收的量，耗尽的来源被移除
+    ///
+    /// 超出明细总量的部分不报告
+    pub fn consume(&mut self, val: f64, ctx: Timer::Ctx<'_>) -> Vec<StackAbsorption<S>>
+    where
+        for<'a> Timer::Ctx<'a>: Copy,
+    {
+        let mut indexes: Vec<usize> = (0..self.entries.len()).collect();
+        match self.order {
+            StackConsumeOrder::OldestFirst => {}
+            StackConsumeOrder::WeakestFirst => indexes.sort_by(|a, b| {
+                let amount = |i: &usize| self.entries[*i].get_amount();
+                amount(a).total_cmp(&amount(b))
+            }),
+            StackConsumeOrder::ExpiringFirst => indexes.sort_by(|a, b| {
+                let remaining =
+                    |i: &usize| time_type::to_f64(self.entries[*i].duration.remaining(ctx));
+                remaining(a).total_cmp(&remaining(b))
+            }),
+        }
+
+        let mut remaining = val;
+        let mut absorptions = Vec::new();
+        for idx in indexes {
+            if remaining <= FLOAT_DEAD_ZONE {
+                break;
+            }
+            let entry = &mut self.entries[idx];
+            let absorbed = entry.get_amount().min(remaining);
+            entry.eff.set_effect_value(entry.get_amount() - absorbed);
+            remaining -= absorbed;
+            absorptions.push(StackAbsorption {
+                from_name: entry.eff.get_from_name().clone(),
+                effect_name: entry.eff.get_effect_name().clone(),
+                absorbed,
+            });
+        }
+
+        self.entries.retain(|e| e.get_amount() > FLOAT_DEAD_ZONE);
+        absorptions
+    }
+
+    /// 重置来源的持续时间，剩余量不变，来源不存在时返回 `false`
+    pub fn reset_timer(&mut self, from_name: &S, effect_name: &S, ctx: Timer::Ctx<'_>) -> bool
+    where
+        Timer: TimerControl,
+    {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.eff.get_from_name() == from_name && e.eff.get_effect_name() == effect_name)
+        {
+            Some(entry) => {
+                entry.duration.reset(ctx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 移除到期的来源并返回
+    pub fn clean_expired(&mut self, ctx: Timer::Ctx<'_>) -> Vec<StackEntry<S, Timer>>
+    where
+        for<'a> Timer::Ctx<'a>: Copy,
+    {
+        let (expired, alive) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|e| e.duration.is_completed(ctx));
+        self.entries = alive;
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::timers::{
+            static_timer::{StaticTimeline, StaticTimer},
+            tiny_timer::Tickable,
+        },
+        eff_attr::{stat_attr_hastes::HasteTracker, stat_attrs::StatAttr},
+    };
+
+    use super::*;
+
+    fn stack_of(
+        order: StackConsumeOrder,
+        timeline: &StaticTimeline,
+    ) -> BoundedAttrStack<&'static str, StaticTimer> {
+        let mut stack = BoundedAttrStack::default().with_order(order);
+        stack.push(
+            Effect::new("alice", "barrier", 30.0),
+            StaticTimer::new(timeline, time_type::unit::<10>()),
+        );
+        stack.push(
+            Effect::new("bob", "barrier", 20.0),
+            StaticTimer::new(timeline, time_type::unit::<2>()),
+        );
+        stack.push(
+            Effect::new("carol", "barrier", 10.0),
+            StaticTimer::new(timeline, time_type::unit::<5>()),
+        );
+        stack
+    }
+
+    fn absorbed_by(absorptions: &[StackAbsorption<&'static str>]) -> Vec<(&'static str, f64)> {
+        absorptions
+            .iter()
+            .map(|a| (a.from_name, a.absorbed))
+            .collect()
+    }
+
+    /// 三种消耗顺序
+    #[test]
+    fn test_consume_orders() {
+        let timeline = StaticTimeline::new();
+
+        let mut stack = stack_of(StackConsumeOrder::OldestFirst, &timeline);
+        let absorptions = stack.consume(40.0, &timeline);
+        assert_eq!(
+            absorbed_by(&absorptions),
+            vec![("alice", 30.0), ("bob", 10.0)]
+        );
+        assert_eq!(stack.get_total(), 20.0);
+
+        let mut stack = stack_of(StackConsumeOrder::WeakestFirst, &timeline);
+        let absorptions = stack.consume(15.0, &timeline);
+        assert_eq!(
+            absorbed_by(&absorptions),
+            vec![("carol", 10.0), ("bob", 5.0)]
+        );
+
+        let mut stack = stack_of(StackConsumeOrder::ExpiringFirst, &timeline);
+        let absorptions = stack.consume(100.0, &timeline);
+        assert_eq!(
+            absorbed_by(&absorptions),
+            vec![("bob", 20.0), ("carol", 10.0), ("alice", 30.0)]
+        );
+        assert!(stack.is_empty());
+    }
+
+    /// 同一来源刷新，到期移除
+    #[test]
+    fn test_refresh_and_expire() {
+        let mut timeline = StaticTimeline::new();
+        let mut stack = stack_of(StackConsumeOrder::OldestFirst, &timeline);
+        stack.push(
+            Effect::new("alice", "barrier", 50.0),
+            StaticTimer::new(&timeline, time_type::unit::<1>()),
+        );
+        let sources: Vec<_> = stack
+            .iter()
+            .map(|e| *e.get_effect().get_from_name())
+            .collect();
+        assert_eq!(sources, vec!["bob", "carol", "alice"]);
+        assert_eq!(stack.get_total(), 80.0);
+
+        timeline.0.tick(time_type::unit::<2>());
+        let expired = stack.clean_expired(&timeline);
+        assert_eq!(expired.len(), 2);
+        assert_eq!(stack.get_total(), 10.0);
+    }
+
+    /// 急速缩放与重置都作用于明细自身的计时器
+    #[test]
+    fn test_rescale_and_reset_timer() {
+        let mut timeline = StaticTimeline::new();
+        let mut stack = stack_of(StackConsumeOrder::OldestFirst, &timeline);
+
+        let mut tracker = HasteTracker::default();
+        assert!(tracker.try_rebase(
+            &StatAttr::new(1.0),
+            stack.iter_mut().map(|e| e.get_timer_mut()),
+            &timeline,
+        ));
+        // bob 的 2 缩放为 1
+        timeline.0.tick(time_type::unit::<1>());
+        assert!(stack.reset_timer(&"bob", &"barrier", &timeline));
+        assert!(!stack.reset_timer(&"dave", &"barrier", &timeline));
+
+        timeline.0.tick(time_type::unit::<3>());
+        let expired: Vec<_> = stack
+            .clean_expired(&timeline)
+            .iter()
+            .map(|e| *e.get_effect().get_from_name())
+            .collect();
+        // carol 的 5 缩放为 2.5 ，bob 重置后剩余 1
+        assert_eq!(expired, vec!["bob", "carol"]);
+        assert_eq!(stack.get_total(), 30.0);
+    }
+}
@@ -0,0 +1,284 @@
+//! [`BoundedAttr`] 的阈值穿越检测，如“平衡清空时倒地”“低血量警告”“能量已满提示”
+//!
+//! 每个有界属性可注册多个阈值，提交时（见 [`super::attr_systems::do_commit_bounded_attr_watched`]）
+//! 比较提交前后所处的一侧，报告本帧穿越的阈值
+//!
+//! 滞回：报告的方向在阈值处精确触发，反方向需越过阈值 `hysteresis` 才重新就绪，
+//! 防止数值在阈值附近抖动时反复触发（如血量在 30% 附近被小额伤害与恢复交替修改）
+
+use crate::base_lib::{
+    cores::{attr_nums::AttrNum, unify_types::FixedName},
+    eff_attr::bounded_attrs::{BoundValue, BoundedAttr},
+};
+
+/// 阈值的取值方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdValue {
+    /// 绝对值
+    Val(f64),
+    /// 上界的百分比
+    UpperPer(f64),
+}
+
+impl ThresholdValue {
+    pub fn resolve(&self, upper: f64) -> f64 {
+        match self {
+            Self::Val(v) => *v,
+            Self::UpperPer(per) => per * upper,
+        }
+    }
+}
+
+/// 关注的穿越方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdWatch {
+    /// 自下而上，如“能量已满”
+    Rising,
+    /// 自上而下，如“平衡清空”
+    Falling,
+    /// 双向，下穿在阈值处触发，上穿需越过滞回
+    Both,
+}
+
+/// 实际发生的穿越方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdEdge {
+    Rising,
+    Falling,
+}
+
+/// 相对阈值所处的一侧
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThresholdSide {
+    Above,
+    Below,
+}
+
+/// 单个阈值
+#[derive(Debug, Clone)]
+pub struct AttrThreshold<S: FixedName> {
+    name: S,
+    value: ThresholdValue,
+    watch: ThresholdWatch,
+    /// 滞回宽度，绝对值
+    hysteresis: f64,
+    /// 首次检测前为 None ，以提交前的快照值初始化
+    side: Option<ThresholdSide>,
+}
+
+impl<S: FixedName> AttrThreshold<S> {
+    pub fn new(name: S, value: ThresholdValue, watch: ThresholdWatch) -> Self {
+        Self {
+            name,
+            value,
+            watch,
+            hysteresis: 0.0,
+            side: None,
+        }
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis.abs();
+        self
+    }
+
+    pub fn get_name(&self) -> &S {
+        &self.name
+    }
+
+    /// 进入下侧、进入上侧的边界值
+    fn calc_edges(&self, upper: f64) -> (f64, f64) {
+        let v = self.value.resolve(upper);
+        match self.watch {
+            ThresholdWatch::Rising => (v - self.hysteresis, v),
+            ThresholdWatch::Falling | ThresholdWatch::Both => (v, v + self.hysteresis),
+        }
+    }
+
+    fn calc_side(&self, cur: ThresholdSide, val: f64, upper: f64) -> ThresholdSide {
+        let (fall_at, rise_at) = self.calc_edges(upper);
+        match cur {
+            ThresholdSide::Above if val <= fall_at => ThresholdSide::Below,
+            ThresholdSide::Below if val >= rise_at => ThresholdSide::Above,
+            _ => cur,
+        }
+    }
+
+    /// 以新值更新所处的一侧，返回需要报告的穿越
+    fn update(&mut self, old_val: f64, new_val: f64, upper: f64) -> Option<ThresholdEdge> {
+        let old_side = self.side.unwrap_or_else(|| {
+            // 初始时没有滞回，只按阈值本身划分，恰好处于阈值时视为已触发
+            let v = self.value.resolve(upper);
+            let is_above = match self.watch {
+                ThresholdWatch::Rising => old_val >= v,
+                ThresholdWatch::Falling | ThresholdWatch::Both => old_val > v,
+            };
+            if is_above {
+                ThresholdSide::Above
+            } else {
+                ThresholdSide::Below
+            }
+        });
+        let new_side = self.calc_side(old_side, new_val, upper);
+        self.side = Some(new_side);
+
+        let edge = match (old_side, new_side) {
+            (ThresholdSide::Above, ThresholdSide::Below) => ThresholdEdge::Falling,
+            (ThresholdSide::Below, ThresholdSide::Above) => ThresholdEdge::Rising,
+            _ => return None,
+        };
+        match (self.watch, edge) {
+            (ThresholdWatch::Rising, ThresholdEdge::Falling)
+            | (ThresholdWatch::Falling, ThresholdEdge::Rising) => None,
+            _ => Some(edge),
+        }
+    }
+}
+
+/// 阈值穿越事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdCrossing<S: FixedName> {
+    pub name: S,
+    pub edge: ThresholdEdge,
+}
+
+/// 单个有界属性上注册的所有阈值
+#[derive(Debug, Clone)]
+pub struct AttrThresholds<S: FixedName>(Vec<AttrThreshold<S>>);
+
+impl<S: FixedName> Default for AttrThresholds<S> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<S: FixedName> AttrThresholds<S> {
+    /// 同名阈值替换
+    pub fn register(&mut self, threshold: AttrThreshold<S>) {
+        if let Some(old) = self.0.iter_mut().find(|t| t.name == threshold.name) {
+            *old = threshold;
+        } else {
+            self.0.push(threshold);
+        }
+    }
+
+    pub fn unregister(&mut self, name: &S) {
+        self.0.retain(|t| t.name != *name);
+    }
+
+    /// 检测从 `old_val` 到 `new_val` 穿越的阈值
+    pub fn detect<V>(&mut self, old_val: f64, new_val: f64, upper: V) -> Vec<ThresholdCrossing<S>>
+    where
+        BoundValue: From<V>,
+    {
+        let upper = BoundValue::from(upper).get_value();
+        self.0
+            .iter_mut()
+            .filter_map(|t| {
+                t.update(old_val, new_val, upper)
+                    .map(|edge| ThresholdCrossing {
+                        name: t.name.clone(),
+                        edge,
+                    })
+            })
+            .collect()
+    }
+
+    /// 提交有界属性的修改，返回本帧穿越的阈值
+    pub fn commit_watched<N: AttrNum, V>(
+        &mut self,
+        attr: &mut BoundedAttr<N>,
+        upper: V,
+    ) -> Vec<ThresholdCrossing<S>>
+    where
+        BoundValue: From<V>,
+    {
+        let old_val = attr.get_snapshot_value();
+        attr.commit_pending_value();
+        self.detect(old_val.to_f64(), attr.get_snapshot_value().to_f64(), upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::eff_attr::bound_attrs::BoundAttr;
+
+    use super::*;
+
+    fn commit(
+        thresholds: &mut AttrThresholds<&'static str>,
+        attr: &mut BoundedAttr,
+        upper: &BoundAttr,
+        val: f64,
+    ) -> Vec<ThresholdCrossing<&'static str>> {
+        attr.apply_eff(val);
+        attr.clamp_by(0.0, upper);
+        thresholds.commit_watched(attr, upper)
+    }
+
+    /// 清空与已满：只报告关注的方向
+    #[test]
+    fn test_empty_and_full() {
+        let upper = BoundAttr::new(100.0);
+        let mut stamina = BoundedAttr::new(50.0);
+        let mut thresholds = AttrThresholds::default();
+        thresholds.register(AttrThreshold::new(
+            "empty",
+            ThresholdValue::Val(0.0),
+            ThresholdWatch::Falling,
+        ));
+        thresholds.register(AttrThreshold::new(
+            "full",
+            ThresholdValue::UpperPer(1.0),
+            ThresholdWatch::Rising,
+        ));
+
+        let crossed = commit(&mut thresholds, &mut stamina, &upper, -80.0);
+        assert_eq!(
+            crossed,
+            vec![ThresholdCrossing {
+                name: "empty",
+                edge: ThresholdEdge::Falling
+            }]
+        );
+        assert!(commit(&mut thresholds, &mut stamina, &upper, 0.0).is_empty());
+
+        let crossed = commit(&mut thresholds, &mut stamina, &upper, 200.0);
+        assert_eq!(
+            crossed,
+            vec![ThresholdCrossing {
+                name: "full",
+                edge: ThresholdEdge::Rising
+            }]
+        );
+    }
+
+    /// 滞回：在阈值附近抖动不重复触发
+    #[test]
+    fn test_hysteresis() {
+        let upper = BoundAttr::new(100.0);
+        let mut health = BoundedAttr::new(100.0);
+        let mut thresholds = AttrThresholds::default();
+        thresholds.register(
+            AttrThreshold::new(
+                "low_health",
+                ThresholdValue::UpperPer(0.3),
+                ThresholdWatch::Both,
+            )
+            .with_hysteresis(10.0),
+        );
+
+        let crossed = commit(&mut thresholds, &mut health, &upper, -71.0);
+        assert_eq!(crossed[0].edge, ThresholdEdge::Falling);
+        // 29 → 35 → 29 ：未越过滞回，不触发
+        assert!(commit(&mut thresholds, &mut health, &upper, 6.0).is_empty());
+        assert!(commit(&mut thresholds, &mut health, &upper, -6.0).is_empty());
+        // 29 → 40 ：越过滞回，上穿
+        let crossed = commit(&mut thresholds, &mut health, &upper, 11.0);
+        assert_eq!(crossed[0].edge, ThresholdEdge::Rising);
+        // 40 → 31 ：未到阈值
+        assert!(commit(&mut thresholds, &mut health, &upper, -9.0).is_empty());
+        let crossed = commit(&mut thresholds, &mut health, &upper, -1.0);
+        assert_eq!(crossed[0].edge, ThresholdEdge::Falling);
+    }
+}
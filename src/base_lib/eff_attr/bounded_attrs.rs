@@ -1,4 +1,10 @@
-use crate::base_lib::eff_attr::bound_attrs::BoundAttr;
+use crate::base_lib::{
+    cores::{
+        attr_nums::{AttrNum, RoundingMode},
+        unify_types::FLOAT_DEAD_ZONE,
+    },
+    eff_attr::{bound_attrs::BoundAttr, replications::ReplicatedAttr},
+};
 
 #[derive(Debug)]
 pub struct AttrAlterResult {
@@ -6,44 +12,78 @@ pub struct AttrAlterResult {
     pub diff_val: f64,
 }
 
-/// 有界属性的约束值，用于自动转换
+/// 有界属性的约束值，用于自动转换，统一以 [`f64`] 表示
 #[derive(Debug)]
 pub struct BoundValue(f64);
 
+impl BoundValue {
+    pub fn get_value(&self) -> f64 {
+        self.0
+    }
+}
+
 impl From<f64> for BoundValue {
     fn from(value: f64) -> Self {
         Self(value)
     }
 }
 
-impl From<&BoundAttr> for BoundValue {
-    fn from(value: &BoundAttr) -> Self {
-        Self(value.get_current())
+impl<N: AttrNum> From<&BoundAttr<N>> for BoundValue {
+    fn from(value: &BoundAttr<N>) -> Self {
+        Self(value.get_current().to_f64())
     }
 }
 
+/// 发生变化的是上限还是下限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundSide {
+    Upper,
+    Lower,
+}
+
+/// 上下限变化时当前值的处理策略，变化后总会钳制到新的上下限内
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReboundPolicy {
+    /// 只钳制，如护盾到期后清除超出的护盾值
+    #[default]
+    Clamp,
+    /// 保持在上下限之间的比例，如 50/100 提升上限后为 60/120
+    KeepRatio,
+    /// 保持与上限的差值，如 80/100 提升上限后为 100/120
+    KeepMissing,
+}
+
+/// 上下限变化导致当前值（ pending ）被修正
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReboundEvent<N: AttrNum = f64> {
+    pub old_value: N,
+    pub new_value: N,
+}
+
 /// 有界属性，一般作为各种系统的结果，比如 “血量/蓝量”
+///
+/// 数值类型默认为 [`f64`] ，可替换为其他 [`crate::base_lib::cores::attr_nums::AttrNum`]
 #[derive(Debug)]
-pub struct BoundedAttr {
+pub struct BoundedAttr<N: AttrNum = f64> {
     /// 快照值，每次修改前的快照，在一帧中保持不变
-    snapshot: f64,
+    snapshot: N,
     /// 计算过程中的中间态
-    pending: f64,
+    pending: N,
 }
 
-impl BoundedAttr {
-    pub fn new(v: f64) -> Self {
+impl<N: AttrNum> BoundedAttr<N> {
+    pub fn new(v: N) -> Self {
         Self {
             snapshot: v,
             pending: v,
         }
     }
 
-    pub fn get_snapshot_value(&self) -> f64 {
+    pub fn get_snapshot_value(&self) -> N {
         self.snapshot
     }
 
-    pub fn get_pending_value(&self) -> f64 {
+    pub fn get_pending_value(&self) -> N {
         self.pending
     }
 
@@ -52,38 +92,120 @@ impl BoundedAttr {
     }
 
     /// 钳制 应用上下界限
+    ///
+    /// 非浮点数值类型下，下限向上取整、上限向下取整
     pub fn clamp_by<V1, V2>(&mut self, lower: V1, upper: V2)
     where
         BoundValue: From<V1>,
         BoundValue: From<V2>,
     {
-        let lower = BoundValue::from(lower).0;
-        let upper = BoundValue::from(upper).0;
+        let lower = N::from_f64(BoundValue::from(lower).0, RoundingMode::Ceil);
+        let upper = N::from_f64(BoundValue::from(upper).0, RoundingMode::Floor);
 
         let old_v = self.pending;
-        let new_v = lower.max(upper.min(old_v));
+        let new_v = lower.max_of(upper.min_of(old_v));
         self.pending = new_v;
     }
 
     /// 考虑到公式计算的复杂性，这里只支持输入具体值，不做统一的计算公式抽象
-    pub fn apply_eff(&mut self, val: f64) {
+    pub fn apply_eff(&mut self, val: N) {
         self.pending += val;
     }
 
     /// 只有当前值足够才会去应用效果（如法力不够则施放失败）
-    pub fn apply_eff_checked<V>(&mut self, lower: V, val: f64, want_gt: f64) -> bool
+    pub fn apply_eff_checked<V>(&mut self, lower: V, val: N, want_gt: N) -> bool
     where
         BoundValue: From<V>,
     {
-        let lower = BoundValue::from(lower).0;
+        let lower = N::from_f64(BoundValue::from(lower).0, RoundingMode::Ceil);
 
-        if lower.max(self.pending + val) >= want_gt {
+        if lower.max_of(self.pending + val) >= want_gt {
             self.pending += val;
             true
         } else {
             false
         }
     }
+
+    /// 上下限由 `old` 变为 `new` 后按策略修正 pending ，值被修正时返回事件
+    ///
+    /// 参数均为 `(lower, upper)`
+    pub fn rebound_by(
+        &mut self,
+        policy: ReboundPolicy,
+        old: (f64, f64),
+        new: (f64, f64),
+    ) -> Option<ReboundEvent<N>> {
+        let old_value = self.pending;
+        let v = old_value.to_f64();
+        let target = match policy {
+            ReboundPolicy::Clamp => v,
+            ReboundPolicy::KeepRatio => {
+                let old_span = old.1 - old.0;
+                if old_span > FLOAT_DEAD_ZONE {
+                    new.0 + (v - old.0) / old_span * (new.1 - new.0)
+                } else {
+                    v
+                }
+            }
+            ReboundPolicy::KeepMissing => v + (new.1 - old.1),
+        };
+        if policy != ReboundPolicy::Clamp {
+            self.pending = N::from_f64(target, RoundingMode::Nearest);
+        }
+        self.clamp_by(new.0, new.1);
+
+        let new_value = self.pending;
+        (new_value != old_value).then_some(ReboundEvent {
+            old_value,
+            new_value,
+        })
+    }
 }
 
-// todo test clamp_by & apply_eff_checked
+impl<N: AttrNum> ReplicatedAttr for BoundedAttr<N> {
+    type Num = N;
+
+    fn get_replicated(&self) -> N {
+        self.snapshot
+    }
+
+    /// 快照与中间态一同覆盖
+    fn set_replicated(&mut self, v: N) {
+        self.snapshot = v;
+        self.pending = v;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::attr_nums::Fixed;
+
+    use super::*;
+
+    /// 非浮点数值类型：下限向上取整、上限向下取整
+    #[test]
+    fn test_clamp_by_integer() {
+        let mut attr = BoundedAttr::<i64>::new(5);
+        attr.apply_eff(-10);
+        attr.clamp_by(-0.5, 99.9);
+        assert_eq!(attr.get_pending_value(), 0);
+        attr.apply_eff(200);
+        attr.clamp_by(-0.5, 99.9);
+        assert_eq!(attr.get_pending_value(), 99);
+        assert_eq!(attr.get_snapshot_value(), 5);
+        attr.commit_pending_value();
+        assert_eq!(attr.get_snapshot_value(), 99);
+    }
+
+    /// 定点数：足够时才扣除
+    #[test]
+    fn test_apply_eff_checked_fixed() {
+        let mut attr = BoundedAttr::new(Fixed::from_int(10));
+        let cost = Fixed::ZERO - Fixed::from_f64(10.5, RoundingMode::Nearest);
+        assert!(!attr.apply_eff_checked(-100.0, cost, Fixed::ZERO));
+        assert_eq!(attr.get_pending_value(), Fixed::from_int(10));
+        assert!(attr.apply_eff_checked(0.0, Fixed::ZERO - Fixed::from_int(10), Fixed::ZERO));
+        assert_eq!(attr.get_pending_value(), Fixed::ZERO);
+    }
+}
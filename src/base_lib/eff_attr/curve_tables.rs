@@ -0,0 +1,466 @@
+//! 曲线表，按等级等自变量取值，如 “第 N 级的生命基础值”
+//!
+//! - 每条曲线由若干 `(x, y)` 关键点组成，关键点之间按常量、线性或三次插值
+//! - 超出关键点范围时钳制到端点，或沿端点线段外推
+//! - 曲线表按名称持有多条曲线，实现 [`LevelCurveSource`] ，可用于效果规格与各处基础值的计算
+//!
+//! 数据来源：
+//! - CSV ：首行为自变量，其余每行为一条曲线，首列为名称，空单元格表示该点无关键点
+//! - JSON ：需启用 `datadriven` 特性，见 [`CurveTable::from_json`]
+//!
+//! ```
+//! # use rust_engine_frame::base_lib::eff_attr::curve_tables::{CurveExtrap, CurveInterp, CurveTable};
+//! let table = CurveTable::<String>::from_csv(
+//!     "level,1,10,20\n\
+//!      health_base,100,550,\n",
+//!     CurveInterp::Linear,
+//!     CurveExtrap::Clamp,
+//! )
+//! .unwrap();
+//! assert_eq!(table.eval(&"health_base".to_string(), 5.5), Some(325.0));
+//! ```
+
+use std::fmt::Display;
+
+use rustc_hash::FxHashMap;
+
+use crate::base_lib::{cores::unify_types::FixedName, eff_attr::effect_specs::LevelCurveSource};
+
+/// 关键点之间的插值方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "datadriven", derive(serde::Deserialize))]
+pub enum CurveInterp {
+    /// 保持前一个关键点的值（阶梯）
+    Constant,
+    /// 线性
+    #[default]
+    Linear,
+    /// 三次 Hermite ，切线取相邻关键点的割线斜率，曲线平滑经过所有关键点
+    Cubic,
+}
+
+/// 超出关键点范围时的处理
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "datadriven", derive(serde::Deserialize))]
+pub enum CurveExtrap {
+    /// 钳制到端点值
+    #[default]
+    Clamp,
+    /// 沿端点线段的斜率外推，常量插值时同钳制
+    Extrapolate,
+}
+
+/// 单条曲线
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    /// 按 x 严格递增
+    keys: Vec<(f64, f64)>,
+    interp: CurveInterp,
+    extrap: CurveExtrap,
+}
+
+impl Curve {
+    /// 关键点必须非空、为有限数，且 x 严格递增
+    pub fn new(
+        keys: Vec<(f64, f64)>,
+        interp: CurveInterp,
+        extrap: CurveExtrap,
+    ) -> Result<Self, &'static str> {
+        if keys.is_empty() {
+            return Err("curve has no keys");
+        }
+        if keys.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+            return Err("curve key must be finite");
+        }
+        if keys.windows(2).any(|w| w[0].0 >= w[1].0) {
+            return Err("curve keys must be strictly ascending");
+        }
+        Ok(Self {
+            keys,
+            interp,
+            extrap,
+        })
+    }
+
+    /// 常量曲线，只有一个关键点
+    pub fn constant(y: f64) -> Self {
+        Self {
+            keys: vec![(0.0, y)],
+            interp: CurveInterp::Constant,
+            extrap: CurveExtrap::Clamp,
+        }
+    }
+
+    pub fn get_keys(&self) -> &[(f64, f64)] {
+        &self.keys
+    }
+
+    /// x 非有限时不外推：`+∞` 取最后一个关键点，`-∞` 与 NaN 取第一个关键点
+    pub fn eval(&self, x: f64) -> f64 {
+        let n = self.keys.len();
+        let (first, last) = (self.keys[0], self.keys[n - 1]);
+        if n == 1 {
+            return first.1;
+        }
+        if !x.is_finite() {
+            return if x == f64::INFINITY { last.1 } else { first.1 };
+        }
+
+        let extrapolate =
+            self.extrap == CurveExtrap::Extrapolate && self.interp != CurveInterp::Constant;
+        if x <= first.0 {
+            return if extrapolate {
+                first.1 + self.segment_slope(0) * (x - first.0)
+            } else {
+                first.1
+            };
+        }
+        if x >= last.0 {
+            return if extrapolate {
+                last.1 + self.segment_slope(n - 2) * (x - last.0)
+            } else {
+                last.1
+            };
+        }
+
+        // x 落在 [keys[i].0, keys[i + 1].0) 内
+        let i = self.keys.partition_point(|(kx, _)| *kx <= x) - 1;
+        let ((x0, y0), (x1, y1)) = (self.keys[i], self.keys[i + 1]);
+        let h = x1 - x0;
+        let t = (x - x0) / h;
+        match self.interp {
+            CurveInterp::Constant => y0,
+            CurveInterp::Linear => y0 + (y1 - y0) * t,
+            CurveInterp::Cubic => {
+                let (m0, m1) = (self.tangent(i), self.tangent(i + 1));
+                let (t2, t3) = (t * t, t * t * t);
+                (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                    + (t3 - 2.0 * t2 + t) * h * m0
+                    + (-2.0 * t3 + 3.0 * t2) * y1
+                    + (t3 - t2) * h * m1
+            }
+        }
+    }
+
+    /// 第 i 段的斜率
+    fn segment_slope(&self, i: usize) -> f64 {
+        let ((x0, y0), (x1, y1)) = (self.keys[i], self.keys[i + 1]);
+        (y1 - y0) / (x1 - x0)
+    }
+
+    /// 第 i 个关键点的切线，端点取单侧斜率
+    fn tangent(&self, i: usize) -> f64 {
+        let last = self.keys.len() - 1;
+        if i == 0 {
+            self.segment_slope(0)
+        } else if i == last {
+            self.segment_slope(last - 1)
+        } else {
+            let ((x0, y0), (x1, y1)) = (self.keys[i - 1], self.keys[i + 1]);
+            (y1 - y0) / (x1 - x0)
+        }
+    }
+}
+
+/// 加载失败的原因
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurveTableErrorKind {
+    /// 数据无法解析
+    Parse(String),
+    /// 名称为空
+    EmptyName,
+    /// 名称重复
+    DuplicateName,
+    /// 关键点非法
+    InvalidKeys(&'static str),
+}
+
+/// 加载错误，`path` 指向出错的位置（如 CSV 的 `line 3` ）
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurveTableError {
+    pub path: String,
+    pub kind: CurveTableErrorKind,
+}
+
+impl CurveTableError {
+    fn new(path: impl Into<String>, kind: CurveTableErrorKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+impl Display for CurveTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            CurveTableErrorKind::Parse(msg) => write!(f, "{}: parse error: {msg}", self.path),
+            CurveTableErrorKind::EmptyName => write!(f, "{}: empty name", self.path),
+            CurveTableErrorKind::DuplicateName => write!(f, "{}: duplicate name", self.path),
+            CurveTableErrorKind::InvalidKeys(reason) => write!(f, "{}: {reason}", self.path),
+        }
+    }
+}
+
+impl std::error::Error for CurveTableError {}
+
+/// 曲线表
+#[derive(Clone, Debug)]
+pub struct CurveTable<S: FixedName> {
+    curves: FxHashMap<S, Curve>,
+}
+
+impl<S: FixedName> Default for CurveTable<S> {
+    fn default() -> Self {
+        Self {
+            curves: FxHashMap::default(),
+        }
+    }
+}
+
+impl<S: FixedName> CurveTable<S> {
+    /// 添加曲线，同名时覆盖并返回旧曲线
+    pub fn insert(&mut self, name: S, curve: Curve) -> Option<Curve> {
+        self.curves.insert(name, curve)
+    }
+
+    pub fn get_curve(&self, name: &S) -> Option<&Curve> {
+        self.curves.get(name)
+    }
+
+    /// 曲线不存在时返回 None
+    pub fn eval(&self, name: &S, x: f64) -> Option<f64> {
+        self.curves.get(name).map(|curve| curve.eval(x))
+    }
+
+    pub fn len(&self) -> usize {
+        self.curves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+
+    /// 从 CSV 加载，所有曲线使用相同的插值与外推方式
+    ///
+    /// 空行与 `#` 开头的行被忽略
+    pub fn from_csv(
+        text: &str,
+        interp: CurveInterp,
+        extrap: CurveExtrap,
+    ) -> Result<Self, CurveTableError>
+    where
+        S: for<'a> From<&'a str>,
+    {
+        let parse_cell = |path: &str, cell: &str| {
+            cell.parse::<f64>().map_err(|e| {
+                CurveTableError::new(path, CurveTableErrorKind::Parse(format!("`{cell}`: {e}")))
+            })
+        };
+
+        let mut rows = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (format!("line {}", i + 1), line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let Some((path, header)) = rows.next() else {
+            return Ok(Self::default());
+        };
+        let xs = header
+            .split(',')
+            .skip(1)
+            .map(|cell| parse_cell(&path, cell.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut table = Self::default();
+        for (path, line) in rows {
+            let mut cells = line.split(',').map(str::trim);
+            let name = cells.next().unwrap_or_default();
+            if name.is_empty() {
+                return Err(CurveTableError::new(path, CurveTableErrorKind::EmptyName));
+            }
+
+            let mut keys = Vec::new();
+            for (i, cell) in cells.enumerate() {
+                if cell.is_empty() {
+                    continue;
+                }
+                let Some(x) = xs.get(i) else {
+                    return Err(CurveTableError::new(
+                        path,
+                        CurveTableErrorKind::Parse("more cells than header".to_string()),
+                    ));
+                };
+                keys.push((*x, parse_cell(&path, cell)?));
+            }
+            table.insert_checked(&path, S::from(name), keys, interp, extrap)?;
+        }
+        Ok(table)
+    }
+
+    fn insert_checked(
+        &mut self,
+        path: &str,
+        name: S,
+        keys: Vec<(f64, f64)>,
+        interp: CurveInterp,
+        extrap: CurveExtrap,
+    ) -> Result<(), CurveTableError> {
+        let curve = Curve::new(keys, interp, extrap).map_err(|reason| {
+            CurveTableError::new(path, CurveTableErrorKind::InvalidKeys(reason))
+        })?;
+        if self.curves.contains_key(&name) {
+            return Err(CurveTableError::new(
+                path,
+                CurveTableErrorKind::DuplicateName,
+            ));
+        }
+        self.curves.insert(name, curve);
+        Ok(())
+    }
+}
+
+impl<S: FixedName> LevelCurveSource<S> for CurveTable<S> {
+    fn eval_curve(&self, curve_name: &S, level: f64) -> Option<f64> {
+        self.eval(curve_name, level)
+    }
+}
+
+#[cfg(feature = "datadriven")]
+mod json_defs {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct CurveDef {
+        name: String,
+        #[serde(default)]
+        interp: CurveInterp,
+        #[serde(default)]
+        extrap: CurveExtrap,
+        keys: Vec<(f64, f64)>,
+    }
+
+    #[derive(Deserialize)]
+    struct CurveTableDef {
+        curves: Vec<CurveDef>,
+    }
+
+    impl<S: FixedName> CurveTable<S> {
+        /// 从 JSON 加载，每条曲线可单独指定插值与外推方式（默认线性、钳制）
+        ///
+        /// ```
+        /// # use rust_engine_frame::base_lib::eff_attr::curve_tables::CurveTable;
+        /// let table = CurveTable::<String>::from_json(
+        ///     r#"{ "curves": [
+        ///         { "name": "sharp", "interp": "Constant", "keys": [[1, 10], [5, 20]] }
+        ///     ] }"#,
+        /// )
+        /// .unwrap();
+        /// assert_eq!(table.eval(&"sharp".to_string(), 4.0), Some(10.0));
+        /// ```
+        pub fn from_json(text: &str) -> Result<Self, CurveTableError>
+        where
+            S: for<'a> From<&'a str>,
+        {
+            let def: CurveTableDef = serde_json::from_str(text)
+                .map_err(|e| CurveTableError::new("", CurveTableErrorKind::Parse(e.to_string())))?;
+
+            let mut table = Self::default();
+            for (i, curve) in def.curves.into_iter().enumerate() {
+                let path = format!("curves[{i}]({})", curve.name);
+                if curve.name.is_empty() {
+                    return Err(CurveTableError::new(path, CurveTableErrorKind::EmptyName));
+                }
+                table.insert_checked(
+                    &path,
+                    S::from(curve.name.as_str()),
+                    curve.keys,
+                    curve.interp,
+                    curve.extrap,
+                )?;
+            }
+            Ok(table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve_of(interp: CurveInterp, extrap: CurveExtrap) -> Curve {
+        Curve::new(vec![(1.0, 0.0), (2.0, 10.0), (4.0, 30.0)], interp, extrap).unwrap()
+    }
+
+    /// 三种插值与两种外推
+    #[test]
+    fn test_curve_eval() {
+        let curve = curve_of(CurveInterp::Constant, CurveExtrap::Extrapolate);
+        assert_eq!(curve.eval(1.5), 0.0);
+        assert_eq!(curve.eval(2.0), 10.0);
+        assert_eq!(curve.eval(9.0), 30.0);
+
+        let curve = curve_of(CurveInterp::Linear, CurveExtrap::Clamp);
+        assert_eq!(curve.eval(3.0), 20.0);
+        assert_eq!(curve.eval(0.0), 0.0);
+        assert_eq!(curve.eval(5.0), 30.0);
+
+        let curve = curve_of(CurveInterp::Linear, CurveExtrap::Extrapolate);
+        assert_eq!(curve.eval(0.0), -10.0);
+        assert_eq!(curve.eval(5.0), 40.0);
+
+        // 非有限的 x 取端点，不外推也不 panic
+        assert_eq!(curve.eval(f64::NAN), 0.0);
+        assert_eq!(curve.eval(f64::NEG_INFINITY), 0.0);
+        assert_eq!(curve.eval(f64::INFINITY), 30.0);
+
+        // 三次插值经过关键点，两点之间平滑
+        let curve = curve_of(CurveInterp::Cubic, CurveExtrap::Clamp);
+        assert_eq!(curve.eval(2.0), 10.0);
+        let v = curve.eval(3.0);
+        assert!(v > 10.0 && v < 30.0);
+
+        assert!(
+            Curve::new(
+                vec![(1.0, 0.0), (1.0, 1.0)],
+                CurveInterp::Linear,
+                CurveExtrap::Clamp
+            )
+            .is_err()
+        );
+    }
+
+    /// CSV 加载：空单元格跳过，错误指向行号
+    #[test]
+    fn test_from_csv() {
+        let table = CurveTable::<String>::from_csv(
+            "# 等级曲线\nlevel, 1, 5, 10\nsharp, 10, , 30\nmass, 2, 3, 4\n",
+            CurveInterp::Linear,
+            CurveExtrap::Clamp,
+        )
+        .unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.eval(&"sharp".to_string(), 5.5), Some(20.0));
+        assert_eq!(table.eval(&"mass".to_string(), 20.0), Some(4.0));
+        assert_eq!(table.eval(&"none".to_string(), 1.0), None);
+
+        let err = CurveTable::<String>::from_csv(
+            "level,1,2\nsharp,1,x\n",
+            CurveInterp::Linear,
+            CurveExtrap::Clamp,
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "line 2");
+
+        let err = CurveTable::<String>::from_csv(
+            "level,1,2\nsharp,1,2\nsharp,3,4\n",
+            CurveInterp::Linear,
+            CurveExtrap::Clamp,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, CurveTableErrorKind::DuplicateName);
+    }
+}
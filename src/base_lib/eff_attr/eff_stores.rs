@@ -0,0 +1,677 @@
+//! 效果存储的统一抽象，按效果数量选择后端
+//!
+//! - [`UpsertContainer`] 数组线性搜索，适用于 30 个以内的效果（大多数角色）
+//! - [`HashEffStore`] 数组加 id 索引，按 id 合并与删除为常数时间，适用于效果很多的单位（如世界首领）
+//! - [`SlotEffStore`] 额外通过 SlotMap 提供带版本号的稳定句柄，删除后句柄不会被重用
+//!
+//! 所有后端保持相同的语义：
+//! - 按插入顺序遍历，更新不改变顺序
+//! - 修改即置脏（见 [`UpsertContainer::iter_mut`] ）
+//! - 删除留下的空洞由 [`EffStore::try_clean_hole`] 按相同阈值回收
+
+use rustc_hash::FxHashMap;
+use slotmap::{DefaultKey, SlotMap};
+
+use crate::base_lib::eff_attr::upsert_container::{Upsert, UpsertContainer, should_compact};
+
+/// 效果存储，[`crate::base_lib::eff_attr::attr_systems`] 对其泛型
+pub trait EffStore<E: Upsert> {
+    /// 只读遍历，按插入顺序
+    fn iter_ele<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a;
+
+    /// 可变遍历，按插入顺序，创建即置脏
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut E>
+    where
+        E: 'a;
+
+    /// 添加或更新，若已有同 id 效果则对其进行修改
+    fn upsert_ele<F>(&mut self, new_ele: E, update_logic: F)
+    where
+        F: Fn(&mut E, E);
+
+    /// 添加或更新:同 id 整体替换
+    fn upsert_replace(&mut self, new_ele: E) {
+        self.upsert_ele(new_ele, Upsert::replace);
+    }
+
+    /// 删除第一个满足条件的元素（幂等）
+    fn delete_ele<F>(&mut self, find_logic: F) -> bool
+    where
+        F: Fn(&E) -> bool;
+
+    /// 取出第一个满足条件的元素
+    fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool;
+
+    /// 查询以更新，命中即置脏
+    fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
+    where
+        F: Fn(&E) -> bool;
+
+    /// 按 id 删除，带索引的后端为常数时间
+    fn delete_by_id(&mut self, id: &E::Id) -> bool {
+        self.delete_ele(|ele| ele.matched_id(id))
+    }
+
+    /// 按 id 查询以更新，带索引的后端为常数时间
+    fn select_mut_by_id(&mut self, id: &E::Id) -> Option<&mut E> {
+        self.select_mut_ele(|ele| ele.matched_id(id))
+    }
+
+    /// 获取当前效果个数
+    fn ele_len(&self) -> usize;
+
+    fn ele_empty(&self) -> bool {
+        self.ele_len() == 0
+    }
+
+    /// 清理空洞，阈值触发
+    fn try_clean_hole(&mut self);
+
+    /// 记录的空洞数与实际空洞数，用于 [`crate::base_lib::eff_attr::validations`] 校验
+    fn get_hole_counts(&self) -> (usize, usize);
+
+    /// id 索引是否与内容一致，无索引的后端恒为 true ，用于校验
+    fn is_index_consistent(&self) -> bool {
+        true
+    }
+
+    fn reset_changed_flag(&mut self);
+
+    fn is_changed(&self) -> bool;
+}
+
+impl<E: Upsert> EffStore<E> for UpsertContainer<E> {
+    fn iter_ele<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        Self::iter_ele(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut E>
+    where
+        E: 'a,
+    {
+        Self::iter_mut(self)
+    }
+
+    fn upsert_ele<F>(&mut self, new_ele: E, update_logic: F)
+    where
+        F: Fn(&mut E, E),
+    {
+        Self::upsert_ele(self, new_ele, update_logic)
+    }
+
+    fn delete_ele<F>(&mut self, find_logic: F) -> bool
+    where
+        F: Fn(&E) -> bool,
+    {
+        Self::delete_ele(self, find_logic)
+    }
+
+    fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        Self::take_ele(self, find_logic)
+    }
+
+    fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        Self::select_mut_ele(self, find_logic)
+    }
+
+    fn ele_len(&self) -> usize {
+        Self::ele_len(self)
+    }
+
+    fn try_clean_hole(&mut self) {
+        Self::try_clean_hole(self)
+    }
+
+    fn get_hole_counts(&self) -> (usize, usize) {
+        (self.get_hole_count(), self.count_holes())
+    }
+
+    fn reset_changed_flag(&mut self) {
+        Self::reset_changed_flag(self)
+    }
+
+    fn is_changed(&self) -> bool {
+        Self::is_changed(self)
+    }
+}
+
+/// 带 id 索引的效果存储，布局同 [`UpsertContainer`] ，额外维护 id 到下标的索引
+#[derive(Debug)]
+pub struct HashEffStore<E: Upsert> {
+    ll: Vec<Option<E>>,
+    index: FxHashMap<E::Id, usize>,
+    hole_count: usize,
+    changed_flag: bool,
+}
+
+impl<E: Upsert> Default for HashEffStore<E> {
+    fn default() -> Self {
+        Self {
+            ll: Default::default(),
+            index: Default::default(),
+            hole_count: 0,
+            changed_flag: false,
+        }
+    }
+}
+
+impl<E: Upsert> HashEffStore<E> {
+    fn locate_idx<F>(&self, find_logic: F) -> Option<usize>
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.ll
+            .iter()
+            .position(|e| e.as_ref().is_some_and(&find_logic))
+    }
+
+    /// 取出下标处的元素并移除索引
+    fn take_at(&mut self, idx: usize) -> Option<E> {
+        let ele = self.ll[idx].take()?;
+        self.index.remove(&ele.gen_id());
+        self.hole_count += 1;
+        self.changed_flag = true;
+        Some(ele)
+    }
+}
+
+impl<E: Upsert> EffStore<E> for HashEffStore<E> {
+    fn iter_ele<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        self.ll.iter().filter_map(|e| e.as_ref())
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut E>
+    where
+        E: 'a,
+    {
+        self.changed_flag = true;
+        self.ll.iter_mut().filter_map(|e| e.as_mut())
+    }
+
+    fn upsert_ele<F>(&mut self, new_ele: E, update_logic: F)
+    where
+        F: Fn(&mut E, E),
+    {
+        let id = new_ele.gen_id();
+        if let Some(old_ele) = self.index.get(&id).and_then(|idx| self.ll[*idx].as_mut()) {
+            update_logic(old_ele, new_ele);
+        } else {
+            self.index.insert(id, self.ll.len());
+            self.ll.push(Some(new_ele));
+        }
+        self.changed_flag = true;
+    }
+
+    fn delete_ele<F>(&mut self, find_logic: F) -> bool
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.locate_idx(find_logic)
+            .and_then(|idx| self.take_at(idx))
+            .is_some()
+    }
+
+    fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.locate_idx(find_logic)
+            .and_then(|idx| self.take_at(idx))
+    }
+
+    fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        let idx = self.locate_idx(find_logic)?;
+        self.changed_flag = true;
+        self.ll[idx].as_mut()
+    }
+
+    fn delete_by_id(&mut self, id: &E::Id) -> bool {
+        self.index
+            .get(id)
+            .copied()
+            .and_then(|idx| self.take_at(idx))
+            .is_some()
+    }
+
+    fn select_mut_by_id(&mut self, id: &E::Id) -> Option<&mut E> {
+        let idx = *self.index.get(id)?;
+        self.changed_flag = true;
+        self.ll[idx].as_mut()
+    }
+
+    fn ele_len(&self) -> usize {
+        self.ll.len() - self.hole_count
+    }
+
+    /// 压缩后重建索引
+    fn try_clean_hole(&mut self) {
+        if !should_compact(self.hole_count, self.ll.len()) {
+            return;
+        }
+        self.ll.retain(|e| e.is_some());
+        self.hole_count = 0;
+        self.index = self
+            .ll
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, e)| e.as_ref().map(|e| (e.gen_id(), idx)))
+            .collect();
+    }
+
+    fn get_hole_counts(&self) -> (usize, usize) {
+        let actual = self.ll.iter().filter(|e| e.is_none()).count();
+        (self.hole_count, actual)
+    }
+
+    fn is_index_consistent(&self) -> bool {
+        self.index.len() == self.ele_len()
+            && self.index.iter().all(|(id, idx)| {
+                self.ll
+                    .get(*idx)
+                    .and_then(|e| e.as_ref())
+                    .is_some_and(|e| e.matched_id(id))
+            })
+    }
+
+    fn reset_changed_flag(&mut self) {
+        self.changed_flag = false;
+    }
+
+    fn is_changed(&self) -> bool {
+        self.changed_flag
+    }
+}
+
+/// 基于 SlotMap 的效果存储
+///
+/// 元素按插入顺序存放于数组（布局同 [`UpsertContainer`] ），SlotMap 只发放带版本号的句柄并记录其下标，
+/// 删除后旧句柄因版本号失效，压缩时更新句柄的下标
+#[derive(Debug)]
+pub struct SlotEffStore<E: Upsert> {
+    ll: Vec<Option<(DefaultKey, E)>>,
+    slots: SlotMap<DefaultKey, usize>,
+    index: FxHashMap<E::Id, DefaultKey>,
+    hole_count: usize,
+    changed_flag: bool,
+}
+
+impl<E: Upsert> Default for SlotEffStore<E> {
+    fn default() -> Self {
+        Self {
+            ll: Vec::new(),
+            slots: SlotMap::new(),
+            index: Default::default(),
+            hole_count: 0,
+            changed_flag: false,
+        }
+    }
+}
+
+impl<E: Upsert> SlotEffStore<E> {
+    /// 效果的稳定句柄，效果被删除后句柄失效且不会被重用
+    pub fn get_key(&self, id: &E::Id) -> Option<DefaultKey> {
+        self.index.get(id).copied()
+    }
+
+    /// 按句柄只读访问
+    pub fn get_by_key(&self, key: DefaultKey) -> Option<&E> {
+        let idx = *self.slots.get(key)?;
+        self.ll[idx].as_ref().map(|(_, e)| e)
+    }
+
+    fn get_mut_by_key(&mut self, key: DefaultKey) -> Option<&mut E> {
+        let idx = *self.slots.get(key)?;
+        self.ll[idx].as_mut().map(|(_, e)| e)
+    }
+
+    fn locate_key<F>(&self, find_logic: F) -> Option<DefaultKey>
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.ll
+            .iter()
+            .flatten()
+            .find(|(_, e)| find_logic(e))
+            .map(|(key, _)| *key)
+    }
+
+    fn take_at(&mut self, key: DefaultKey) -> Option<E> {
+        let idx = self.slots.remove(key)?;
+        let (_, ele) = self.ll[idx].take()?;
+        self.index.remove(&ele.gen_id());
+        self.hole_count += 1;
+        self.changed_flag = true;
+        Some(ele)
+    }
+}
+
+impl<E: Upsert> EffStore<E> for SlotEffStore<E> {
+    fn iter_ele<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        self.ll.iter().flatten().map(|(_, e)| e)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut E>
+    where
+        E: 'a,
+    {
+        self.changed_flag = true;
+        self.ll.iter_mut().flatten().map(|(_, e)| e)
+    }
+
+    fn upsert_ele<F>(&mut self, new_ele: E, update_logic: F)
+    where
+        F: Fn(&mut E, E),
+    {
+        let id = new_ele.gen_id();
+        if let Some(old_ele) = self.get_key(&id).and_then(|key| self.get_mut_by_key(key)) {
+            update_logic(old_ele, new_ele);
+        } else {
+            let key = self.slots.insert(self.ll.len());
+            self.index.insert(id, key);
+            self.ll.push(Some((key, new_ele)));
+        }
+        self.changed_flag = true;
+    }
+
+    fn delete_ele<F>(&mut self, find_logic: F) -> bool
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.locate_key(find_logic)
+            .and_then(|key| self.take_at(key))
+            .is_some()
+    }
+
+    fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        self.locate_key(find_logic)
+            .and_then(|key| self.take_at(key))
+    }
+
+    fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        let key = self.locate_key(find_logic)?;
+        self.changed_flag = true;
+        self.get_mut_by_key(key)
+    }
+
+    fn delete_by_id(&mut self, id: &E::Id) -> bool {
+        self.get_key(id).and_then(|key| self.take_at(key)).is_some()
+    }
+
+    fn select_mut_by_id(&mut self, id: &E::Id) -> Option<&mut E> {
+        let key = self.get_key(id)?;
+        self.changed_flag = true;
+        self.get_mut_by_key(key)
+    }
+
+    fn ele_len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// 压缩后更新句柄的下标，句柄本身不变
+    fn try_clean_hole(&mut self) {
+        if !should_compact(self.hole_count, self.ll.len()) {
+            return;
+        }
+        self.ll.retain(|e| e.is_some());
+        self.hole_count = 0;
+        for (idx, (key, _)) in self.ll.iter().flatten().enumerate() {
+            self.slots[*key] = idx;
+        }
+    }
+
+    fn get_hole_counts(&self) -> (usize, usize) {
+        let actual = self.ll.iter().filter(|e| e.is_none()).count();
+        (self.hole_count, actual)
+    }
+
+    /// 索引、句柄与数组三者互相指向
+    fn is_index_consistent(&self) -> bool {
+        self.index.len() == self.slots.len()
+            && self.ll.len() - self.hole_count == self.slots.len()
+            && self.index.iter().all(|(id, key)| {
+                self.slots
+                    .get(*key)
+                    .and_then(|idx| self.ll.get(*idx))
+                    .and_then(|e| e.as_ref())
+                    .is_some_and(|(k, e)| k == key && e.matched_id(id))
+            })
+    }
+
+    fn reset_changed_flag(&mut self) {
+        self.changed_flag = false;
+    }
+
+    fn is_changed(&self) -> bool {
+        self.changed_flag
+    }
+}
+
+/// 转发到被借用的存储，便于对 `&mut C` 的集合（如 `[(&mut StatAttr, &mut Effs)]` ）直接调用属性系统
+impl<E: Upsert, C: EffStore<E>> EffStore<E> for &mut C {
+    fn iter_ele<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        C::iter_ele(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut E>
+    where
+        E: 'a,
+    {
+        C::iter_mut(self)
+    }
+
+    fn upsert_ele<F>(&mut self, new_ele: E, update_logic: F)
+    where
+        F: Fn(&mut E, E),
+    {
+        C::upsert_ele(self, new_ele, update_logic)
+    }
+
+    fn delete_ele<F>(&mut self, find_logic: F) -> bool
+    where
+        F: Fn(&E) -> bool,
+    {
+        C::delete_ele(self, find_logic)
+    }
+
+    fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        C::take_ele(self, find_logic)
+    }
+
+    fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        C::select_mut_ele(self, find_logic)
+    }
+
+    fn delete_by_id(&mut self, id: &E::Id) -> bool {
+        C::delete_by_id(self, id)
+    }
+
+    fn select_mut_by_id(&mut self, id: &E::Id) -> Option<&mut E> {
+        C::select_mut_by_id(self, id)
+    }
+
+    fn ele_len(&self) -> usize {
+        C::ele_len(self)
+    }
+
+    fn try_clean_hole(&mut self) {
+        C::try_clean_hole(self)
+    }
+
+    fn get_hole_counts(&self) -> (usize, usize) {
+        C::get_hole_counts(self)
+    }
+
+    fn is_index_consistent(&self) -> bool {
+        C::is_index_consistent(self)
+    }
+
+    fn reset_changed_flag(&mut self) {
+        C::reset_changed_flag(self)
+    }
+
+    fn is_changed(&self) -> bool {
+        C::is_changed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::timers::tick_timer::TickTimer,
+        eff_attr::{
+            effects::Effect,
+            stat_attr_effs::{StatAttrEff, StatAttrEffType},
+        },
+    };
+
+    use super::*;
+
+    type Eff = StatAttrEff<&'static str, TickTimer>;
+
+    fn eff_of(from_name: &'static str, val: f64) -> Eff {
+        StatAttrEff::new(
+            StatAttrEffType::BasicAdd,
+            Effect::new(from_name, "might", val),
+            TickTimer::inf(),
+        )
+    }
+
+    fn sources<C: EffStore<Eff>>(store: &C) -> Vec<(&'static str, f64)> {
+        store
+            .iter_ele()
+            .map(|e| {
+                (
+                    *e.get_effect().get_from_name(),
+                    e.get_effect().get_effect_value(),
+                )
+            })
+            .collect()
+    }
+
+    /// 同一组操作在各后端的遍历顺序、数量与脏标记一致
+    fn check_same_semantics<C: EffStore<Eff> + Default>() {
+        let mut store = C::default();
+        for (i, from_name) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+            store.upsert_replace(eff_of(from_name, i as f64));
+        }
+        assert!(store.is_changed());
+        store.reset_changed_flag();
+
+        // 更新不改变顺序
+        store.upsert_replace(eff_of("b", 10.0));
+        assert!(store.delete_ele(|e| *e.get_effect().get_from_name() == "a"));
+        let id = eff_of("c", 0.0).gen_id();
+        assert!(store.delete_by_id(&id));
+        assert!(!store.delete_by_id(&id));
+        store.upsert_replace(eff_of("a", 20.0));
+        let taken = store.take_ele(|e| *e.get_effect().get_from_name() == "d");
+        assert!(taken.is_some());
+        assert!(store.is_changed());
+
+        store.try_clean_hole();
+        store.reset_changed_flag();
+        let id = eff_of("e", 0.0).gen_id();
+        store
+            .select_mut_by_id(&id)
+            .unwrap()
+            .get_effect_mut()
+            .set_effect_value(40.0);
+        assert!(store.is_changed());
+        for ele in store.iter_mut() {
+            let v = ele.get_effect().get_effect_value();
+            ele.get_effect_mut().set_effect_value(v + 1.0);
+        }
+
+        assert_eq!(store.ele_len(), 3);
+        assert_eq!(sources(&store), vec![("b", 11.0), ("e", 41.0), ("a", 21.0)]);
+    }
+
+    #[test]
+    fn test_backends_share_semantics() {
+        check_same_semantics::<UpsertContainer<Eff>>();
+        check_same_semantics::<HashEffStore<Eff>>();
+        check_same_semantics::<SlotEffStore<Eff>>();
+    }
+
+    /// 句柄在删除后失效，重新添加得到新句柄
+    #[test]
+    fn test_slot_key_not_reused() {
+        let mut store = SlotEffStore::default();
+        store.upsert_replace(eff_of("a", 1.0));
+        let id = eff_of("a", 0.0).gen_id();
+        let key = store.get_key(&id).unwrap();
+        store.delete_by_id(&id);
+        store.upsert_replace(eff_of("a", 2.0));
+        assert!(store.get_by_key(key).is_none());
+        let new_key = store.get_key(&id).unwrap();
+        assert_eq!(
+            store
+                .get_by_key(new_key)
+                .unwrap()
+                .get_effect()
+                .get_effect_value(),
+            2.0
+        );
+    }
+
+    /// 压缩空洞后句柄仍指向原效果，遍历顺序不变
+    #[test]
+    fn test_slot_key_survives_compaction() {
+        let mut store = SlotEffStore::default();
+        for (i, from_name) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+            store.upsert_replace(eff_of(from_name, i as f64));
+        }
+        let key = store.get_key(&eff_of("e", 0.0).gen_id()).unwrap();
+        for from_name in ["a", "b", "c"] {
+            store.delete_by_id(&eff_of(from_name, 0.0).gen_id());
+        }
+        store.try_clean_hole();
+        assert_eq!(store.ll.len(), 2);
+        assert_eq!(
+            store
+                .get_by_key(key)
+                .unwrap()
+                .get_effect()
+                .get_effect_value(),
+            4.0
+        );
+        assert_eq!(sources(&store), vec![("d", 3.0), ("e", 4.0)]);
+    }
+}
@@ -0,0 +1,311 @@
+//! 效果规格，在 [`Effect`] 之外携带玩法元数据，避免将元数据编码进名称
+//!
+//! - 授予标签：效果存在期间授予目标的标签，由上层的标签系统添加与移除
+//! - 资产标签：描述效果本身，如 “火焰” “可驱散” ，用于查询与免疫判断
+//! - 层数与等级：效果值为 `单层效果值 * 层数` ，单层效果值可按等级查曲线
+//! - 调用方设定值：效果值在生成时才确定（如技能蓄力时长），由调用方按名称设定
+//!
+//! 规格本身不参与计算，计算出效果值后转换为现有的效果类型：
+//! [`StatAttrEff`] [`BoundAttrEff`] [`AttrAlterEff`] ，以及业务层的伤害效果
+
+use crate::base_lib::{
+    cores::unify_types::FixedName,
+    eff_attr::{
+        bound_attr_effs::{BoundAttrEff, BoundAttrEffType},
+        bounded_attr_effs::{AttrAlterEff, AttrAlterEffType},
+        effects::Effect,
+        stat_attr_effs::{StatAttrEff, StatAttrEffType},
+    },
+};
+
+/// 按等级取值的曲线来源，如曲线表
+pub trait LevelCurveSource<S> {
+    /// 曲线不存在时返回 None
+    fn eval_curve(&self, curve_name: &S, level: f64) -> Option<f64>;
+}
+
+/// 不提供任何曲线，用于不需要按等级取值的场景
+#[derive(Debug, Default)]
+pub struct NoCurves;
+
+impl<S> LevelCurveSource<S> for NoCurves {
+    fn eval_curve(&self, _curve_name: &S, _level: f64) -> Option<f64> {
+        None
+    }
+}
+
+/// 单层效果值的来源
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecMagnitude<S> {
+    /// 固定值
+    Scalar(f64),
+    /// 按效果等级查曲线，再乘以系数
+    LevelCurve { curve_name: S, coefficient: f64 },
+    /// 由调用方按名称设定
+    SetByCaller(S),
+}
+
+/// 计算效果值失败的原因
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecMagnitudeError<S> {
+    /// 曲线不存在
+    UnknownCurve(S),
+    /// 调用方未设定该名称的值
+    MissingCallerMagnitude(S),
+}
+
+/// 效果规格
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectSpec<S: FixedName> {
+    from_name: S,
+    effect_name: S,
+    magnitude: SpecMagnitude<S>,
+    /// 效果等级，从 1 开始
+    level: u32,
+    /// 层数，效果值按层数倍乘
+    stack_count: u32,
+    granted_tags: Vec<S>,
+    asset_tags: Vec<S>,
+    /// 调用方设定值，数量很少，使用数组
+    caller_magnitudes: Vec<(S, f64)>,
+}
+
+impl<S: FixedName> EffectSpec<S> {
+    pub fn new(from_name: S, effect_name: S, magnitude: SpecMagnitude<S>) -> Self {
+        Self {
+            from_name,
+            effect_name,
+            magnitude,
+            level: 1,
+            stack_count: 1,
+            granted_tags: Vec::new(),
+            asset_tags: Vec::new(),
+            caller_magnitudes: Vec::new(),
+        }
+    }
+
+    pub fn with_level(mut self, level: u32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_stack_count(mut self, stack_count: u32) -> Self {
+        self.stack_count = stack_count;
+        self
+    }
+
+    pub fn with_granted_tag(mut self, tag: S) -> Self {
+        self.granted_tags.push(tag);
+        self
+    }
+
+    pub fn with_asset_tag(mut self, tag: S) -> Self {
+        self.asset_tags.push(tag);
+        self
+    }
+
+    /// 设定调用方的值，同名覆盖
+    pub fn set_caller_magnitude(&mut self, name: S, value: f64) {
+        if let Some((_, v)) = self.caller_magnitudes.iter_mut().find(|(n, _)| *n == name) {
+            *v = value;
+        } else {
+            self.caller_magnitudes.push((name, value));
+        }
+    }
+
+    pub fn with_caller_magnitude(mut self, name: S, value: f64) -> Self {
+        self.set_caller_magnitude(name, value);
+        self
+    }
+
+    // region: getter setter
+
+    pub fn get_from_name(&self) -> &S {
+        &self.from_name
+    }
+
+    pub fn get_effect_name(&self) -> &S {
+        &self.effect_name
+    }
+
+    pub fn get_magnitude(&self) -> &SpecMagnitude<S> {
+        &self.magnitude
+    }
+
+    pub fn get_level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: u32) {
+        self.level = level;
+    }
+
+    pub fn get_stack_count(&self) -> u32 {
+        self.stack_count
+    }
+
+    pub fn set_stack_count(&mut self, stack_count: u32) {
+        self.stack_count = stack_count;
+    }
+
+    pub fn get_granted_tags(&self) -> &[S] {
+        &self.granted_tags
+    }
+
+    pub fn get_asset_tags(&self) -> &[S] {
+        &self.asset_tags
+    }
+
+    pub fn has_asset_tag(&self, tag: &S) -> bool {
+        self.asset_tags.contains(tag)
+    }
+
+    pub fn get_caller_magnitude(&self, name: &S) -> Option<f64> {
+        self.caller_magnitudes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| *v)
+    }
+
+    // endregion
+
+    /// 单层效果值
+    pub fn calc_single_magnitude(
+        &self,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<f64, SpecMagnitudeError<S>> {
+        match &self.magnitude {
+            SpecMagnitude::Scalar(v) => Ok(*v),
+            SpecMagnitude::LevelCurve {
+                curve_name,
+                coefficient,
+            } => curves
+                .eval_curve(curve_name, self.level as f64)
+                .map(|v| v * coefficient)
+                .ok_or_else(|| SpecMagnitudeError::UnknownCurve(curve_name.clone())),
+            SpecMagnitude::SetByCaller(name) => self
+                .get_caller_magnitude(name)
+                .ok_or_else(|| SpecMagnitudeError::MissingCallerMagnitude(name.clone())),
+        }
+    }
+
+    /// 效果值，单层效果值按层数倍乘
+    pub fn calc_magnitude(
+        &self,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<f64, SpecMagnitudeError<S>> {
+        Ok(self.calc_single_magnitude(curves)? * self.stack_count as f64)
+    }
+
+    /// 计算效果值，生成 [`Effect`]
+    pub fn to_effect(
+        &self,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<Effect<S>, SpecMagnitudeError<S>> {
+        Ok(Effect::new(
+            self.from_name.clone(),
+            self.effect_name.clone(),
+            self.calc_magnitude(curves)?,
+        ))
+    }
+
+    pub fn to_stat_attr_eff<Timer>(
+        &self,
+        eff_type: StatAttrEffType,
+        duration: Timer,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<StatAttrEff<S, Timer>, SpecMagnitudeError<S>> {
+        Ok(StatAttrEff::new(
+            eff_type,
+            self.to_effect(curves)?,
+            duration,
+        ))
+    }
+
+    pub fn to_bound_attr_eff<Timer>(
+        &self,
+        eff_type: BoundAttrEffType,
+        duration: Timer,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<BoundAttrEff<S, Timer>, SpecMagnitudeError<S>> {
+        Ok(BoundAttrEff::new(
+            eff_type,
+            self.to_effect(curves)?,
+            duration,
+        ))
+    }
+
+    pub fn to_attr_alter_eff(
+        &self,
+        eff_type: AttrAlterEffType,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<AttrAlterEff<S>, SpecMagnitudeError<S>> {
+        Ok(AttrAlterEff::new(eff_type, self.to_effect(curves)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::tick_timer::TickTimer;
+
+    use super::*;
+
+    /// 每级 +10
+    struct LinearCurves;
+
+    impl LevelCurveSource<&'static str> for LinearCurves {
+        fn eval_curve(&self, curve_name: &&'static str, level: f64) -> Option<f64> {
+            (*curve_name == "per_level").then_some(level * 10.0)
+        }
+    }
+
+    /// 等级曲线、系数与层数
+    #[test]
+    fn test_level_curve_and_stacks() {
+        let spec = EffectSpec::new(
+            "alice",
+            "poison",
+            SpecMagnitude::LevelCurve {
+                curve_name: "per_level",
+                coefficient: -0.5,
+            },
+        )
+        .with_level(4)
+        .with_stack_count(3)
+        .with_asset_tag("dispellable")
+        .with_granted_tag("poisoned");
+
+        assert_eq!(spec.calc_single_magnitude(&LinearCurves), Ok(-20.0));
+        let eff = spec.to_effect(&LinearCurves).unwrap();
+        assert_eq!(eff.get_effect_value(), -60.0);
+        assert!(spec.has_asset_tag(&"dispellable"));
+        assert_eq!(spec.get_granted_tags(), &["poisoned"]);
+
+        assert_eq!(
+            spec.to_effect(&NoCurves),
+            Err(SpecMagnitudeError::UnknownCurve("per_level"))
+        );
+    }
+
+    /// 调用方设定值，转换为现有效果类型
+    #[test]
+    fn test_set_by_caller() {
+        let mut spec = EffectSpec::new("bob", "charge", SpecMagnitude::SetByCaller("held"));
+        assert_eq!(
+            spec.calc_magnitude(&NoCurves),
+            Err(SpecMagnitudeError::MissingCallerMagnitude("held"))
+        );
+
+        spec.set_caller_magnitude("held", 1.0);
+        spec.set_caller_magnitude("held", 2.5);
+        let eff = spec
+            .to_stat_attr_eff(StatAttrEffType::BasicAdd, TickTimer::inf(), &NoCurves)
+            .unwrap();
+        assert_eq!(eff.get_effect().get_effect_value(), 2.5);
+
+        let eff = spec
+            .to_attr_alter_eff(AttrAlterEffType::Val, &NoCurves)
+            .unwrap();
+        assert!(matches!(eff.get_type(), AttrAlterEffType::Val));
+    }
+}
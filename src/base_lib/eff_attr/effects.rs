@@ -1,3 +1,8 @@
+use crate::base_lib::cores::{
+    tiny_tags::{PureTagContainer, TinyTag},
+    unify_types::FixedName,
+};
+
 /// 效果描述 不实现具体效果
 ///
 /// 可与计时器组合实现复杂效果：
@@ -12,7 +17,7 @@
 /// - 某效果根据延迟生效的时长增加伤害，施加重置延迟效果，在最后造成大量伤害，这种机制也可替换成堆叠效果组合（同时施加重置延迟和堆叠层数两种效果）
 ///
 /// 注意：若允许不同来源的效果可叠加，那么必然会导致伤害结算存在误差：叠加产生的额外收益算谁的，这划分给谁都不合适，也许可以算成团队收益
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Effect<S> {
     /// 效果来源，始终是角色名称，一般用于结算记录
     from_name: S,
@@ -66,6 +71,25 @@ impl<S> Effect<S> {
     // endregion
 }
 
+/// 带生效条件的持久效果，条件不满足时保留在容器中但不参与属性计算
+///
+/// 条件的求值结果缓存在效果内，只有翻转时才需要重新计算属性
+pub trait ConditionalEffect<S: FixedName> {
+    /// 生效条件，None 表示无条件生效
+    fn get_condition(&self) -> Option<&TinyTag<S>>;
+
+    /// 上次求值的结果
+    fn is_condition_met(&self) -> bool;
+
+    fn set_condition_met(&mut self, met: bool);
+
+    /// 按标签求值，返回结果是否与上次不同
+    fn is_condition_flipped(&self, tags: &impl PureTagContainer<PureTag = S>) -> bool {
+        self.get_condition()
+            .is_some_and(|cond| cond.check_condition(tags) != self.is_condition_met())
+    }
+}
+
 /// 判断增益或减益效果
 pub trait EffectMeaning {
     /// 判断增益或减益效果
@@ -73,7 +97,7 @@ pub trait EffectMeaning {
 }
 
 /// 增益或减益效果标识
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EffectMean {
     /// 减益效果
     Bad,
@@ -0,0 +1,406 @@
+//! 对效果生效的效果（拦截器），如“受到的治疗 +10%”“受到的火焰伤害 -30%”
+//!
+//! 插件模式：拦截器存放在独立的效果容器中，每次计算单次效果的数值时检查
+//!
+//! - 按目标、效果名称、增益减益匹配，未指定的维度视为任意
+//! - 命中后缩放或替换数值
+//! - 拥有独立的生命周期（计时器），可用 [`super::attr_systems::clean_expired_element`] 清理
+//! - 生命周期为次数时，每次结算事件中命中即消耗一次（与拦截的效果个数无关），见 [`InterceptCharge`]
+//! - 按计算阶段区分，避免同一拦截器在管线的多个环节重复生效
+
+use crate::base_lib::{
+    cores::{
+        timers::{
+            charge_counters::{ChargeCounter, ChargeEvent},
+            static_timer::StaticTimer,
+            tick_timer::TickTimer,
+            tiny_timer::HasTimer,
+        },
+        unify_types::FixedName,
+    },
+    eff_attr::{
+        effects::{Effect, EffectMean},
+        upsert_container::{Upsert, UpsertContainer},
+    },
+};
+
+/// 拦截后的数值处理方式，参数为 [`Effect`] 的效果值
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterceptKind {
+    /// 缩放，如 `1.1` 表示 +10%
+    Scale,
+    /// 替换为固定值，如“本次伤害固定为 1 ”
+    Replace,
+}
+
+/// 拦截器生效的计算阶段，由使用方的管线定义其含义
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterceptPhase {
+    /// 原始值阶段（百分比已折算为绝对值，尚未经过来源缩放）
+    Raw,
+    /// 缩放后阶段（即将作用到属性上的最终值）
+    Scaled,
+}
+
+/// 拦截器
+#[derive(Clone, Debug)]
+pub struct EffInterceptor<S: FixedName, Target, Timer> {
+    kind: InterceptKind,
+    phase: InterceptPhase,
+    /// 匹配的目标，None 表示任意
+    match_target: Option<Target>,
+    /// 匹配的效果名称，None 表示任意
+    match_eff_name: Option<S>,
+    /// 匹配被拦截效果的增益减益（按数值正负判断），None 表示任意
+    match_nature: Option<EffectMean>,
+    /// 拦截器自身的来源、名称和参数
+    eff: Effect<S>,
+    duration: Timer,
+}
+
+impl<S: FixedName, Target: Copy + Eq, Timer> EffInterceptor<S, Target, Timer> {
+    /// 默认匹配任意被拦截效果，通过 `with_*` 收窄
+    pub fn new(
+        kind: InterceptKind,
+        phase: InterceptPhase,
+        eff: Effect<S>,
+        duration: Timer,
+    ) -> Self {
+        Self {
+            kind,
+            phase,
+            match_target: None,
+            match_eff_name: None,
+            match_nature: None,
+            eff,
+            duration,
+        }
+    }
+
+    pub fn with_target(mut self, target: Target) -> Self {
+        self.match_target = Some(target);
+        self
+    }
+
+    pub fn with_eff_name(mut self, eff_name: S) -> Self {
+        self.match_eff_name = Some(eff_name);
+        self
+    }
+
+    pub fn with_nature(mut self, nature: EffectMean) -> Self {
+        self.match_nature = Some(nature);
+        self
+    }
+
+    pub fn get_kind(&self) -> InterceptKind {
+        self.kind
+    }
+
+    pub fn get_phase(&self) -> InterceptPhase {
+        self.phase
+    }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    /// 是否命中被拦截的效果（按效果当前值判断增益减益）
+    pub fn is_matched(
+        &self,
+        phase: InterceptPhase,
+        target: Target,
+        intercepted: &Effect<S>,
+    ) -> bool {
+        self.phase == phase
+            && self.match_target.is_none_or(|t| t == target)
+            && self
+                .match_eff_name
+                .as_ref()
+                .is_none_or(|name| name == intercepted.get_effect_name())
+            && self.match_nature.is_none_or(|nature| {
+                nature == EffectMean::which_nature(intercepted.get_effect_value(), 0.0)
+            })
+    }
+}
+
+impl<S: FixedName, Target, Timer> HasTimer for EffInterceptor<S, Target, Timer> {
+    type Timer = Timer;
+
+    fn get_timer(&self) -> &Self::Timer {
+        &self.duration
+    }
+
+    fn get_timer_mut(&mut self) -> &mut Self::Timer {
+        &mut self.duration
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EffInterceptorId<S: FixedName> {
+    pub eff: S,
+    pub from: S,
+}
+
+impl<S: FixedName, Target, Timer> Upsert for EffInterceptor<S, Target, Timer> {
+    type Id = EffInterceptorId<S>;
+
+    fn gen_id(&self) -> Self::Id {
+        EffInterceptorId {
+            eff: self.eff.get_effect_name().clone(),
+            from: self.eff.get_from_name().clone(),
+        }
+    }
+
+    fn matched_id(&self, id: &Self::Id) -> bool {
+        *self.eff.get_effect_name() == id.eff && *self.eff.get_from_name() == id.from
+    }
+
+    fn has_same_id(&self, other: &Self) -> bool {
+        self.eff.get_effect_name() == other.eff.get_effect_name()
+            && self.eff.get_from_name() == other.eff.get_from_name()
+    }
+}
+
+/// 拦截器的生命周期是否随拦截消耗，如“抵挡下一次攻击”
+///
+/// 计时器不随拦截消耗；[`ChargeCounter`] 响应事件时每次事件消耗一次，耗尽后不再命中
+pub trait InterceptCharge {
+    /// 是否还能拦截
+    fn can_intercept(&self, _event: ChargeEvent) -> bool {
+        true
+    }
+
+    /// 一次事件中拦截过效果后调用一次
+    fn on_intercepted(&mut self, _event: ChargeEvent) {}
+}
+
+impl InterceptCharge for TickTimer {}
+
+impl InterceptCharge for StaticTimer {}
+
+impl InterceptCharge for ChargeCounter {
+    fn can_intercept(&self, event: ChargeEvent) -> bool {
+        !self.is_consumed_by(event) || self.get_remaining() > 0
+    }
+
+    fn on_intercepted(&mut self, event: ChargeEvent) {
+        self.try_consume(event);
+    }
+}
+
+/// 类型别名 拦截器的容器
+pub type EffInterceptors<S, Target, Timer> = UpsertContainer<EffInterceptor<S, Target, Timer>>;
+
+fn fold_intercepted<'a, S, Target, Timer>(
+    matched: impl Iterator<Item = &'a EffInterceptor<S, Target, Timer>>,
+    intercepted: &Effect<S>,
+) -> f64
+where
+    S: FixedName + 'a,
+    Target: 'a,
+    Timer: 'a,
+{
+    let mut replaced = None;
+    let mut scale = 1.0;
+    for interceptor in matched {
+        let v = interceptor.eff.get_effect_value();
+        match interceptor.kind {
+            InterceptKind::Scale => scale *= v,
+            InterceptKind::Replace => replaced = Some(v),
+        }
+    }
+    replaced.unwrap_or(intercepted.get_effect_value()) * scale
+}
+
+/// 计算拦截后的效果值
+///
+/// 冲突规则：先替换（多个替换取容器中靠后者），再连乘所有缩放，结果与容器内顺序无关（替换除外）
+pub fn intercept_eff_value<S, Target, Timer>(
+    interceptors: &EffInterceptors<S, Target, Timer>,
+    phase: InterceptPhase,
+    target: Target,
+    intercepted: &Effect<S>,
+) -> f64
+where
+    S: FixedName,
+    Target: Copy + Eq,
+{
+    fold_intercepted(
+        interceptors
+            .iter_ele()
+            .filter(|ele| ele.is_matched(phase, target, intercepted)),
+        intercepted,
+    )
+}
+
+/// 一次结算事件中命中的拦截器，事件结束后经 [`consume_intercept_charges`] 消耗次数
+pub type InterceptHits<S> = Vec<EffInterceptorId<S>>;
+
+/// 计算拦截后的效果值，并记录命中的拦截器，不消耗次数
+///
+/// 冲突规则同 [`intercept_eff_value`]，次数已耗尽的拦截器不命中；
+/// 同一事件中的多个效果共用 `hits` ，事件结束后统一消耗，每个拦截器只消耗一次
+pub fn intercept_eff_value_recorded<S, Target, Timer>(
+    interceptors: &EffInterceptors<S, Target, Timer>,
+    phase: InterceptPhase,
+    target: Target,
+    intercepted: &Effect<S>,
+    event: ChargeEvent,
+    hits: &mut InterceptHits<S>,
+) -> f64
+where
+    S: FixedName,
+    Target: Copy + Eq,
+    Timer: InterceptCharge,
+{
+    let matched: Vec<_> = interceptors
+        .iter_ele()
+        .filter(|ele| {
+            ele.is_matched(phase, target, intercepted) && ele.duration.can_intercept(event)
+        })
+        .collect();
+    for interceptor in matched.iter() {
+        let id = interceptor.gen_id();
+        if !hits.contains(&id) {
+            hits.push(id);
+        }
+    }
+    fold_intercepted(matched.into_iter(), intercepted)
+}
+
+/// 命中的拦截器按 `event` 各消耗一次次数，无命中时不置脏
+pub fn consume_intercept_charges<S, Target, Timer>(
+    interceptors: &mut EffInterceptors<S, Target, Timer>,
+    hits: &InterceptHits<S>,
+    event: ChargeEvent,
+) where
+    S: FixedName,
+    Timer: InterceptCharge,
+{
+    if hits.is_empty() {
+        return;
+    }
+    for interceptor in interceptors.iter_mut() {
+        if hits.iter().any(|id| interceptor.matched_id(id)) {
+            interceptor.duration.on_intercepted(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::tiny_timer::TimerView;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Element {
+        Fire,
+        Ice,
+    }
+
+    fn scale(name: &'static str, v: f64) -> EffInterceptor<&'static str, Element, TickTimer> {
+        EffInterceptor::new(
+            InterceptKind::Scale,
+            InterceptPhase::Scaled,
+            Effect::new("buff", name, v),
+            TickTimer::inf(),
+        )
+    }
+
+    /// 按目标、名称、增益减益匹配
+    #[test]
+    fn test_match_dimensions() {
+        let mut interceptors = EffInterceptors::default();
+        interceptors.upsert_replace(scale("fire_resist", 0.7).with_target(Element::Fire));
+        interceptors.upsert_replace(scale("heal_up", 1.1).with_nature(EffectMean::Good));
+        interceptors.upsert_replace(scale("anti_bomb", 0.5).with_eff_name("bomb"));
+
+        let phase = InterceptPhase::Scaled;
+        let fire_hit = Effect::new("enemy", "fireball", -100.0);
+        let v = intercept_eff_value(&interceptors, phase, Element::Fire, &fire_hit);
+        assert!((v + 70.0).abs() < 1e-9);
+        let v = intercept_eff_value(&interceptors, phase, Element::Ice, &fire_hit);
+        assert_eq!(v, -100.0);
+
+        let heal = Effect::new("ally", "renew", 100.0);
+        let v = intercept_eff_value(&interceptors, phase, Element::Ice, &heal);
+        assert!((v - 110.0).abs() < 1e-9);
+
+        let bomb = Effect::new("enemy", "bomb", -100.0);
+        let v = intercept_eff_value(&interceptors, phase, Element::Fire, &bomb);
+        assert!((v + 35.0).abs() < 1e-9);
+
+        // 阶段不同不生效
+        let v = intercept_eff_value(&interceptors, InterceptPhase::Raw, Element::Fire, &bomb);
+        assert_eq!(v, -100.0);
+    }
+
+    /// 先替换后缩放
+    #[test]
+    fn test_replace_then_scale() {
+        let mut interceptors = EffInterceptors::default();
+        interceptors.upsert_replace(scale("armor", 0.5));
+        interceptors.upsert_replace(EffInterceptor::new(
+            InterceptKind::Replace,
+            InterceptPhase::Scaled,
+            Effect::new("boss", "fixed_hit", -40.0),
+            TickTimer::inf(),
+        ));
+
+        let hit = Effect::new("enemy", "slash", -100.0);
+        let v = intercept_eff_value(&interceptors, InterceptPhase::Scaled, Element::Ice, &hit);
+        assert_eq!(v, -20.0);
+    }
+
+    /// 次数拦截器在一次事件中拦截多个效果只消耗一次，耗尽后不再命中；不响应该事件的次数不消耗
+    #[test]
+    fn test_charged_intercept() {
+        let mut interceptors = EffInterceptors::default();
+        interceptors.upsert_replace(EffInterceptor::new(
+            InterceptKind::Replace,
+            InterceptPhase::Raw,
+            Effect::new("self", "parry", 0.0),
+            ChargeCounter::new(1, ChargeEvent::OnDamageTaken),
+        ));
+        interceptors.upsert_replace(EffInterceptor::new(
+            InterceptKind::Scale,
+            InterceptPhase::Raw,
+            Effect::new("self", "empower", 2.0),
+            ChargeCounter::new(1, ChargeEvent::OnHit),
+        ));
+
+        let phase = InterceptPhase::Raw;
+        let event = ChargeEvent::OnDamageTaken;
+        let hit = Effect::new("enemy", "slash", -10.0);
+        let mut hits = InterceptHits::new();
+        for _ in 0..2 {
+            let v = intercept_eff_value_recorded(
+                &interceptors,
+                phase,
+                Element::Fire,
+                &hit,
+                event,
+                &mut hits,
+            );
+            assert_eq!(v, 0.0);
+        }
+        assert_eq!(hits.len(), 2);
+        consume_intercept_charges(&mut interceptors, &hits, event);
+
+        let counters: Vec<_> = interceptors.iter_ele().map(|ele| ele.get_timer()).collect();
+        assert!(counters[0].is_completed(()));
+        assert_eq!(counters[1].get_remaining(), 1);
+
+        let mut hits = InterceptHits::new();
+        let v = intercept_eff_value_recorded(
+            &interceptors,
+            phase,
+            Element::Fire,
+            &hit,
+            event,
+            &mut hits,
+        );
+        assert_eq!(v, -20.0);
+    }
+}
@@ -1,6 +1,34 @@
+use crate::base_lib::{
+    cores::attr_nums::{AttrNum, RoundingMode},
+    eff_attr::stack_curves::{CurvedValue, StackCurve},
+};
+
 pub(super) const ADDITION_BASE_LINE: f64 = 0.0;
 pub(super) const PERCENT_BASE_LINE: f64 = 0.0;
 pub(super) const MULT_BASE_LINE: f64 = 1.0;
+pub(super) const FLOOR_BASE_LINE: f64 = f64::NEG_INFINITY;
+pub(super) const CAP_BASE_LINE: f64 = f64::INFINITY;
+
+/// 修改器各阶段结果落回属性数值类型时的舍入规则，浮点类型忽略
+///
+/// - 基础阶段 `base_value * (1 + b_per) + b_add` 结束后按 `basic` 舍入
+/// - 公式阶段 `basic_value * (1 + f_per) * f_multi` 以舍入后的基础值计算，结束后按 `formula` 舍入，覆盖值同样按 `formula` 舍入
+/// - 钳制阶段下限向上取整、上限向下取整，保证结果不越过效果声明的界限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ModifierRounding {
+    pub basic: RoundingMode,
+    pub formula: RoundingMode,
+}
+
+impl ModifierRounding {
+    /// 所有阶段使用同一舍入方式
+    pub const fn uniform(rounding: RoundingMode) -> Self {
+        Self {
+            basic: rounding,
+            formula: rounding,
+        }
+    }
+}
 
 /// 基准锚点值的修改器
 ///
@@ -9,18 +37,34 @@ pub(super) const MULT_BASE_LINE: f64 = 1.0;
 pub(super) struct AnchorModifier {
     addition: f64,
     percent: f64,
+    addition_curve: StackCurve,
+    percent_curve: StackCurve,
 }
 
 impl Default for AnchorModifier {
     fn default() -> Self {
+        Self::new(StackCurve::Linear, StackCurve::Linear)
+    }
+}
+
+impl AnchorModifier {
+    pub fn new(addition_curve: StackCurve, percent_curve: StackCurve) -> Self {
         Self {
             addition: ADDITION_BASE_LINE,
             percent: PERCENT_BASE_LINE,
+            addition_curve,
+            percent_curve,
         }
     }
-}
 
-impl AnchorModifier {
+    pub fn addition_term(&self) -> CurvedValue {
+        CurvedValue::new(self.addition, &self.addition_curve, ADDITION_BASE_LINE)
+    }
+
+    pub fn percent_term(&self) -> CurvedValue {
+        CurvedValue::new(self.percent, &self.percent_curve, PERCENT_BASE_LINE)
+    }
+
     pub fn reduce_add(&mut self, v: f64) {
         self.addition += v
     }
@@ -29,33 +73,81 @@ impl AnchorModifier {
         self.percent += v
     }
 
-    /// 计算公式 `base_value * (1 + b_per) + b_add`
+    /// 计算公式 `base_value * (1 + b_per) + b_add` （各项均为曲线后的值）
     pub fn apply_modify(&self, v: f64) -> f64 {
-        self.addition + (1.0 + self.percent) * v
+        self.addition_term().post + (1.0 + self.percent_term().post) * v
+    }
+
+    /// 同 [`Self::apply_modify`] ，结果按 `rounding.basic` 落回数值类型
+    pub fn apply_modify_num<N: AttrNum>(&self, v: N, rounding: ModifierRounding) -> N {
+        N::from_f64(self.apply_modify(v.to_f64()), rounding.basic)
     }
 }
 
 /// 聚合值的修改器
 ///
 /// 支持多源公式合并，保证了“时间一致性”
+///
+/// 公式结果之后依次经过钳制、覆盖两个阶段，冲突规则：
+/// - 多个下限取最大值、多个上限取最小值（最严格者生效），下限高于上限时上限优先
+/// - 多个覆盖值取优先级最高者，同优先级取后遍历到的（即容器插入顺序靠后者）
+/// - 覆盖值不受钳制，如“定身时移速为 0 ”应无视“移速不低于 50%”
 #[derive(Debug)]
 pub(super) struct AggregateModifier {
     basic: AnchorModifier,
     final_pct: f64,
     final_mult: f64,
+    final_pct_curve: StackCurve,
+    final_mult_curve: StackCurve,
+    floor_min: f64,
+    cap_max: f64,
+    /// (优先级, 覆盖值)
+    override_val: Option<(u8, f64)>,
 }
 
 impl Default for AggregateModifier {
     fn default() -> Self {
+        Self::new([StackCurve::Linear; 4])
+    }
+}
+
+impl AggregateModifier {
+    /// 曲线顺序 `[b_add, b_per, f_per, f_multi]`
+    pub fn new(curves: [StackCurve; 4]) -> Self {
+        let [
+            basic_add_curve,
+            basic_pct_curve,
+            final_pct_curve,
+            final_mult_curve,
+        ] = curves;
         Self {
-            basic: Default::default(),
+            basic: AnchorModifier::new(basic_add_curve, basic_pct_curve),
             final_pct: PERCENT_BASE_LINE,
             final_mult: MULT_BASE_LINE,
+            final_pct_curve,
+            final_mult_curve,
+            floor_min: FLOOR_BASE_LINE,
+            cap_max: CAP_BASE_LINE,
+            override_val: None,
         }
     }
-}
 
-impl AggregateModifier {
+    pub fn basic_add_term(&self) -> CurvedValue {
+        self.basic.addition_term()
+    }
+
+    pub fn basic_pct_term(&self) -> CurvedValue {
+        self.basic.percent_term()
+    }
+
+    pub fn final_pct_term(&self) -> CurvedValue {
+        CurvedValue::new(self.final_pct, &self.final_pct_curve, PERCENT_BASE_LINE)
+    }
+
+    pub fn final_mult_term(&self) -> CurvedValue {
+        CurvedValue::new(self.final_mult, &self.final_mult_curve, MULT_BASE_LINE)
+    }
+
     pub fn reduce_basic_add(&mut self, v: f64) {
         self.basic.reduce_add(v);
     }
@@ -72,9 +164,74 @@ impl AggregateModifier {
         self.final_mult *= v;
     }
 
+    pub fn reduce_floor_min(&mut self, v: f64) {
+        self.floor_min = self.floor_min.max(v);
+    }
+
+    pub fn reduce_cap_max(&mut self, v: f64) {
+        self.cap_max = self.cap_max.min(v);
+    }
+
+    pub fn reduce_override(&mut self, priority: u8, v: f64) {
+        match self.override_val {
+            Some((old_priority, _)) if old_priority > priority => {}
+            _ => self.override_val = Some((priority, v)),
+        }
+    }
+
+    /// 生效的下限，无下限效果时为 None
+    pub fn floor_min_term(&self) -> Option<f64> {
+        (self.floor_min != FLOOR_BASE_LINE).then_some(self.floor_min)
+    }
+
+    /// 生效的上限，无上限效果时为 None
+    pub fn cap_max_term(&self) -> Option<f64> {
+        (self.cap_max != CAP_BASE_LINE).then_some(self.cap_max)
+    }
+
+    /// 生效的覆盖值，无覆盖效果时为 None
+    pub fn override_term(&self) -> Option<f64> {
+        self.override_val.map(|(_, v)| v)
+    }
+
+    /// 基础阶段 `base_value * (1 + b_per) + b_add`
+    pub fn apply_basic(&self, v: f64) -> f64 {
+        self.basic.apply_modify(v)
+    }
+
+    /// 公式阶段 `(base_value * (1 + b_per) + b_add) * (1 + f_per) * f_multi`
+    pub fn apply_formula(&self, v: f64) -> f64 {
+        self.apply_basic(v) * (1.0 + self.final_pct_term().post) * self.final_mult_term().post
+    }
+
     /// 计算公式 `(base_value * (1 + b_per) + b_add) * (1 + f_per) * f_multi`
+    ///
+    /// 而后 `min(max(formula, floor), cap)` ，若存在覆盖值则直接取覆盖值
     pub fn apply_modify(&self, v: f64) -> f64 {
-        self.basic.apply_modify(v) * (1.0 + self.final_pct) * self.final_mult
+        if let Some(override_val) = self.override_term() {
+            return override_val;
+        }
+
+        self.apply_formula(v).max(self.floor_min).min(self.cap_max)
+    }
+
+    /// 同 [`Self::apply_modify`] ，各阶段按 [`ModifierRounding`] 落回数值类型
+    pub fn apply_modify_num<N: AttrNum>(&self, v: N, rounding: ModifierRounding) -> N {
+        if let Some(override_val) = self.override_term() {
+            return N::from_f64(override_val, rounding.formula);
+        }
+
+        let basic = self.basic.apply_modify_num(v, rounding);
+        let formula =
+            basic.to_f64() * (1.0 + self.final_pct_term().post) * self.final_mult_term().post;
+        let mut result = N::from_f64(formula, rounding.formula);
+        if let Some(floor_min) = self.floor_min_term() {
+            result = result.max_of(N::from_f64(floor_min, RoundingMode::Ceil));
+        }
+        if let Some(cap_max) = self.cap_max_term() {
+            result = result.min_of(N::from_f64(cap_max, RoundingMode::Floor));
+        }
+        result
     }
 }
 
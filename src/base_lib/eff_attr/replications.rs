@@ -0,0 +1,409 @@
+//! 属性与效果的增量复制，用于服务端权威的联机游戏
+//!
+//! 服务端为每个客户端保存一份基线（上次发送的状态），每帧与当前状态比较生成差量：
+//! - 属性差量：只包含与基线不同的属性值，以使用方定义的键区分（如属性枚举）
+//! - 效果差量：以 [`Upsert::Id`] 区分，包含新增或内容变化的效果与被移除的效果 id
+//!
+//! 差量中的值均为绝对值而非增量，重复应用结果不变
+//!
+//! 【注意】生成差量后基线立即推进，需要可靠有序的通道；不可靠通道应在收到确认后再应用差量推进基线
+//!
+//! 【注意】效果的计时器随效果一同复制，逐帧累加的计时器（如 [`crate::base_lib::cores::timers::tick_timer::TickTimer`]）
+//! 每帧都会产生差量，推荐使用 [`crate::base_lib::cores::timers::static_timer::StaticTimer`] ，并同步时间线
+
+use crate::base_lib::{
+    cores::attr_nums::AttrNum,
+    eff_attr::upsert_container::{Upsert, UpsertContainer},
+};
+
+/// 可复制的属性，服务端读取、客户端写入
+pub trait ReplicatedAttr {
+    type Num: AttrNum;
+
+    /// 需要复制的值
+    fn get_replicated(&self) -> Self::Num;
+
+    /// 客户端以复制值覆盖本地值
+    fn set_replicated(&mut self, v: Self::Num);
+}
+
+/// 属性差量
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrDelta<K, N> {
+    pub changed: Vec<(K, N)>,
+}
+
+impl<K, N> AttrDelta<K, N> {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+/// 属性基线，属性数量较少，使用数组存储
+#[derive(Debug, Clone)]
+pub struct AttrBaseline<K, N>(Vec<(K, N)>);
+
+impl<K, N> Default for AttrBaseline<K, N> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<K: Eq + Clone, N: AttrNum> AttrBaseline<K, N> {
+    pub fn get(&self, key: &K) -> Option<N> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| *v)
+    }
+
+    /// 与当前值比较生成差量，不修改基线；差值在 [`AttrNum::DEAD_ZONE`] 内视为未变化，
+    /// 非有限值（NaN 、无穷）总是发送
+    pub fn diff(&self, current: impl IntoIterator<Item = (K, N)>) -> AttrDelta<K, N> {
+        let changed = current
+            .into_iter()
+            .filter(|(k, v)| self.get(k).is_none_or(|old| is_replicated_change(old, *v)))
+            .collect();
+        AttrDelta { changed }
+    }
+
+    /// 以差量推进基线
+    pub fn apply(&mut self, delta: &AttrDelta<K, N>) {
+        for (key, v) in delta.changed.iter() {
+            if let Some(old) = self.0.iter_mut().find(|(k, _)| k == key) {
+                old.1 = *v;
+            } else {
+                self.0.push((key.clone(), *v));
+            }
+        }
+    }
+
+    /// 生成差量并推进基线
+    pub fn diff_and_advance(
+        &mut self,
+        current: impl IntoIterator<Item = (K, N)>,
+    ) -> AttrDelta<K, N> {
+        let delta = self.diff(current);
+        self.apply(&delta);
+        delta
+    }
+}
+
+/// 不直接相减，避免整数类型相距过远时溢出
+fn is_replicated_change<N: AttrNum>(old: N, v: N) -> bool {
+    if !v.to_f64().is_finite() {
+        return true;
+    }
+    if v == old {
+        return false;
+    }
+    N::DEAD_ZONE == N::ZERO || (v.to_f64() - old.to_f64()).abs() > N::DEAD_ZONE.to_f64()
+}
+
+/// 效果容器差量
+#[derive(Debug, Clone)]
+pub struct EffDelta<E: Upsert> {
+    /// 新增或内容变化的效果，按容器中的顺序
+    pub upserted: Vec<E>,
+    /// 被移除的效果
+    pub removed: Vec<E::Id>,
+}
+
+impl<E: Upsert> EffDelta<E> {
+    pub fn is_empty(&self) -> bool {
+        self.upserted.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 效果容器基线，与 [`UpsertContainer`] 一致使用数组存储
+#[derive(Debug, Clone)]
+pub struct EffBaseline<E: Upsert>(Vec<E>);
+
+impl<E: Upsert> Default for EffBaseline<E> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<E: Upsert + Clone + PartialEq> EffBaseline<E> {
+    /// 与容器当前内容比较生成差量，不修改基线
+    pub fn diff(&self, container: &UpsertContainer<E>) -> EffDelta<E> {
+        let upserted = container
+            .iter_ele()
+            .filter(|ele| !self.0.iter().any(|old| old == *ele))
+            .cloned()
+            .collect();
+        let removed = self
+            .0
+            .iter()
+            .filter(|old| !container.iter_ele().any(|ele| ele.has_same_id(old)))
+            .map(|old| old.gen_id())
+            .collect();
+        EffDelta { upserted, removed }
+    }
+
+    /// 以差量推进基线
+    pub fn apply(&mut self, delta: &EffDelta<E>) {
+        self.0
+            .retain(|old| !delta.removed.iter().any(|id| old.matched_id(id)));
+        for new_ele in delta.upserted.iter() {
+            if let Some(old) = self.0.iter_mut().find(|old| old.has_same_id(new_ele)) {
+                *old = new_ele.clone();
+            } else {
+                self.0.push(new_ele.clone());
+            }
+        }
+    }
+
+    /// 生成差量并推进基线
+    pub fn diff_and_advance(&mut self, container: &UpsertContainer<E>) -> EffDelta<E> {
+        let delta = self.diff(container);
+        self.apply(&delta);
+        delta
+    }
+}
+
+/// 客户端应用差量
+pub mod replication_system {
+    use super::*;
+
+    /// 按键查找本地属性并覆盖，找不到的键忽略，返回实际应用的数量
+    pub fn apply_attr_delta<K: Eq, N: AttrNum>(
+        delta: &AttrDelta<K, N>,
+        attrs: &mut [(K, &mut dyn ReplicatedAttr<Num = N>)],
+    ) -> usize {
+        let mut applied = 0;
+        for (key, v) in delta.changed.iter() {
+            if let Some((_, attr)) = attrs.iter_mut().find(|(k, _)| k == key) {
+                attr.set_replicated(*v);
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    /// 应用效果差量，同 id 直接替换，不触发 [`Upsert::replace`] 的堆叠等逻辑
+    pub fn apply_eff_delta<E: Upsert + Clone>(
+        delta: &EffDelta<E>,
+        container: &mut UpsertContainer<E>,
+    ) {
+        for id in delta.removed.iter() {
+            container.delete_ele(|ele| ele.matched_id(id));
+        }
+        for new_ele in delta.upserted.iter() {
+            container.upsert_ele(new_ele.clone(), |old, new| *old = new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::{
+            timers::static_timer::{StaticTimeline, StaticTimer},
+            unify_types::time_type,
+        },
+        eff_attr::{
+            bound_attrs::BoundAttr,
+            bounded_attrs::BoundedAttr,
+            effects::Effect,
+            stat_attr_effs::{StatAttrEff, StatAttrEffType},
+            stat_attrs::StatAttr,
+        },
+    };
+
+    use super::{replication_system::*, *};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum AttrKey {
+        Attack,
+        Health,
+        HealthMax,
+    }
+
+    type Eff = StatAttrEff<&'static str, StaticTimer>;
+
+    struct Unit {
+        attack: StatAttr,
+        health: BoundedAttr,
+        health_max: BoundAttr,
+        attack_effs: UpsertContainer<Eff>,
+    }
+
+    impl Unit {
+        fn new() -> Self {
+            Self {
+                attack: StatAttr::new(10.0),
+                health: BoundedAttr::new(100.0),
+                health_max: BoundAttr::new(100.0),
+                attack_effs: UpsertContainer::default(),
+            }
+        }
+
+        fn attr_values(&self) -> [(AttrKey, f64); 3] {
+            [
+                (AttrKey::Attack, self.attack.get_replicated()),
+                (AttrKey::Health, self.health.get_replicated()),
+                (AttrKey::HealthMax, self.health_max.get_replicated()),
+            ]
+        }
+    }
+
+    /// 一帧的复制数据
+    struct Packet {
+        attrs: AttrDelta<AttrKey, f64>,
+        attack_effs: EffDelta<Eff>,
+    }
+
+    /// 本地回环：服务端为客户端保存基线，差量经由内存通道送达客户端
+    struct Loopback {
+        server: Unit,
+        client: Unit,
+        attr_baseline: AttrBaseline<AttrKey, f64>,
+        eff_baseline: EffBaseline<Eff>,
+        wire: Vec<Packet>,
+    }
+
+    impl Loopback {
+        fn new() -> Self {
+            Self {
+                server: Unit::new(),
+                client: Unit::new(),
+                attr_baseline: AttrBaseline::default(),
+                eff_baseline: EffBaseline::default(),
+                wire: Vec::new(),
+            }
+        }
+
+        fn send(&mut self) -> &Packet {
+            let packet = Packet {
+                attrs: self
+                    .attr_baseline
+                    .diff_and_advance(self.server.attr_values()),
+                attack_effs: self.eff_baseline.diff_and_advance(&self.server.attack_effs),
+            };
+            self.wire.push(packet);
+            self.wire.last().unwrap()
+        }
+
+        fn deliver(&mut self) {
+            let client = &mut self.client;
+            for packet in self.wire.drain(..) {
+                apply_attr_delta(
+                    &packet.attrs,
+                    &mut [
+                        (AttrKey::Attack, &mut client.attack),
+                        (AttrKey::Health, &mut client.health),
+                        (AttrKey::HealthMax, &mut client.health_max),
+                    ],
+                );
+                apply_eff_delta(&packet.attack_effs, &mut client.attack_effs);
+            }
+        }
+
+        fn assert_synced(&self) {
+            assert_eq!(self.server.attr_values(), self.client.attr_values());
+            let server_effs: Vec<_> = self.server.attack_effs.iter_ele().collect();
+            let client_effs: Vec<_> = self.client.attack_effs.iter_ele().collect();
+            assert_eq!(server_effs, client_effs);
+        }
+    }
+
+    /// 首帧全量，之后只发送变化的部分，移除的效果以 id 同步
+    #[test]
+    fn test_loopback_sync() {
+        let timeline = StaticTimeline::new();
+        let mut net = Loopback::new();
+
+        assert_eq!(net.send().attrs.changed.len(), 3);
+        net.deliver();
+        net.assert_synced();
+        assert!(net.send().attrs.is_empty());
+
+        let buff = StatAttrEff::new(
+            StatAttrEffType::BasicAdd,
+            Effect::new("player", "sharpen", 5.0),
+            StaticTimer::new(&timeline, time_type::unit::<10>()),
+        );
+        net.server.attack_effs.upsert_replace(buff);
+        net.server
+            .attack
+            .refresh_value(net.server.attack_effs.iter_ele());
+        net.server.health.apply_eff(-30.0);
+        net.server.health.commit_pending_value();
+
+        let packet = net.send();
+        assert_eq!(
+            packet.attrs.changed,
+            vec![(AttrKey::Attack, 15.0), (AttrKey::Health, 70.0)]
+        );
+        assert_eq!(packet.attack_effs.upserted.len(), 1);
+        net.deliver();
+        net.assert_synced();
+        assert_eq!(net.client.health.get_pending_value(), 70.0);
+
+        assert!(net.send().attack_effs.is_empty());
+
+        net.server
+            .attack_effs
+            .delete_ele(|ele| ele.get_effect().get_effect_name() == &"sharpen");
+        net.server
+            .attack
+            .refresh_value(net.server.attack_effs.iter_ele());
+        let packet = net.send();
+        assert_eq!(packet.attack_effs.removed.len(), 1);
+        assert!(packet.attack_effs.upserted.is_empty());
+        net.deliver();
+        net.assert_synced();
+        assert_eq!(net.client.attack.get_current(), 10.0);
+    }
+
+    /// 死区内的变化不发送；差量为绝对值，重复应用结果不变
+    #[test]
+    fn test_attr_delta_dead_zone_and_idempotent() {
+        let mut baseline = AttrBaseline::default();
+        let delta = baseline.diff_and_advance([(AttrKey::Health, 100_i64)]);
+        assert_eq!(delta.changed, vec![(AttrKey::Health, 100)]);
+        assert!(baseline.diff([(AttrKey::Health, 100)]).is_empty());
+
+        let mut float_baseline = AttrBaseline::default();
+        float_baseline.diff_and_advance([(AttrKey::Health, 1.0)]);
+        assert!(
+            float_baseline
+                .diff([(AttrKey::Health, 1.0 + 1e-10)])
+                .is_empty()
+        );
+
+        let delta = baseline.diff_and_advance([(AttrKey::Health, 90)]);
+        let mut health = BoundedAttr::<i64>::new(100);
+        for _ in 0..2 {
+            apply_attr_delta(&delta, &mut [(AttrKey::Health, &mut health)]);
+        }
+        assert_eq!(health.get_snapshot_value(), 90);
+        assert_eq!(baseline.get(&AttrKey::Health), Some(90));
+    }
+
+    /// 整数相距极远时不溢出；非有限值总是发送
+    #[test]
+    fn test_attr_delta_extreme_values() {
+        let mut baseline = AttrBaseline::default();
+        baseline.diff_and_advance([(AttrKey::Health, i64::MIN)]);
+        let delta = baseline.diff([(AttrKey::Health, i64::MAX)]);
+        assert_eq!(delta.changed, vec![(AttrKey::Health, i64::MAX)]);
+
+        let mut float_baseline = AttrBaseline::default();
+        float_baseline.diff_and_advance([(AttrKey::Health, 1.0)]);
+        let delta = float_baseline.diff_and_advance([(AttrKey::Health, f64::NAN)]);
+        assert!(delta.changed[0].1.is_nan());
+        assert_eq!(
+            float_baseline
+                .diff([(AttrKey::Health, f64::NAN)])
+                .changed
+                .len(),
+            1
+        );
+        float_baseline.diff_and_advance([(AttrKey::Health, f64::INFINITY)]);
+        assert!(
+            !float_baseline
+                .diff([(AttrKey::Health, f64::INFINITY)])
+                .is_empty()
+        );
+    }
+}
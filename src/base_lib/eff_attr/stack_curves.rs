@@ -0,0 +1,130 @@
+//! 同类效果堆叠后的聚合曲线（边际递减、软上限、硬上限）
+//!
+//! 同类效果先累加（乘法类累乘），再对“偏离基线的量”应用曲线，最后代入公式
+//! - 加法类、百分比类基线为 0 ，直接对累加值应用曲线
+//! - 乘法类基线为 1 ，对 `累乘值 - 1` 应用曲线后再加回 1
+//!
+//! 曲线按绝对值生效并保留符号，因此减益堆叠同样会边际递减（如减速叠加）
+
+/// 聚合曲线
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StackCurve {
+    /// 线性，不做任何处理（默认）
+    #[default]
+    Linear,
+    /// 双曲线边际递减 `x / (x + k)` ，结果趋近于 1 ，适用于百分比类效果
+    ///
+    /// `k` 须为正数，否则（含 NaN）按线性处理
+    Hyperbolic(f64),
+    /// 软上限，超过 `cap` 的部分按 `falloff` 比例生效
+    SoftCap { cap: f64, falloff: f64 },
+    /// 硬上限，超过 `cap` 的部分直接舍弃
+    HardCap(f64),
+}
+
+impl StackCurve {
+    /// 对偏离基线的量应用曲线
+    pub fn apply(&self, x: f64) -> f64 {
+        let magnitude = x.abs();
+        let curved = match *self {
+            StackCurve::Linear => magnitude,
+            StackCurve::Hyperbolic(k) if k > 0.0 => magnitude / (magnitude + k),
+            StackCurve::Hyperbolic(_) => magnitude,
+            StackCurve::SoftCap { cap, falloff } => {
+                if magnitude <= cap {
+                    magnitude
+                } else {
+                    cap + (magnitude - cap) * falloff
+                }
+            }
+            StackCurve::HardCap(cap) => magnitude.min(cap),
+        };
+        curved.copysign(x)
+    }
+}
+
+/// 应用曲线前后的聚合值，用于界面提示（如“暴击率 +120% （生效 +75%）”）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurvedValue {
+    /// 曲线前，同类效果的原始聚合值
+    pub pre: f64,
+    /// 曲线后，实际代入公式的值
+    pub post: f64,
+}
+
+impl CurvedValue {
+    pub fn new(pre: f64, curve: &StackCurve, base_line: f64) -> Self {
+        Self {
+            pre,
+            post: base_line + curve.apply(pre - base_line),
+        }
+    }
+
+    /// 是否被曲线削减（或放大）
+    pub fn is_curved(&self) -> bool {
+        self.pre != self.post
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 线性曲线恒等
+    #[test]
+    fn linear_is_identity() {
+        assert_eq!(StackCurve::Linear.apply(3.5), 3.5);
+        assert_eq!(StackCurve::Linear.apply(-2.0), -2.0);
+    }
+
+    /// 双曲线：`x / (x + k)` ，按绝对值生效并保留符号
+    #[test]
+    fn hyperbolic_diminishes() {
+        let curve = StackCurve::Hyperbolic(1.0);
+        assert_eq!(curve.apply(1.0), 0.5);
+        assert_eq!(curve.apply(3.0), 0.75);
+        assert_eq!(curve.apply(-1.0), -0.5);
+        assert_eq!(curve.apply(0.0), 0.0);
+
+        // 非正数或 NaN 的 k 按线性处理，不产生 NaN 或负值
+        for k in [0.0, -1.0, f64::NAN] {
+            let curve = StackCurve::Hyperbolic(k);
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(2.0), 2.0);
+            assert_eq!(curve.apply(-0.5), -0.5);
+        }
+    }
+
+    /// 软上限：超过部分按比例生效
+    #[test]
+    fn soft_cap_falloff() {
+        let curve = StackCurve::SoftCap {
+            cap: 0.5,
+            falloff: 0.5,
+        };
+        assert_eq!(curve.apply(0.4), 0.4);
+        assert_eq!(curve.apply(1.5), 1.0); // 0.5 + 1.0 * 0.5
+        assert_eq!(curve.apply(-1.5), -1.0);
+    }
+
+    /// 硬上限：超过部分舍弃
+    #[test]
+    fn hard_cap_truncates() {
+        let curve = StackCurve::HardCap(0.75);
+        assert_eq!(curve.apply(0.5), 0.5);
+        assert_eq!(curve.apply(2.0), 0.75);
+    }
+
+    /// 乘法类以 1 为基线，对偏离量应用曲线
+    #[test]
+    fn curved_value_respects_base_line() {
+        let curved = CurvedValue::new(3.0, &StackCurve::HardCap(1.0), 1.0);
+        assert_eq!(curved.pre, 3.0);
+        assert_eq!(curved.post, 2.0);
+        assert!(curved.is_curved());
+
+        let curved = CurvedValue::new(1.5, &StackCurve::Linear, 1.0);
+        assert_eq!(curved.post, 1.5);
+        assert!(!curved.is_curved());
+    }
+}
@@ -1,8 +1,15 @@
 use crate::base_lib::{
-    cores::{timers::tiny_timer::HasTimer, unify_types::FixedName},
+    cores::{
+        attr_nums::AttrNum, timers::tiny_timer::HasTimer, tiny_tags::TinyTag,
+        unify_types::FixedName,
+    },
     eff_attr::{
-        effects::{Effect, EffectMean, EffectMeaning},
-        modifiers::{ADDITION_BASE_LINE, AggregateModifier, MULT_BASE_LINE, PERCENT_BASE_LINE},
+        effects::{ConditionalEffect, Effect, EffectMean, EffectMeaning},
+        modifiers::{
+            ADDITION_BASE_LINE, AggregateModifier, MULT_BASE_LINE, ModifierRounding,
+            PERCENT_BASE_LINE,
+        },
+        stack_curves::{CurvedValue, StackCurve},
         upsert_container::Upsert,
     },
 };
@@ -20,17 +27,62 @@ pub enum StatAttrEffType {
     FinalPer,
     /// 乘法（描述参考：力量变为原先的xx倍），指数增长、谨慎使用
     FinalMul,
+    /// 下限（描述参考：力量不低于xx），多个下限取最大值
+    FloorMin,
+    /// 上限（描述参考：力量不高于xx），多个上限取最小值，与下限冲突时上限优先
+    CapMax,
+    /// 覆盖（描述参考：力量固定为xx），无视公式与钳制，多个覆盖取优先级最高者（同级取插入顺序靠后者）
+    Override(u8),
+}
+
+impl StatAttrEffType {
+    /// 生成可读的效果描述，如“基础力量增加10”“力量提升20%”
+    pub fn describe(&self, attr_name: &str, eff_value: f64) -> String {
+        let up_down =
+            |up: &'static str, down: &'static str| if eff_value < 0.0 { down } else { up };
+        let abs_val = fmt_num(eff_value.abs());
+        let abs_pct = fmt_num(eff_value.abs() * 100.0);
+        match self {
+            StatAttrEffType::BasicAdd => {
+                format!("基础{attr_name}{}{abs_val}", up_down("增加", "减少"))
+            }
+            StatAttrEffType::BasicPer => {
+                format!("基础{attr_name}{}{abs_pct}%", up_down("提升", "降低"))
+            }
+            StatAttrEffType::FinalPer => {
+                format!("{attr_name}{}{abs_pct}%", up_down("提升", "降低"))
+            }
+            StatAttrEffType::FinalMul => {
+                format!("{attr_name}变为原先的{}倍", fmt_num(eff_value))
+            }
+            StatAttrEffType::FloorMin => format!("{attr_name}不低于{}", fmt_num(eff_value)),
+            StatAttrEffType::CapMax => format!("{attr_name}不高于{}", fmt_num(eff_value)),
+            StatAttrEffType::Override(_) => format!("{attr_name}固定为{}", fmt_num(eff_value)),
+        }
+    }
+}
+
+/// 描述用数值格式：最多保留两位小数，去除末尾的 0
+fn fmt_num(v: f64) -> String {
+    let s = format!("{v:.2}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
 }
 
 /// 属性效果
-#[derive(Clone, Debug)]
-pub struct StatAttrEff<S: FixedName, Timer> {
+///
+/// 生效条件的标签类型 `T` 与效果名类型 `S` 相互独立，默认相同
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatAttrEff<S: FixedName, Timer, T: FixedName = S> {
     /// 效果类型 对应公式变量
     eff_type: StatAttrEffType,
     /// 效果
     eff: Effect<S>,
     /// 持续时间（可以不用计时器，而是计数器或者BUFF列表，通过空判断是否结束）
     duration: Timer,
+    /// 生效条件，如“血量低于 30% 时”“格挡时”，条件切换时无需移除重加
+    condition: Option<TinyTag<T>>,
+    /// 条件的求值缓存，无条件时恒为 true
+    condition_met: bool,
 }
 
 impl<S: FixedName, Timer> StatAttrEff<S, Timer> {
@@ -39,15 +91,51 @@ impl<S: FixedName, Timer> StatAttrEff<S, Timer> {
             eff_type,
             eff,
             duration,
+            condition: None,
+            condition_met: true,
+        }
+    }
+
+    /// 附加生效条件，在属性刷新时首次求值，求值前不生效
+    ///
+    /// 条件的标签类型可与效果名类型不同，如以枚举标签描述单位状态
+    pub fn with_condition<T: FixedName>(self, condition: TinyTag<T>) -> StatAttrEff<S, Timer, T> {
+        StatAttrEff {
+            eff_type: self.eff_type,
+            eff: self.eff,
+            duration: self.duration,
+            condition: Some(condition),
+            condition_met: false,
         }
     }
+}
+
+impl<S: FixedName, Timer, T: FixedName> StatAttrEff<S, Timer, T> {
+    /// 是否参与属性计算
+    pub fn is_active(&self) -> bool {
+        self.condition_met
+    }
 
     pub fn get_type(&self) -> StatAttrEffType {
         self.eff_type
     }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    pub fn get_effect_mut(&mut self) -> &mut Effect<S> {
+        &mut self.eff
+    }
+
+    /// 生成可读的效果描述，见 [`StatAttrEffType::describe`]
+    pub fn describe(&self, attr_name: &str) -> String {
+        self.eff_type
+            .describe(attr_name, self.eff.get_effect_value())
+    }
 }
 
-impl<S: FixedName, Timer> HasTimer for StatAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> HasTimer for StatAttrEff<S, Timer, T> {
     type Timer = Timer;
 
     fn get_timer(&self) -> &Self::Timer {
@@ -65,7 +153,7 @@ pub struct StatAttrEffId<S: FixedName> {
     pub from: S,
 }
 
-impl<S: FixedName, Timer> Upsert for StatAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> Upsert for StatAttrEff<S, Timer, T> {
     type Id = StatAttrEffId<S>;
 
     fn gen_id(&self) -> Self::Id {
@@ -85,7 +173,21 @@ impl<S: FixedName, Timer> Upsert for StatAttrEff<S, Timer> {
     }
 }
 
-impl<S: FixedName, Timer> EffectMeaning for StatAttrEff<S, Timer> {
+impl<S: FixedName, Timer, T: FixedName> ConditionalEffect<T> for StatAttrEff<S, Timer, T> {
+    fn get_condition(&self) -> Option<&TinyTag<T>> {
+        self.condition.as_ref()
+    }
+
+    fn is_condition_met(&self) -> bool {
+        self.condition_met
+    }
+
+    fn set_condition_met(&mut self, met: bool) {
+        self.condition_met = met;
+    }
+}
+
+impl<S: FixedName, Timer, T: FixedName> EffectMeaning for StatAttrEff<S, Timer, T> {
     fn which_nature(&self) -> EffectMean {
         let eff_value = self.eff.get_effect_value();
         match self.eff_type {
@@ -93,10 +195,42 @@ impl<S: FixedName, Timer> EffectMeaning for StatAttrEff<S, Timer> {
             StatAttrEffType::BasicPer => EffectMean::which_nature(eff_value, PERCENT_BASE_LINE),
             StatAttrEffType::FinalPer => EffectMean::which_nature(eff_value, PERCENT_BASE_LINE),
             StatAttrEffType::FinalMul => EffectMean::which_nature(eff_value, MULT_BASE_LINE),
+            // 保底视为增益、封顶视为减益
+            StatAttrEffType::FloorMin => EffectMean::Good,
+            StatAttrEffType::CapMax => EffectMean::Bad,
+            // 覆盖值的好坏取决于属性当前值，无法单独判断
+            StatAttrEffType::Override(_) => EffectMean::Neutral,
         }
     }
 }
 
+/// 属性各效果类型的聚合曲线，默认均为线性
+///
+/// 钳制与覆盖类效果（下限、上限、覆盖）不参与曲线
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StatAttrCurves {
+    pub basic_add: StackCurve,
+    pub basic_per: StackCurve,
+    pub final_per: StackCurve,
+    pub final_mul: StackCurve,
+}
+
+/// 属性计算公式的各中间项，用于界面提示和调试
+#[derive(Clone, Copy, Debug)]
+pub struct StatAttrFormulaTerms {
+    pub basic_add: CurvedValue,
+    pub basic_per: CurvedValue,
+    pub final_per: CurvedValue,
+    pub final_mul: CurvedValue,
+    /// 基础阶段 `base_value * (1 + b_per) + b_add`
+    pub basic_value: f64,
+    /// 公式阶段 `basic_value * (1 + f_per) * f_multi`
+    pub formula_value: f64,
+    pub floor_min: Option<f64>,
+    pub cap_max: Option<f64>,
+    pub override_value: Option<f64>,
+}
+
 /// 属性效果修改器
 ///
 /// 计算公式见 [`StatAttrModifier::apply_modify`]
@@ -104,7 +238,32 @@ impl<S: FixedName, Timer> EffectMeaning for StatAttrEff<S, Timer> {
 pub struct StatAttrModifier(AggregateModifier);
 
 impl StatAttrModifier {
-    pub fn reduce<S: FixedName, Timer>(&mut self, eff: &StatAttrEff<S, Timer>) {
+    pub fn new(curves: StatAttrCurves) -> Self {
+        let StatAttrCurves {
+            basic_add,
+            basic_per,
+            final_per,
+            final_mul,
+        } = curves;
+        Self(AggregateModifier::new([
+            basic_add, basic_per, final_per, final_mul,
+        ]))
+    }
+
+    /// 同类效果聚合值在曲线前后的值，用于界面提示；钳制与覆盖类效果返回 None
+    pub fn curved_term(&self, eff_type: StatAttrEffType) -> Option<CurvedValue> {
+        match eff_type {
+            StatAttrEffType::BasicAdd => Some(self.0.basic_add_term()),
+            StatAttrEffType::BasicPer => Some(self.0.basic_pct_term()),
+            StatAttrEffType::FinalPer => Some(self.0.final_pct_term()),
+            StatAttrEffType::FinalMul => Some(self.0.final_mult_term()),
+            StatAttrEffType::FloorMin | StatAttrEffType::CapMax | StatAttrEffType::Override(_) => {
+                None
+            }
+        }
+    }
+
+    pub fn reduce<S: FixedName, Timer, T: FixedName>(&mut self, eff: &StatAttrEff<S, Timer, T>) {
         let v = eff.eff.get_effect_value();
 
         match eff.eff_type {
@@ -112,12 +271,38 @@ impl StatAttrModifier {
             StatAttrEffType::BasicPer => self.0.reduce_basic_pct(v),
             StatAttrEffType::FinalPer => self.0.reduce_final_pct(v),
             StatAttrEffType::FinalMul => self.0.reduce_final_mult(v),
+            StatAttrEffType::FloorMin => self.0.reduce_floor_min(v),
+            StatAttrEffType::CapMax => self.0.reduce_cap_max(v),
+            StatAttrEffType::Override(priority) => self.0.reduce_override(priority, v),
+        }
+    }
+
+    /// 计算公式的各中间项
+    pub fn formula_terms(&self, v: f64) -> StatAttrFormulaTerms {
+        StatAttrFormulaTerms {
+            basic_add: self.0.basic_add_term(),
+            basic_per: self.0.basic_pct_term(),
+            final_per: self.0.final_pct_term(),
+            final_mul: self.0.final_mult_term(),
+            basic_value: self.0.apply_basic(v),
+            formula_value: self.0.apply_formula(v),
+            floor_min: self.0.floor_min_term(),
+            cap_max: self.0.cap_max_term(),
+            override_value: self.0.override_term(),
         }
     }
 
+    /// 计算公式 `(base_value * (1 + b_per) + b_add) * (1 + f_per) * f_multi` ，各项为曲线后的值
+    ///
+    /// 而后经过下限、上限钳制，若存在覆盖值则直接取覆盖值
     pub fn apply_modify(&self, v: f64) -> f64 {
         self.0.apply_modify(v)
     }
+
+    /// 同 [`Self::apply_modify`] ，用于非浮点数值类型的属性
+    pub fn apply_modify_num<N: AttrNum>(&self, v: N, rounding: ModifierRounding) -> N {
+        self.0.apply_modify_num(v, rounding)
+    }
 }
 
 #[cfg(test)]
@@ -298,4 +483,150 @@ mod tests {
                 .is_neutral()
         );
     }
+
+    /// 下限：公式结果低于下限时取下限，多个下限取最大值
+    #[test]
+    fn test_floor_min() {
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::BasicPer, -0.8));
+        am.reduce(&make_eff(StatAttrEffType::FloorMin, 30.0));
+        am.reduce(&make_eff(StatAttrEffType::FloorMin, 50.0));
+        assert_eq!(am.apply_modify(100.0), 50.0); // 20 → 保底 50
+
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::FloorMin, 50.0));
+        assert_eq!(am.apply_modify(100.0), 100.0); // 高于下限不受影响
+    }
+
+    /// 上限：公式结果高于上限时取上限，多个上限取最小值
+    #[test]
+    fn test_cap_max() {
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::FinalPer, 1.0));
+        am.reduce(&make_eff(StatAttrEffType::CapMax, 150.0));
+        am.reduce(&make_eff(StatAttrEffType::CapMax, 120.0));
+        assert_eq!(am.apply_modify(100.0), 120.0); // 200 → 封顶 120
+    }
+
+    /// 下限高于上限时上限优先
+    #[test]
+    fn test_cap_max_wins_over_floor_min() {
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::FloorMin, 80.0));
+        am.reduce(&make_eff(StatAttrEffType::CapMax, 60.0));
+        assert_eq!(am.apply_modify(10.0), 60.0);
+        assert_eq!(am.apply_modify(100.0), 60.0);
+    }
+
+    /// 覆盖：无视公式与钳制，取优先级最高者
+    #[test]
+    fn test_override_highest_priority_wins() {
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::BasicAdd, 10.0));
+        am.reduce(&make_eff(StatAttrEffType::FloorMin, 50.0));
+        am.reduce(&make_eff(StatAttrEffType::Override(2), 0.0));
+        am.reduce(&make_eff(StatAttrEffType::Override(1), 70.0));
+        assert_eq!(am.apply_modify(100.0), 0.0); // 定身：优先级 2 的 0 胜出，且不受下限影响
+    }
+
+    /// 覆盖：同优先级取后遍历到的
+    #[test]
+    fn test_override_same_priority_last_wins() {
+        let mut am = StatAttrModifier::default();
+        am.reduce(&make_eff(StatAttrEffType::Override(1), 30.0));
+        am.reduce(&make_eff(StatAttrEffType::Override(1), 40.0));
+        assert_eq!(am.apply_modify(100.0), 40.0);
+    }
+
+    /// 增益/减益判断：保底为增益、封顶为减益、覆盖为中性
+    #[test]
+    fn test_meaning_clamp_and_override() {
+        assert!(
+            make_eff(StatAttrEffType::FloorMin, 50.0)
+                .which_nature()
+                .is_good()
+        );
+        assert!(
+            make_eff(StatAttrEffType::CapMax, 75.0)
+                .which_nature()
+                .is_bad()
+        );
+        assert!(
+            make_eff(StatAttrEffType::Override(0), 0.0)
+                .which_nature()
+                .is_neutral()
+        );
+    }
+
+    /// 曲线：百分比堆叠边际递减，并报告曲线前后的值
+    #[test]
+    fn test_curve_diminishes_stacked_percent() {
+        let curves = StatAttrCurves {
+            final_per: StackCurve::Hyperbolic(1.0),
+            ..Default::default()
+        };
+        let mut am = StatAttrModifier::new(curves);
+        am.reduce(&make_eff(StatAttrEffType::FinalPer, 0.5));
+        am.reduce(&make_eff(StatAttrEffType::FinalPer, 0.5));
+        assert_eq!(am.apply_modify(100.0), 150.0); // 1.0 → 0.5
+
+        let term = am.curved_term(StatAttrEffType::FinalPer).unwrap();
+        assert_eq!(term.pre, 1.0);
+        assert_eq!(term.post, 0.5);
+
+        // 未配置曲线的类型保持线性
+        let term = am.curved_term(StatAttrEffType::BasicPer).unwrap();
+        assert!(!term.is_curved());
+        assert!(am.curved_term(StatAttrEffType::CapMax).is_none());
+    }
+
+    /// 曲线：乘法类以 1 为基线应用硬上限
+    #[test]
+    fn test_curve_hard_cap_final_mul() {
+        let curves = StatAttrCurves {
+            final_mul: StackCurve::HardCap(1.0),
+            ..Default::default()
+        };
+        let mut am = StatAttrModifier::new(curves);
+        am.reduce(&make_eff(StatAttrEffType::FinalMul, 2.0));
+        am.reduce(&make_eff(StatAttrEffType::FinalMul, 3.0));
+        assert_eq!(am.apply_modify(100.0), 200.0); // 6 倍 → 封顶 2 倍
+    }
+
+    /// 可读描述：按类型与正负生成
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            make_eff(StatAttrEffType::BasicAdd, 10.0).describe("力量"),
+            "基础力量增加10"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::BasicAdd, -2.5).describe("力量"),
+            "基础力量减少2.5"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::BasicPer, 0.1).describe("力量"),
+            "基础力量提升10%"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::FinalPer, -0.25).describe("力量"),
+            "力量降低25%"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::FinalMul, 1.5).describe("力量"),
+            "力量变为原先的1.5倍"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::FloorMin, 50.0).describe("攻速"),
+            "攻速不低于50"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::CapMax, 0.75).describe("暴击率"),
+            "暴击率不高于0.75"
+        );
+        assert_eq!(
+            make_eff(StatAttrEffType::Override(0), 0.0).describe("移速"),
+            "移速固定为0"
+        );
+    }
 }
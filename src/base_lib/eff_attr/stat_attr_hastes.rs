@@ -0,0 +1,153 @@
+//! 急速，由 [`StatAttr`] 驱动计时器的推进速率，如冷却缩减、攻击速度
+//!
+//! 急速值 `h` 对应速率 `1 + h` ，计时器的实际时长为 `基础时长 / 速率`：
+//! - 新建计时器时用 [`HasteTracker::scale_duration`] 换算时长
+//! - 急速值变化时，已在计时的计时器按 `旧速率 / 新速率` 同比缩放总时长与剩余时长，进度不变
+//!
+//! 计时器本身不感知急速，[`TimerProgress::remaining`] 始终是真实的剩余时间，
+//! 因此 [`TickTimer`] [`StaticTimer`] 都可直接使用，见 [`TimerRescale`]
+//!
+//! [`TimerProgress::remaining`]: crate::base_lib::cores::timers::tiny_timer::TimerProgress::remaining
+//! [`TickTimer`]: crate::base_lib::cores::timers::tick_timer::TickTimer
+//! [`StaticTimer`]: crate::base_lib::cores::timers::static_timer::StaticTimer
+
+use crate::base_lib::{
+    cores::{
+        attr_nums::AttrNum,
+        timers::tiny_timer::TimerRescale,
+        unify_types::{FLOAT_DEAD_ZONE, time_type},
+    },
+    eff_attr::stat_attrs::StatAttr,
+};
+
+/// 速率下限，防止负急速使计时器停滞
+pub const MIN_HASTE_RATE: f64 = 0.1;
+
+/// 记录已生效的速率，急速值变化时给出缩放比例
+#[derive(Debug, Clone, PartialEq)]
+pub struct HasteTracker {
+    rate: f64,
+}
+
+impl Default for HasteTracker {
+    fn default() -> Self {
+        Self { rate: 1.0 }
+    }
+}
+
+impl HasteTracker {
+    pub fn new<N: AttrNum>(haste: &StatAttr<N>) -> Self {
+        Self {
+            rate: Self::calc_rate(haste.get_current().to_f64()),
+        }
+    }
+
+    pub fn calc_rate(haste: f64) -> f64 {
+        (1.0 + haste).max(MIN_HASTE_RATE)
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// 按当前速率换算新计时器的时长
+    pub fn scale_duration(&self, base: time_type::T) -> time_type::T {
+        if base == time_type::INFINITY {
+            return base;
+        }
+        time_type::from_f64(time_type::to_f64(base) / self.rate)
+    }
+
+    /// 急速值变化时更新速率，返回计时器时长的缩放比例
+    pub fn try_update<N: AttrNum>(&mut self, haste: &StatAttr<N>) -> Option<f64> {
+        let rate = Self::calc_rate(haste.get_current().to_f64());
+        if (rate - self.rate).abs() < FLOAT_DEAD_ZONE {
+            return None;
+        }
+        let ratio = self.rate / rate;
+        self.rate = rate;
+        Some(ratio)
+    }
+
+    /// 急速值变化时缩放所有计时器，返回是否发生变化
+    ///
+    /// 同一急速属性驱动的计时器应一并传入，否则未传入的计时器将错过本次缩放
+    pub fn try_rebase<'a, 'b, N, T, Ctx>(
+        &mut self,
+        haste: &StatAttr<N>,
+        timers: impl IntoIterator<Item = &'a mut T>,
+        ctx: Ctx,
+    ) -> bool
+    where
+        N: AttrNum,
+        Ctx: Copy,
+        T: TimerRescale<Ctx<'b> = Ctx> + 'a,
+    {
+        let Some(ratio) = self.try_update(haste) else {
+            return false;
+        };
+        for timer in timers {
+            timer.rescale(ctx, ratio);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::cores::timers::{
+        static_timer::{StaticTimeline, StaticTimer},
+        tick_timer::TickTimer,
+        tiny_timer::{Tickable, TimerProgress, TimerView},
+    };
+
+    use super::*;
+
+    fn haste_attr(v: f64) -> StatAttr {
+        StatAttr::new(v)
+    }
+
+    /// 新建时按速率换算；计时中急速翻倍，剩余时长减半且进度不变
+    #[test]
+    fn test_rebase_tick_timers() {
+        let mut tracker = HasteTracker::new(&haste_attr(0.25));
+        let mut cooldown = TickTimer::new(tracker.scale_duration(time_type::unit::<10>()));
+        assert_eq!(cooldown.duration(()), time_type::unit::<8>());
+
+        cooldown.tick(time_type::unit::<4>());
+        let mut timers = [cooldown, TickTimer::inf()];
+        assert!(!tracker.try_rebase(&haste_attr(0.25), timers.iter_mut(), ()));
+        assert!(tracker.try_rebase(&haste_attr(1.5), timers.iter_mut(), ()));
+
+        let [cooldown, inf] = &mut timers;
+        assert_eq!(cooldown.remaining(()), time_type::unit::<2>());
+        assert_eq!(cooldown.progress(()), 0.5);
+        assert!(!inf.is_completed(()));
+
+        // 负急速按速率下限钳制
+        assert!(tracker.try_rebase(&haste_attr(-5.0), [&mut *cooldown], ()));
+        assert_eq!(tracker.get_rate(), MIN_HASTE_RATE);
+        assert_eq!(cooldown.progress(()), 0.5);
+    }
+
+    /// 静态计时器从当前时间起重新计算结束时间
+    #[test]
+    fn test_rebase_static_timers() {
+        let mut timeline = StaticTimeline::new();
+        let mut tracker = HasteTracker::default();
+        let mut timers = [
+            StaticTimer::new(&timeline, time_type::unit::<10>()),
+            StaticTimer::new(&timeline, time_type::unit::<2>()),
+        ];
+        timeline.0.tick(time_type::unit::<4>());
+
+        assert!(tracker.try_rebase(&haste_attr(1.0), timers.iter_mut(), &timeline));
+        let [running, done] = &timers;
+        assert_eq!(running.remaining(&timeline), time_type::unit::<3>());
+        assert_eq!(running.duration(&timeline), time_type::unit::<5>());
+        assert!(done.is_completed(&timeline));
+
+        timeline.0.tick(time_type::unit::<3>());
+        assert!(running.is_completed(&timeline));
+    }
+}
@@ -1,46 +1,245 @@
 use crate::base_lib::{
-    cores::unify_types::FixedName,
-    eff_attr::stat_attr_effs::{StatAttrEff, StatAttrModifier},
+    cores::{attr_nums::AttrNum, unify_types::FixedName},
+    eff_attr::{
+        modifiers::ModifierRounding,
+        replications::ReplicatedAttr,
+        stat_attr_effs::{
+            StatAttrCurves, StatAttrEff, StatAttrEffId, StatAttrEffType, StatAttrFormulaTerms,
+            StatAttrModifier,
+        },
+        upsert_container::Upsert,
+    },
 };
 
+/// 单个效果对属性的贡献
+#[derive(Debug)]
+pub struct StatAttrEffBreakdown<S: FixedName> {
+    pub id: StatAttrEffId<S>,
+    pub eff_type: StatAttrEffType,
+    pub eff_value: f64,
+    /// 边际贡献：去掉该效果后最终值的变化量（受曲线、钳制、覆盖影响，各项之和不一定等于总变化量）
+    pub marginal: f64,
+}
+
+/// 属性值的明细，回答“为什么力量是 137 ”
+#[derive(Debug)]
+pub struct StatAttrBreakdown<S: FixedName> {
+    pub origin: f64,
+    pub current: f64,
+    pub terms: StatAttrFormulaTerms,
+    /// 按效果遍历顺序排列
+    pub effs: Vec<StatAttrEffBreakdown<S>>,
+}
+
 /// 状态属性，常用于各种系统的源端，比如 “攻击力/防御力”
 ///
 /// 另一种风格是将所有基础属性展开平铺，每个效果直接修改对应属性，灵活但复杂
+///
+/// 数值类型默认为 [`f64`] ，可替换为其他 [`AttrNum`]
 #[derive(Debug)]
-pub struct StatAttr {
+pub struct StatAttr<N: AttrNum = f64> {
     /// 原始值，未经过修改器修改
-    origin: f64,
+    origin: N,
     /// 当前值，经过修改器修改
-    current: f64,
+    current: N,
+    /// 同类效果的聚合曲线
+    curves: StatAttrCurves,
+    /// 修改器各阶段的舍入规则
+    rounding: ModifierRounding,
 }
 
-impl StatAttr {
-    pub fn new(origin: f64) -> Self {
+impl<N: AttrNum> StatAttr<N> {
+    pub fn new(origin: N) -> Self {
+        Self::new_with_curves(origin, StatAttrCurves::default())
+    }
+
+    pub fn new_with_curves(origin: N, curves: StatAttrCurves) -> Self {
         Self {
             origin,
             current: origin,
+            curves,
+            rounding: ModifierRounding::default(),
         }
     }
 
-    pub fn get_origin(&self) -> f64 {
+    pub fn with_rounding(mut self, rounding: ModifierRounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn get_origin(&self) -> N {
         self.origin
     }
 
-    pub fn get_current(&self) -> f64 {
+    pub fn get_current(&self) -> N {
         self.current
     }
 
+    /// 按本属性的曲线聚合效果（跳过条件不满足的效果），也可用于界面提示查询曲线前后的值
+    pub fn build_modifier<'a, S: FixedName + 'a, Timer: 'a, T: FixedName + 'a>(
+        &self,
+        effs: impl Iterator<Item = &'a StatAttrEff<S, Timer, T>>,
+    ) -> StatAttrModifier {
+        let mut modifier = StatAttrModifier::new(self.curves);
+
+        for ele in effs.filter(|ele| ele.is_active()) {
+            modifier.reduce(ele);
+        }
+
+        modifier
+    }
+
     /// 刷新属性，在效果更新后
-    pub fn refresh_value<'a, S: FixedName + 'a, Timer: 'a>(
+    pub fn refresh_value<'a, S: FixedName + 'a, Timer: 'a, T: FixedName + 'a>(
         &mut self,
-        effs: impl Iterator<Item = &'a StatAttrEff<S, Timer>>,
+        effs: impl Iterator<Item = &'a StatAttrEff<S, Timer, T>>,
     ) {
-        let mut modifier = StatAttrModifier::default();
+        let modifier = self.build_modifier(effs);
 
-        for ele in effs {
-            modifier.reduce(ele);
+        self.current = modifier.apply_modify_num(self.origin, self.rounding)
+    }
+
+    /// 查询属性值明细，用于界面提示和调试；逐个剔除效果计算边际贡献，开销为 O(n^2) ，不应每帧调用
+    ///
+    /// 明细统一以 [`f64`] 表示，最终值与边际贡献为舍入后的值，公式中间项为舍入前的值
+    pub fn breakdown<'a, S: FixedName + 'a, Timer: 'a, T: FixedName + 'a>(
+        &self,
+        effs: impl Iterator<Item = &'a StatAttrEff<S, Timer, T>>,
+    ) -> StatAttrBreakdown<S> {
+        let effs: Vec<_> = effs.collect();
+        let modifier = self.build_modifier(effs.iter().copied());
+        let current = modifier
+            .apply_modify_num(self.origin, self.rounding)
+            .to_f64();
+
+        let eff_breakdowns = effs
+            .iter()
+            .enumerate()
+            .map(|(idx, eff)| {
+                let others = effs
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_idx, _)| *other_idx != idx)
+                    .map(|(_, other)| *other);
+                let without = self
+                    .build_modifier(others)
+                    .apply_modify_num(self.origin, self.rounding)
+                    .to_f64();
+                StatAttrEffBreakdown {
+                    id: eff.gen_id(),
+                    eff_type: eff.get_type(),
+                    eff_value: eff.get_effect().get_effect_value(),
+                    marginal: current - without,
+                }
+            })
+            .collect();
+
+        StatAttrBreakdown {
+            origin: self.origin.to_f64(),
+            current,
+            terms: modifier.formula_terms(self.origin.to_f64()),
+            effs: eff_breakdowns,
         }
+    }
+}
+
+impl<N: AttrNum> ReplicatedAttr for StatAttr<N> {
+    type Num = N;
+
+    fn get_replicated(&self) -> N {
+        self.current
+    }
+
+    /// 只覆盖当前值，客户端不应再以效果刷新
+    fn set_replicated(&mut self, v: N) {
+        self.current = v;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::{attr_nums::RoundingMode, timers::tick_timer::TickTimer},
+        eff_attr::{effects::Effect, upsert_container::UpsertContainer},
+    };
+
+    use super::*;
+
+    fn make_eff(
+        eff_name: &'static str,
+        eff_type: StatAttrEffType,
+        eff_value: f64,
+    ) -> StatAttrEff<&'static str, TickTimer> {
+        StatAttrEff::new(
+            eff_type,
+            Effect::new("player", eff_name, eff_value),
+            TickTimer::inf(),
+        )
+    }
+
+    /// 明细：每个效果的边际贡献与公式中间项
+    #[test]
+    fn test_breakdown_marginal_and_terms() {
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(make_eff("ring", StatAttrEffType::BasicAdd, 20.0));
+        effs.upsert_replace(make_eff("buff", StatAttrEffType::BasicPer, 0.5));
+        effs.upsert_replace(make_eff("rage", StatAttrEffType::FinalPer, 0.1));
+
+        let mut attr = StatAttr::new(100.0);
+        attr.refresh_value(effs.iter_ele());
+        let bd = attr.breakdown(effs.iter_ele());
+
+        // (100 * 1.5 + 20) * 1.1 = 187
+        assert_eq!(bd.current, attr.get_current());
+        assert_eq!(bd.terms.basic_value, 170.0);
+        assert_eq!(bd.terms.formula_value, bd.current);
+        assert!(bd.terms.override_value.is_none());
+
+        let marginals: Vec<_> = bd.effs.iter().map(|e| (e.id.eff, e.marginal)).collect();
+        assert_eq!(marginals.len(), 3);
+        assert_eq!(marginals[0].0, "ring");
+        assert!((marginals[0].1 - 22.0).abs() < 1e-9); // 187 - 165
+        assert!((marginals[1].1 - 55.0).abs() < 1e-9); // 187 - 132
+        assert!((marginals[2].1 - 17.0).abs() < 1e-9); // 187 - 170
+    }
+
+    /// 明细：被覆盖时其余效果的边际贡献为 0
+    #[test]
+    fn test_breakdown_override_masks_others() {
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(make_eff("ring", StatAttrEffType::BasicAdd, 20.0));
+        effs.upsert_replace(make_eff("root", StatAttrEffType::Override(0), 0.0));
+
+        let attr = StatAttr::new(100.0);
+        let bd = attr.breakdown(effs.iter_ele());
+        assert_eq!(bd.current, 0.0);
+        assert_eq!(bd.terms.override_value, Some(0.0));
+        assert_eq!(bd.effs[0].marginal, 0.0);
+        assert_eq!(bd.effs[1].marginal, -120.0);
+    }
+
+    /// 整数属性：各阶段按舍入规则取整
+    #[test]
+    fn test_integer_attr_rounding() {
+        let mut effs = UpsertContainer::default();
+        effs.upsert_replace(make_eff("buff", StatAttrEffType::BasicPer, 0.25));
+        effs.upsert_replace(make_eff("rage", StatAttrEffType::FinalPer, 0.1));
+
+        // 10 * 1.25 = 12.5 → 12 ； 12 * 1.1 = 13.2 → 13
+        let mut attr = StatAttr::<i64>::new(10).with_rounding(ModifierRounding {
+            basic: RoundingMode::Floor,
+            formula: RoundingMode::Nearest,
+        });
+        attr.refresh_value(effs.iter_ele());
+        assert_eq!(attr.get_current(), 13);
+
+        // 上限向下取整
+        effs.upsert_replace(make_eff("cap", StatAttrEffType::CapMax, 12.9));
+        attr.refresh_value(effs.iter_ele());
+        assert_eq!(attr.get_current(), 12);
 
-        self.current = modifier.apply_modify(self.origin)
+        let bd = attr.breakdown(effs.iter_ele());
+        assert_eq!(bd.current, 12.0);
+        assert_eq!(bd.terms.basic_value, 12.5);
     }
 }
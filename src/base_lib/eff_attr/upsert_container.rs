@@ -2,7 +2,7 @@
 
 use std::{fmt::Debug, hash::Hash};
 
-use crate::base_lib::cores::unify_types::time_type;
+use crate::base_lib::{cores::unify_types::time_type, eff_attr::eff_stores::EffStore};
 
 /// 可合并类型
 pub trait Upsert {
@@ -117,6 +117,17 @@ impl<E: Upsert> UpsertContainer<E> {
         }
     }
 
+    /// 取出（删除并返回所有权），用于元素移出后继续流转，如延迟效果到期后生效
+    pub fn take_ele<F>(&mut self, find_logic: F) -> Option<E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        let ele = Self::locate_slot(&mut self.ll, find_logic)?.take();
+        self.hole_count += 1;
+        self.changed_flag = true;
+        ele
+    }
+
     /// 查询以更新(修改即置脏契约:命中即置脏,不论是否实际修改;只读请走 [`Self::iter_ele`])
     pub fn select_mut_ele<F>(&mut self, find_logic: F) -> Option<&mut E>
     where
@@ -140,23 +151,23 @@ impl<E: Upsert> UpsertContainer<E> {
         self.ele_len() == 0
     }
 
+    /// 记录的空洞数
+    pub fn get_hole_count(&self) -> usize {
+        self.hole_count
+    }
+
+    /// 实际的空洞数，遍历统计，用于校验记录的空洞数
+    pub fn count_holes(&self) -> usize {
+        self.ll.iter().filter(|e| e.is_none()).count()
+    }
+
     /// 清理空洞，让元素排列紧凑，不影响容量
     ///
     /// 阈值触发，若空洞数过少或空洞率很低，则不进行任何操作
     ///
     /// 应该定时去执行，如 5s 清理一次
     pub fn try_clean_hole(&mut self) {
-        if self.hole_count < 3 {
-            // 空洞数过少则不回收
-            return;
-        } else if self.hole_count > 50 {
-            // 异常膨胀，无论比率如何，都进行压缩
-            self.do_clean_hole();
-            return;
-        }
-
-        // 空洞率达到 25% 才压缩
-        if self.hole_count * 4 >= self.ll.len() {
+        if should_compact(self.hole_count, self.ll.len()) {
             self.do_clean_hole();
         }
     }
@@ -176,6 +187,20 @@ impl<E: Upsert> UpsertContainer<E> {
     }
 }
 
+/// 空洞回收的阈值，各效果存储后端共用（见 [`super::eff_stores`]）
+pub(crate) fn should_compact(hole_count: usize, len: usize) -> bool {
+    if hole_count < 3 {
+        // 空洞数过少则不回收
+        false
+    } else if hole_count > 50 {
+        // 异常膨胀，无论比率如何，都进行压缩
+        true
+    } else {
+        // 空洞率达到 25% 才压缩
+        hole_count * 4 >= len
+    }
+}
+
 /// 集合定时清理工具:自持默认周期(time_type::DEFAULT_REFRESH_PERIOD),可构造覆盖
 #[derive(Debug)]
 pub struct UpsertContainerCleaner {
@@ -209,10 +234,10 @@ impl UpsertContainerCleaner {
     }
 
     /// 单一编排入口:按周期累加,到期时对迭代到的每个容器执行清洞
-    pub fn clean_holes<'a, E: Upsert + 'a>(
+    pub fn clean_holes<'a, E: Upsert + 'a, C: EffStore<E> + 'a>(
         &mut self,
         delta: time_type::T,
-        containers: impl Iterator<Item = &'a mut UpsertContainer<E>>,
+        containers: impl Iterator<Item = &'a mut C>,
     ) {
         if self.should_clean_hole(delta) {
             for c in containers {
@@ -357,6 +382,24 @@ mod tests {
         assert_eq!(c.ele_len(), 0);
     }
 
+    /// take：取出所有权并产生空洞，未命中返回 None
+    #[test]
+    fn test_take_returns_ownership() {
+        let mut c = UpsertContainer::<TestEff>::default();
+        c.upsert_ele(TestEff::new(1), |_, _| {});
+        c.upsert_ele(TestEff::new(2), |_, _| {});
+        c.reset_changed_flag();
+
+        let taken = c.take_ele(|e| e.id == 2).unwrap();
+        assert_eq!(taken.id, 2);
+        assert_eq!(c.ele_len(), 1);
+        assert_eq!(c.hole_count, 1);
+        assert!(c.is_changed());
+
+        assert!(c.take_ele(|e| e.id == 2).is_none());
+        assert_eq!(c.hole_count, 1);
+    }
+
     /// 空洞数过少（< 3）不回收
     #[test]
     fn test_clean_hole_too_few_no_op() {
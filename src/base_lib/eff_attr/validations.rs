@@ -0,0 +1,311 @@
+//! 属性管线的不变量校验，按需调用（如浸泡测试、加载模组数据后），不影响正常运行
+//!
+//! 校验内容：
+//! - 有界属性的快照值在上下限内，且上下限没有颠倒
+//! - 效果存储的空洞计数、 id 索引与实际一致，且没有重复 id
+//! - 复合属性的层级拓扑（同 [`attr_layer_system::check_attr_layer`] ，但不 panic ）
+//!
+//! 所有问题收集为 [`AttrViolation`] 返回；调试构建下可用 [`AttrValidator::debug_assert_valid`] 断言
+//!
+//! [`attr_layer_system::check_attr_layer`]: super::attr_layers::attr_layer_system::check_attr_layer
+
+use std::fmt::Debug;
+
+use crate::base_lib::{
+    cores::{attr_nums::AttrNum, unify_types::FLOAT_DEAD_ZONE},
+    eff_attr::{
+        attr_layers::AttrLayerType,
+        bounded_attrs::{BoundValue, BoundedAttr},
+        eff_stores::EffStore,
+        upsert_container::Upsert,
+    },
+};
+
+/// 违反的不变量，`key` 为调用方给出的属性标识的 [`Debug`] 输出
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrViolation {
+    /// 值不是有限数
+    NotFinite { key: String, value: f64 },
+    /// 下限大于上限
+    InvertedBounds { key: String, lower: f64, upper: f64 },
+    /// 值超出上下限
+    OutOfBounds {
+        key: String,
+        value: f64,
+        lower: f64,
+        upper: f64,
+    },
+    /// 记录的空洞数与实际不符
+    HoleCountMismatch {
+        key: String,
+        recorded: usize,
+        actual: usize,
+    },
+    /// 容器内存在相同 id 的元素
+    DuplicateId { key: String, id: String },
+    /// 带索引的存储中索引与内容不一致
+    IndexMismatch { key: String },
+    /// 下一层级不是恰好小 1
+    LayerStep {
+        from: String,
+        to: String,
+        from_layer: u8,
+        to_layer: u8,
+    },
+}
+
+/// 不变量校验的收集器
+#[derive(Debug, Default)]
+pub struct AttrValidator {
+    violations: Vec<AttrViolation>,
+}
+
+impl AttrValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn get_violations(&self) -> &[AttrViolation] {
+        &self.violations
+    }
+
+    pub fn into_violations(self) -> Vec<AttrViolation> {
+        self.violations
+    }
+
+    /// 调试构建下断言没有违反，发布构建下无开销
+    pub fn debug_assert_valid(&self) {
+        debug_assert!(self.is_valid(), "attr violations: {:?}", self.violations);
+    }
+
+    /// 校验有界属性的快照值，应在提交中间态后调用（计算过程中的中间态允许暂时越界）
+    pub fn check_bounded<N, V1, V2>(
+        &mut self,
+        key: impl Debug,
+        attr: &BoundedAttr<N>,
+        lower: V1,
+        upper: V2,
+    ) -> &mut Self
+    where
+        N: AttrNum,
+        BoundValue: From<V1>,
+        BoundValue: From<V2>,
+    {
+        let key = format!("{key:?}");
+        let value = attr.get_snapshot_value().to_f64();
+        let lower = BoundValue::from(lower).get_value();
+        let upper = BoundValue::from(upper).get_value();
+
+        if !value.is_finite() {
+            self.violations
+                .push(AttrViolation::NotFinite { key, value });
+        } else if lower > upper + FLOAT_DEAD_ZONE {
+            self.violations
+                .push(AttrViolation::InvertedBounds { key, lower, upper });
+        } else if value < lower - FLOAT_DEAD_ZONE || value > upper + FLOAT_DEAD_ZONE {
+            self.violations.push(AttrViolation::OutOfBounds {
+                key,
+                value,
+                lower,
+                upper,
+            });
+        }
+        self
+    }
+
+    /// 校验效果存储的空洞计数、 id 索引与 id 唯一性，支持 [`EffStore`] 的所有后端
+    pub fn check_container<E: Upsert>(
+        &mut self,
+        key: impl Debug,
+        container: &impl EffStore<E>,
+    ) -> &mut Self {
+        let key = format!("{key:?}");
+        if !container.is_index_consistent() {
+            self.violations
+                .push(AttrViolation::IndexMismatch { key: key.clone() });
+        }
+        let (recorded, actual) = container.get_hole_counts();
+        if recorded != actual {
+            self.violations.push(AttrViolation::HoleCountMismatch {
+                key: key.clone(),
+                recorded,
+                actual,
+            });
+        }
+
+        let mut ids: Vec<E::Id> = Vec::new();
+        for ele in container.iter_ele() {
+            if ids.iter().any(|id| ele.matched_id(id)) {
+                self.violations.push(AttrViolation::DuplicateId {
+                    key: key.clone(),
+                    id: format!("{:?}", ele.gen_id()),
+                });
+            } else {
+                ids.push(ele.gen_id());
+            }
+        }
+        self
+    }
+
+    /// 校验从 `start_at` 出发的层级链，每一步恰好小 1 即保证无环
+    pub fn check_layer<L: AttrLayerType>(&mut self, start_at: L) -> &mut Self {
+        let mut current = start_at;
+        loop {
+            let next = current.get_next();
+            if next == current {
+                return self;
+            }
+            if current.get_layer().checked_sub(1) != Some(next.get_layer()) {
+                self.violations.push(AttrViolation::LayerStep {
+                    from: format!("{current:?}"),
+                    to: format!("{next:?}"),
+                    from_layer: current.get_layer(),
+                    to_layer: next.get_layer(),
+                });
+                return self;
+            }
+            current = next;
+        }
+    }
+
+    /// 校验所有层级
+    pub fn check_layers<L: AttrLayerType>(
+        &mut self,
+        layers: impl IntoIterator<Item = L>,
+    ) -> &mut Self {
+        for layer in layers {
+            self.check_layer(layer);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::timers::tick_timer::TickTimer,
+        eff_attr::{
+            bound_attr_effs::{BoundAttrEff, BoundAttrEffType},
+            eff_stores::{HashEffStore, SlotEffStore},
+            effects::Effect,
+            upsert_container::UpsertContainer,
+        },
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum BadLayer {
+        Bottom,
+        Skip,
+        BelowBottom,
+    }
+
+    impl AttrLayerType for BadLayer {
+        fn get_next(&self) -> Self {
+            match self {
+                Self::Bottom => Self::Bottom,
+                Self::Skip => Self::Bottom,
+                Self::BelowBottom => Self::Bottom,
+            }
+        }
+
+        fn get_layer(&self) -> u8 {
+            match self {
+                Self::Bottom => 0,
+                Self::Skip => 2,
+                // 底层之下，原校验会因减法溢出 panic
+                Self::BelowBottom => 0,
+            }
+        }
+    }
+
+    /// 越界、颠倒的上下限与非有限值
+    #[test]
+    fn test_check_bounded() {
+        let mut health = BoundedAttr::new(100.0);
+        health.apply_eff(20.0);
+        health.commit_pending_value();
+
+        let mut validator = AttrValidator::new();
+        validator
+            .check_bounded("health", &health, 0.0, 120.0)
+            .check_bounded("health", &health, 0.0, 100.0)
+            .check_bounded("health", &health, 10.0, 5.0)
+            .check_bounded("nan", &BoundedAttr::new(f64::NAN), 0.0, 1.0);
+        assert_eq!(
+            &validator.get_violations()[..2],
+            &[
+                AttrViolation::OutOfBounds {
+                    key: "\"health\"".to_string(),
+                    value: 120.0,
+                    lower: 0.0,
+                    upper: 100.0,
+                },
+                AttrViolation::InvertedBounds {
+                    key: "\"health\"".to_string(),
+                    lower: 10.0,
+                    upper: 5.0,
+                },
+            ]
+        );
+        assert!(matches!(
+            validator.get_violations()[2],
+            AttrViolation::NotFinite { .. }
+        ));
+    }
+
+    /// 容器记账正确时没有违反；层级跳级与底层之下均被报告而不 panic
+    #[test]
+    fn test_check_container_and_layers() {
+        let mut container = UpsertContainer::default();
+        for from_name in ["alice", "bob"] {
+            container.upsert_replace(BoundAttrEff::new(
+                BoundAttrEffType::BasicAdd,
+                Effect::new(from_name, "vigor", 10.0),
+                TickTimer::inf(),
+            ));
+        }
+        container.delete_ele(|e| *e.get_effect().get_from_name() == "alice");
+
+        let mut validator = AttrValidator::new();
+        validator.check_container("buffs", &container);
+        validator.debug_assert_valid();
+
+        // 带索引的后端同样可校验
+        let mut store = SlotEffStore::default();
+        for eff in container.iter_ele() {
+            store.upsert_replace(eff.clone());
+        }
+        store.delete_ele(|e| *e.get_effect().get_from_name() == "bob");
+        validator.check_container("slot_buffs", &store);
+        let mut store = HashEffStore::default();
+        store.upsert_replace(container.iter_ele().next().unwrap().clone());
+        validator.check_container("hash_buffs", &store);
+        validator.debug_assert_valid();
+
+        validator.check_layers([BadLayer::Bottom, BadLayer::Skip, BadLayer::BelowBottom]);
+        let violations = validator.into_violations();
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            violations[0],
+            AttrViolation::LayerStep {
+                from_layer: 2,
+                to_layer: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            violations[1],
+            AttrViolation::LayerStep {
+                from_layer: 0,
+                to_layer: 0,
+                ..
+            }
+        ));
+    }
+}
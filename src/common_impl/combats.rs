@@ -13,6 +13,8 @@
 //! - 盔甲坚韧
 //! - 盔甲柔韧
 //! - 盔甲质量
+//! - 元素抗性（熵、电势能）
+//! - 攻击急速与冷却急速，缩放计时中的攻击间隔与技能冷却
 //!
 //! 战斗属性 直接表达战斗时的各种状态
 //! - 能量（气势）
@@ -84,4 +86,10 @@ pub mod energies;
 
 pub mod damages;
 
+pub mod elements;
+
+pub mod periodic_effs;
+
+pub mod delayed_effs;
+
 pub mod combat_unit_systems;
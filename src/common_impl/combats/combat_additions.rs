@@ -24,8 +24,28 @@ pub struct ArmorSoft(pub StatAttr);
 /// 盔甲质量
 pub struct ArmorMass(pub StatAttr);
 
+/// 熵抗性 来自盔甲，按比例减免炎热与寒冷的积攒，见 [`super::elements`]
+pub struct EntropyResist(pub StatAttr);
+
+/// 电抗性 来自盔甲，按比例减免电的积攒
+pub struct ElectricResist(pub StatAttr);
+
+/// 攻击急速，缩放攻击间隔，见 [`HasteTracker`]
+///
+/// [`HasteTracker`]: crate::base_lib::eff_attr::stat_attr_hastes::HasteTracker
+pub struct AttackHaste(pub StatAttr);
+
+/// 冷却急速，缩放技能冷却，见 [`HasteTracker`]
+///
+/// [`HasteTracker`]: crate::base_lib::eff_attr::stat_attr_hastes::HasteTracker
+pub struct CooldownHaste(pub StatAttr);
+
 pub struct WeaponSharpEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
 pub struct WeaponMassEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
 pub struct ArmorHardEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
 pub struct ArmorSoftEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
 pub struct ArmorMassEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
+pub struct EntropyResistEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
+pub struct ElectricResistEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
+pub struct AttackHasteEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
+pub struct CooldownHasteEffs<S: FixedName>(pub UpsertContainer<StatAttrEff<S, StaticTimer>>);
@@ -5,25 +5,41 @@
 //! - 减益伤害类入参用负值(与 `EffectMeaning::Bad` 语义一致)。
 //! - ECS 语义:护盾上限由 [`crate::base_lib::eff_attr::attr_systems`] 每帧依脏标签刷新,
 //!   护盾当前值由伤害管线经缓冲消费([`load_shield_or_health_upper`] 仅编排不入值);
+//!   记录来源明细的护盾层, 上限与当前值均由明细得出([`sync_shield_with_stack`]);
 //!   即时变更(cost/cut)直接应用,不经缓冲。
 
+use strum::IntoEnumIterator;
+
 use crate::{
     base_lib::{
-        cores::{timers::static_timer::StaticTimer, unify_types::FixedName},
+        cores::{
+            timers::static_timer::{StaticTimeline, StaticTimer},
+            tiny_tags::PureTagContainer,
+            unify_types::{FLOAT_DEAD_ZONE, FixedName},
+        },
         eff_attr::{
+            attr_systems::try_refresh_dirty_bound_attr_rebound,
             bound_attr_effs::{BoundAttrEff, BoundAttrEffType},
             bound_attrs::BoundAttr,
-            bounded_attr_effs::AttrAlterEff,
-            bounded_attrs::BoundedAttr,
+            bounded_attr_effs::{AttrAlterEff, AttrCost, AttrCostPool, attr_alter_system},
+            bounded_attr_regens::RegenEvent,
+            bounded_attr_stacks::StackEntry,
+            bounded_attrs::{BoundSide, BoundValue, BoundedAttr, ReboundEvent, ReboundPolicy},
+            eff_stores::EffStore,
+            effect_specs::LevelCurveSource,
             effects::Effect,
             upsert_container::UpsertContainer,
+            validations::AttrValidator,
         },
     },
     common_impl::combats::{
         combat_additions::ArmorHard,
         combat_inherents::{Belief, Strength},
         combat_units::{
-            Health, HealthLower, HealthUpper, Magicka, MagickaUpper, Stamina, StaminaUpper,
+            EnergyAttrLayer, Health, HealthLower, HealthUpper, Magicka, MagickaUpper,
+            ResourceReboundPolicies, ShieldArcane, ShieldArcaneUpper, ShieldDefence,
+            ShieldDefenceUpper, ShieldStack, ShieldSubstitute, ShieldSubstituteUpper, Stamina,
+            StaminaUpper, SurvivalAttrLayer, SurvivalShieldStacks,
         },
         damages::{SurvivalAttrEff, SurvivalEffBuffer, SurvivalEffTargets, damage_system},
         energies::{EnergyEffBuffer, MagickaEnergyLevel},
@@ -33,6 +49,9 @@ use crate::{
 /// 花费能量后允许的最低值(资源下限门槛,即时扣减用)
 const COST_FLOOR: f64 = 0.0;
 
+/// 护盾的下限
+const SHIELD_FLOOR: f64 = 0.0;
+
 /// 初始化三维的配置参数(由上层传入,不含魔法数字)
 pub struct ThreeBarsConfig {
     pub health_base: f64,
@@ -43,6 +62,38 @@ pub struct ThreeBarsConfig {
     pub magicka_energy_level: MagickaEnergyLevel,
 }
 
+/// 三维配置各项对应的曲线名称，见 [`ThreeBarsConfig::from_level_curves`]
+pub struct ThreeBarsCurveNames<S> {
+    pub health_base: S,
+    pub health_scale: S,
+    pub magicka_base: S,
+    pub magicka_scale: S,
+    pub stamina_max: S,
+    /// 魔法能级的三档，见 [`MagickaEnergyLevel::new`]
+    pub magicka_energy_level: [S; 3],
+}
+
+impl ThreeBarsConfig {
+    /// 按等级从曲线(如 [`crate::base_lib::eff_attr::curve_tables::CurveTable`])取得所有配置项,
+    /// 任一曲线不存在时返回 None, 同 [`crate::common_impl::combats::equips::EquipWeapon::new_at_level`]
+    pub fn from_level_curves<S>(
+        curves: &impl LevelCurveSource<S>,
+        names: &ThreeBarsCurveNames<S>,
+        level: f64,
+    ) -> Option<Self> {
+        let eval = |name: &S| curves.eval_curve(name, level);
+        let [l0, l1, l2] = &names.magicka_energy_level;
+        Some(Self {
+            health_base: eval(&names.health_base)?,
+            health_scale: eval(&names.health_scale)?,
+            magicka_base: eval(&names.magicka_base)?,
+            magicka_scale: eval(&names.magicka_scale)?,
+            stamina_max: eval(&names.stamina_max)?,
+            magicka_energy_level: MagickaEnergyLevel::new(eval(l0)?, eval(l1)?, eval(l2)?),
+        })
+    }
+}
+
 /// 角色出生三维(按内禀属性生成,返回所有权装配到实体)
 pub struct ThreeBars {
     pub health: Health,
@@ -126,7 +177,165 @@ pub fn load_shield_or_health_upper<S: FixedName>(
     svv_eff_buffer.push(value_eff);
 }
 
-/// 花费能量(硬扣): 直接修改, 推入 buffer
+/// 装载护盾并记录来源明细: 明细即护盾, 不经上限效果容器与缓冲
+///
+/// 提供的量按 `upper_bound` 折算为绝对值（同 [`gen_shield_or_health_upper`]）,
+/// 计时器只由明细持有, 急速缩放与重置持续时间见 [`ShieldStack::iter_mut`] [`ShieldStack::reset_timer`]
+///
+/// 护盾的上限与当前值由 [`sync_shield_with_stack`] 得出
+pub fn load_shield_with_stack<S: FixedName>(
+    shield_stack: &mut ShieldStack<S>,
+    upper_bound: &BoundAttr,
+    eff_type: BoundAttrEffType,
+    mut effect: Effect<S>,
+    duration: StaticTimer,
+) {
+    let eff_val = eff_type.calc_real_val(upper_bound.get_current(), effect.get_effect_value());
+    effect.set_effect_value(eff_val);
+    shield_stack.push(effect, duration);
+}
+
+/// 由来源明细得出一层护盾: 移除到期的明细并返回, 上限为基础值加明细之和, 当前值为明细之和
+///
+/// 到期的明细恰好带走其剩余量, 伤害由伤害系统同时从护盾与明细中扣除
+/// （见 [`crate::common_impl::combats::damages::DamageHooks::with_shield_stacks`]）,
+/// 因此每帧伤害结算前后调用均可
+pub fn sync_shield_with_stack<S: FixedName>(
+    shield: &mut BoundedAttr,
+    shield_upper: &mut BoundAttr,
+    shield_stack: &mut ShieldStack<S>,
+    timeline: &StaticTimeline,
+) -> Vec<StackEntry<S, StaticTimer>> {
+    let expired = shield_stack.clean_expired(timeline);
+
+    let bound_effs: Vec<_> = shield_stack
+        .iter()
+        .map(|entry| BoundAttrEff::new(BoundAttrEffType::BasicAdd, entry.get_effect().clone(), ()))
+        .collect();
+    shield_upper.refresh_value(bound_effs.iter());
+
+    shield.apply_eff(shield_stack.get_total() - shield.get_pending_value());
+    shield.clamp_by(SHIELD_FLOOR, &*shield_upper);
+
+    expired
+}
+
+/// 受上限约束的一种资源: 上限及其效果容器、当前值
+pub struct ReboundPair<'a, E> {
+    pub upper: &'a mut BoundAttr,
+    pub upper_effs: &'a mut E,
+    pub value: &'a mut BoundedAttr,
+}
+
+impl<E> ReboundPair<'_, E> {
+    fn try_refresh<S, Timer, T, V>(
+        &mut self,
+        tags: &impl PureTagContainer<PureTag = T>,
+        lower: V,
+        policy: ReboundPolicy,
+    ) -> Option<ReboundEvent>
+    where
+        S: FixedName,
+        T: FixedName,
+        E: EffStore<BoundAttrEff<S, Timer, T>>,
+        BoundValue: From<V>,
+    {
+        try_refresh_dirty_bound_attr_rebound(
+            self.upper,
+            self.upper_effs,
+            tags,
+            BoundSide::Upper,
+            lower,
+            self.value,
+            policy,
+        )
+    }
+}
+
+/// 上限刷新用的三维与各层护盾, 生命值的下限只作为约束, 不在此刷新
+pub struct CombatReboundAttrs<'a, E> {
+    pub health: ReboundPair<'a, E>,
+    pub health_lower: &'a HealthLower,
+    pub stamina: ReboundPair<'a, E>,
+    pub magicka: ReboundPair<'a, E>,
+    pub shield_sub: ReboundPair<'a, E>,
+    pub shield_def: ReboundPair<'a, E>,
+    pub shield_arc: ReboundPair<'a, E>,
+}
+
+/// 上限刷新后各资源当前值被修正的事件, 与 [`ResourceReboundPolicies`] 一一对应
+#[derive(Debug)]
+pub struct CombatReboundEvents<S: FixedName> {
+    pub shield_sub: Option<ReboundEvent>,
+    pub shield_def: Option<ReboundEvent>,
+    pub shield_arc: Option<ReboundEvent>,
+    pub health: Option<ReboundEvent>,
+    pub stamina: Option<ReboundEvent>,
+    pub magicka: Option<ReboundEvent>,
+    /// 到期移除的护盾明细, 仅启用明细时记录
+    pub shield_expired: Vec<(SurvivalAttrLayer, StackEntry<S, StaticTimer>)>,
+}
+
+/// 刷新三维与各层护盾的上限, 并按 [`ResourceReboundPolicies`] 修正当前值, 应在帧开头触发
+///
+/// 启用护盾明细时各层护盾以明细为准（见 [`sync_shield_with_stack`]）, 忽略护盾上限的效果容器与策略,
+/// 当前值的变化（如护盾到期）同样报告为事件
+pub fn try_refresh_combat_bounds<S, Timer, T, E>(
+    attrs: CombatReboundAttrs<E>,
+    tags: &impl PureTagContainer<PureTag = T>,
+    policies: &ResourceReboundPolicies,
+    mut shield_stacks: Option<(&mut SurvivalShieldStacks<S>, &StaticTimeline)>,
+) -> CombatReboundEvents<S>
+where
+    S: FixedName,
+    T: FixedName,
+    E: EffStore<BoundAttrEff<S, Timer, T>>,
+{
+    let CombatReboundAttrs {
+        mut health,
+        health_lower,
+        mut stamina,
+        mut magicka,
+        shield_sub,
+        shield_def,
+        shield_arc,
+    } = attrs;
+
+    let mut shield_expired = Vec::new();
+
+    let mut refresh_shield = |layer, mut pair: ReboundPair<E>| match shield_stacks
+        .as_mut()
+        .and_then(|(stacks, timeline)| Some((stacks.get_stack_mut(layer)?, *timeline)))
+    {
+        Some((stack, timeline)) => {
+            let old_value = pair.value.get_pending_value();
+            let expired = sync_shield_with_stack(pair.value, pair.upper, stack, timeline);
+            shield_expired.extend(expired.into_iter().map(|entry| (layer, entry)));
+            let new_value = pair.value.get_pending_value();
+            (new_value != old_value).then_some(ReboundEvent {
+                old_value,
+                new_value,
+            })
+        }
+        None => pair.try_refresh(tags, SHIELD_FLOOR, policies.get_survival_policy(layer)),
+    };
+
+    CombatReboundEvents {
+        shield_sub: refresh_shield(SurvivalAttrLayer::ShieldSubstitute, shield_sub),
+        shield_def: refresh_shield(SurvivalAttrLayer::ShieldDefence, shield_def),
+        shield_arc: refresh_shield(SurvivalAttrLayer::ShieldArcane, shield_arc),
+        health: health.try_refresh(tags, &health_lower.0, policies.health),
+        stamina: stamina.try_refresh(tags, COST_FLOOR, policies.stamina),
+        magicka: magicka.try_refresh(tags, COST_FLOOR, policies.magicka),
+        shield_expired,
+    }
+}
+
+/// 花费能量(硬扣): 推入 buffer, 由能量系统结算
+///
+/// 打断恢复的 [`RegenEvent::Spent`] 由结算结果 [`EnergyInfo::get_regen_event`] 给出
+///
+/// [`EnergyInfo::get_regen_event`]: crate::common_impl::combats::energies::EnergyInfo::get_regen_event
 pub fn cost_magicka<S: FixedName>(buffer: &mut EnergyEffBuffer<S>, eff: AttrAlterEff<S>) {
     buffer.push(eff);
 }
@@ -142,6 +351,109 @@ pub fn try_cost_magicka<S: FixedName>(
     bounded_attr.apply_eff_checked(COST_FLOOR, abs_val, COST_FLOOR)
 }
 
+/// 可被即时消耗的战斗资源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombatResource {
+    Health,
+    Stamina,
+    Magicka,
+}
+
+/// 多资源消耗时借用的三维及其上限
+pub struct CombatResourcePool<'a> {
+    pub health: &'a mut Health,
+    pub health_upper: &'a HealthUpper,
+    pub stamina: &'a mut Stamina,
+    pub stamina_upper: &'a StaminaUpper,
+    pub magicka: &'a mut Magicka,
+    pub magicka_upper: &'a MagickaUpper,
+}
+
+impl AttrCostPool<CombatResource> for CombatResourcePool<'_> {
+    fn get_cost_attr(&mut self, resource: CombatResource) -> (&mut BoundedAttr, &BoundAttr) {
+        match resource {
+            CombatResource::Health => (&mut self.health.0, &self.health_upper.0),
+            CombatResource::Stamina => (&mut self.stamina.0, &self.stamina_upper.0),
+            CombatResource::Magicka => (&mut self.magicka.0, &self.magicka_upper.0),
+        }
+    }
+}
+
+/// 尝试花费多种资源(软扣): 全部足够才全部扣除, 否则返回不足的资源; 顺序同 [`try_cost_magicka`]
+///
+/// 每项的最低值由调用方给出, 如生命消耗一般要求不低于 1 , 能量消耗不低于 0
+pub fn try_cost_resources<S: FixedName>(
+    pool: &mut CombatResourcePool,
+    costs: &[AttrCost<CombatResource, S>],
+) -> Result<(), CombatResource> {
+    attr_alter_system::try_cost_atomic(pool, costs)
+}
+
+/// 校验用的三维、护盾及其上下限, 均为只读借用
+pub struct CombatValidateAttrs<'a> {
+    pub health: &'a Health,
+    pub health_upper: &'a HealthUpper,
+    pub health_lower: &'a HealthLower,
+    pub stamina: &'a Stamina,
+    pub stamina_upper: &'a StaminaUpper,
+    pub magicka: &'a Magicka,
+    pub magicka_upper: &'a MagickaUpper,
+    pub shield_sub: &'a ShieldSubstitute,
+    pub shield_sub_upper: &'a ShieldSubstituteUpper,
+    pub shield_def: &'a ShieldDefence,
+    pub shield_def_upper: &'a ShieldDefenceUpper,
+    pub shield_arc: &'a ShieldArcane,
+    pub shield_arc_upper: &'a ShieldArcaneUpper,
+}
+
+/// 校验三维与各层护盾在上下限内, 在提交中间态后调用(如浸泡测试每帧末尾)
+pub fn validate_resources(validator: &mut AttrValidator, attrs: &CombatValidateAttrs) {
+    validator
+        .check_bounded(
+            CombatResource::Health,
+            &attrs.health.0,
+            &attrs.health_lower.0,
+            &attrs.health_upper.0,
+        )
+        .check_bounded(
+            CombatResource::Stamina,
+            &attrs.stamina.0,
+            COST_FLOOR,
+            &attrs.stamina_upper.0,
+        )
+        .check_bounded(
+            CombatResource::Magicka,
+            &attrs.magicka.0,
+            COST_FLOOR,
+            &attrs.magicka_upper.0,
+        )
+        .check_bounded(
+            SurvivalAttrLayer::ShieldSubstitute,
+            &attrs.shield_sub.0,
+            SHIELD_FLOOR,
+            &attrs.shield_sub_upper.0,
+        )
+        .check_bounded(
+            SurvivalAttrLayer::ShieldDefence,
+            &attrs.shield_def.0,
+            SHIELD_FLOOR,
+            &attrs.shield_def_upper.0,
+        )
+        .check_bounded(
+            SurvivalAttrLayer::ShieldArcane,
+            &attrs.shield_arc.0,
+            SHIELD_FLOOR,
+            &attrs.shield_arc_upper.0,
+        );
+}
+
+/// 校验生存与能量属性的层级拓扑, 层级由模组数据扩展后调用
+pub fn validate_combat_layers(validator: &mut AttrValidator) {
+    validator
+        .check_layers(SurvivalAttrLayer::iter())
+        .check_layers(EnergyAttrLayer::iter());
+}
+
 /// 削韧: 削减平衡, 返回实际生效值
 ///
 /// 由于设计比较简单，无需通过 buffer ，直接生效
@@ -150,7 +462,195 @@ pub fn cut_stamina<S: FixedName>(
     stamina_upper: &StaminaUpper,
     eff: AttrAlterEff<S>,
 ) {
+    cut_stamina_evented(stamina, stamina_upper, eff);
+}
+
+/// 同 [`cut_stamina`] ，返回打断 [`StaminaRegen`] 的事件
+///
+/// 削减为 [`RegenEvent::Damaged`] ，恢复为 [`RegenEvent::Gained`]
+///
+/// [`StaminaRegen`]: crate::common_impl::combats::combat_units::StaminaRegen
+pub fn cut_stamina_evented<S: FixedName>(
+    stamina: &mut Stamina,
+    stamina_upper: &StaminaUpper,
+    eff: AttrAlterEff<S>,
+) -> Option<RegenEvent> {
     let bounded_attr = &mut stamina.0;
     let abs_val = eff.calc_alter_val(bounded_attr, &stamina_upper.0);
-    bounded_attr.apply_eff(abs_val)
+    bounded_attr.apply_eff(abs_val);
+    if abs_val < -FLOAT_DEAD_ZONE {
+        Some(RegenEvent::Damaged)
+    } else if abs_val > FLOAT_DEAD_ZONE {
+        Some(RegenEvent::Gained)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::{timers::tiny_timer::Tickable, tiny_tags::EmptyTags, unify_types::time_type},
+        eff_attr::{
+            bounded_attr_stacks::StackConsumeOrder,
+            curve_tables::{CurveExtrap, CurveInterp, CurveTable},
+            validations::AttrViolation,
+        },
+    };
+
+    use super::*;
+
+    /// 按等级取得所有配置项，任一曲线缺失（如拼写错误）时失败
+    #[test]
+    fn test_three_bars_config_from_curves() {
+        let table = CurveTable::<String>::from_csv(
+            "level,1,10
+health_base,100,280
+health_scale,1,1
+magicka_base,50,50
+magicka_scale,1,2
+stamina_max,100,100
+energy_l0,100,100
+energy_l1,200,200
+energy_l2,300,300
+",
+            CurveInterp::Linear,
+            CurveExtrap::Clamp,
+        )
+        .unwrap();
+        let name = |s: &str| s.to_string();
+        let mut names = ThreeBarsCurveNames {
+            health_base: name("health_base"),
+            health_scale: name("health_scale"),
+            magicka_base: name("magicka_base"),
+            magicka_scale: name("magicka_scale"),
+            stamina_max: name("stamina_max"),
+            magicka_energy_level: [name("energy_l0"), name("energy_l1"), name("energy_l2")],
+        };
+
+        let config = ThreeBarsConfig::from_level_curves(&table, &names, 5.0).unwrap();
+        assert_eq!(config.health_base, 180.0);
+        assert_eq!(
+            config.magicka_energy_level.get_levels(),
+            [100.0, 200.0, 300.0]
+        );
+
+        names.magicka_energy_level[2] = name("energy_l3");
+        assert!(ThreeBarsConfig::from_level_curves(&table, &names, 5.0).is_none());
+    }
+
+    /// 只读借用即可校验，护盾超出上限同样被报告
+    #[test]
+    fn test_validate_resources_with_shields() {
+        let bar = |v: f64| BoundedAttr::new(v);
+        let upper = |v: f64| BoundAttr::new(v);
+        let (health, stamina, magicka) =
+            (Health(bar(100.0)), Stamina(bar(50.0)), Magicka(bar(0.0)));
+        let (shield_sub, shield_def, shield_arc) = (
+            ShieldSubstitute(bar(0.0)),
+            ShieldDefence(bar(30.0)),
+            ShieldArcane(bar(0.0)),
+        );
+        let attrs = CombatValidateAttrs {
+            health: &health,
+            health_upper: &HealthUpper(upper(100.0)),
+            health_lower: &HealthLower(upper(0.0)),
+            stamina: &stamina,
+            stamina_upper: &StaminaUpper(upper(50.0)),
+            magicka: &magicka,
+            magicka_upper: &MagickaUpper(upper(100.0)),
+            shield_sub: &shield_sub,
+            shield_sub_upper: &ShieldSubstituteUpper(upper(0.0)),
+            shield_def: &shield_def,
+            shield_def_upper: &ShieldDefenceUpper(upper(20.0)),
+            shield_arc: &shield_arc,
+            shield_arc_upper: &ShieldArcaneUpper(upper(0.0)),
+        };
+
+        let mut validator = AttrValidator::new();
+        validate_resources(&mut validator, &attrs);
+        let violations = validator.into_violations();
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            AttrViolation::OutOfBounds { key, value: 30.0, .. } if key == "ShieldDefence"
+        ));
+    }
+
+    /// 按策略修正三维，启用明细的护盾以明细为准
+    #[test]
+    fn test_refresh_combat_bounds() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs: [UpsertContainer<BoundAttrEff<&'static str, StaticTimer>>; 6] =
+            Default::default();
+        effs[0].upsert_replace(BoundAttrEff::new(
+            BoundAttrEffType::BasicAdd,
+            Effect::new("ring", "vigor", 50.0),
+            StaticTimer::inf(),
+        ));
+        let mut stacks = SurvivalShieldStacks::default();
+        stacks.shield_def = stacks
+            .shield_def
+            .with_order(StackConsumeOrder::WeakestFirst);
+        load_shield_with_stack(
+            &mut stacks.shield_def,
+            &BoundAttr::new(0.0),
+            BoundAttrEffType::BasicAdd,
+            Effect::new("alice", "barrier", 30.0),
+            StaticTimer::new(&timeline, time_type::unit::<2>()),
+        );
+
+        let mut values: [BoundedAttr; 6] = [50.0, 80.0, 40.0, 0.0, 0.0, 0.0].map(BoundedAttr::new);
+        let mut uppers: [BoundAttr; 6] = [100.0, 100.0, 100.0, 0.0, 0.0, 0.0].map(BoundAttr::new);
+        let health_lower = HealthLower(BoundAttr::new(0.0));
+        let policies = ResourceReboundPolicies {
+            health: ReboundPolicy::KeepRatio,
+            ..Default::default()
+        };
+        let mut refresh = |timeline: &StaticTimeline| {
+            let [health, stamina, magicka, shield_sub, shield_def, shield_arc] = effs
+                .iter_mut()
+                .zip(values.iter_mut().zip(uppers.iter_mut()))
+                .map(|(upper_effs, (value, upper))| ReboundPair {
+                    upper,
+                    upper_effs,
+                    value,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .ok()
+                .unwrap();
+            let attrs = CombatReboundAttrs {
+                health,
+                health_lower: &health_lower,
+                stamina,
+                magicka,
+                shield_sub,
+                shield_def,
+                shield_arc,
+            };
+            try_refresh_combat_bounds(
+                attrs,
+                &EmptyTags::default(),
+                &policies,
+                Some((&mut stacks, timeline)),
+            )
+        };
+
+        let events = refresh(&timeline);
+        let health = events.health.unwrap();
+        assert_eq!((health.old_value, health.new_value), (50.0, 75.0));
+        assert!(events.stamina.is_none() && events.magicka.is_none());
+        let shield_def = events.shield_def.unwrap();
+        assert_eq!((shield_def.old_value, shield_def.new_value), (0.0, 30.0));
+        assert!(events.shield_sub.is_none() && events.shield_expired.is_empty());
+
+        timeline.0.tick(time_type::unit::<3>());
+        let events = refresh(&timeline);
+        assert!(events.health.is_none());
+        let shield_def = events.shield_def.unwrap();
+        assert_eq!((shield_def.old_value, shield_def.new_value), (30.0, 0.0));
+        assert_eq!(events.shield_expired.len(), 1);
+        assert_eq!(events.shield_expired[0].0, SurvivalAttrLayer::ShieldDefence);
+    }
 }
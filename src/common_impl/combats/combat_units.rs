@@ -12,22 +12,36 @@
 //! - 系统内部定义属性条满时正常（为与 [`crate::base_lib::eff_attr::effects::EffectMean`] 一致）
 //! - 界面视觉上可翻转成空时正常
 //! - 类别
-//!   - Entropy 熵（炎热寒冷）
-//!   - Electric 电势能
+//!   - Entropy 熵（炎热寒冷） [`Entropy`]
+//!   - Electric 电势能 [`Electric`]
+//! - 积攒、阈值与元素反应见 [`super::elements`]
 //!
-//! todo 后续系统性实现
-//! - 实现角色周期性效果
-//! - 生命值以最大值的百分比进行恢复
-//! - 平衡以固定值进行恢复 受击后延迟一段时间继续恢复
-//! - 能量以固定值削减 增长后延迟一段时间继续削减
+//! 角色周期性效果见 [`super::periodic_effs`]
+//!
+//! 资源的自然恢复与衰减见 [`BoundedAttrRegen`]
+//! - 生命值以最大值的百分比进行恢复 [`HealthRegen`]
+//! - 平衡以固定值进行恢复 受击后延迟一段时间继续恢复 [`StaminaRegen`]
+//! - 能量以固定值削减 增长后延迟一段时间继续削减 [`MagickaDecay`]
+//!
+//! 资源上下限变化（如护盾到期）时当前值的修正见 [`ResourceReboundPolicies`]
+//!
+//! 同一层护盾的多个来源见 [`SurvivalShieldStacks`]
+//!
+//! 资源条的界面显示值（如滞后消退的血条）见 [`HealthDisplay`] [`StaminaDisplay`] [`MagickaDisplay`]
 
 use strum_macros::EnumIter;
 
 use crate::base_lib::{
     cores::{timers::static_timer::StaticTimer, unify_types::FixedName},
     eff_attr::{
-        attr_layers::AttrLayerType, bound_attr_effs::BoundAttrEff, bound_attrs::BoundAttr,
-        bounded_attrs::BoundedAttr, upsert_container::UpsertContainer,
+        attr_layers::AttrLayerType,
+        bound_attr_effs::BoundAttrEff,
+        bound_attrs::BoundAttr,
+        bounded_attr_displays::BoundedAttrDisplay,
+        bounded_attr_regens::BoundedAttrRegen,
+        bounded_attr_stacks::BoundedAttrStack,
+        bounded_attrs::{BoundedAttr, ReboundPolicy},
+        upsert_container::UpsertContainer,
     },
 };
 
@@ -113,6 +127,106 @@ pub struct Magicka(pub BoundedAttr);
 /// 外部能源 环境逸散的自由态能量
 pub struct ExternalEnergy(pub BoundedAttr);
 
+/// 熵积攒槽 被元素系统控制； 满时正常，炎热与寒冷的积攒均使其减少
+pub struct Entropy(pub BoundedAttr);
+
+/// 电势能积攒槽 被元素系统控制； 满时正常，电的积攒使其减少
+pub struct Electric(pub BoundedAttr);
+
+/// 类型别名 护盾的来源明细
+pub type ShieldStack<S> = BoundedAttrStack<S, StaticTimer>;
+
+/// 各层护盾的来源明细，与 [`ShieldSubstitute`] [`ShieldDefence`] [`ShieldArcane`] 一一对应
+///
+/// 启用明细的护盾层以明细为准：装载只添加明细，上限与当前值由明细之和得出，
+/// 受到伤害时由伤害系统按各自的消耗顺序消耗并报告每个来源吸收的量
+///
+/// 见 [`super::combat_unit_systems::load_shield_with_stack`] [`super::combat_unit_systems::sync_shield_with_stack`]
+#[derive(Debug)]
+pub struct SurvivalShieldStacks<S: FixedName> {
+    pub shield_sub: ShieldStack<S>,
+    pub shield_def: ShieldStack<S>,
+    pub shield_arc: ShieldStack<S>,
+}
+
+impl<S: FixedName> Default for SurvivalShieldStacks<S> {
+    fn default() -> Self {
+        Self {
+            shield_sub: ShieldStack::default(),
+            shield_def: ShieldStack::default(),
+            shield_arc: ShieldStack::default(),
+        }
+    }
+}
+
+impl<S: FixedName> SurvivalShieldStacks<S> {
+    /// 生命值没有来源明细
+    pub fn get_stack_mut(&mut self, layer: SurvivalAttrLayer) -> Option<&mut ShieldStack<S>> {
+        match layer {
+            SurvivalAttrLayer::Health => None,
+            SurvivalAttrLayer::ShieldSubstitute => Some(&mut self.shield_sub),
+            SurvivalAttrLayer::ShieldDefence => Some(&mut self.shield_def),
+            SurvivalAttrLayer::ShieldArcane => Some(&mut self.shield_arc),
+        }
+    }
+}
+
+/// 护盾的吸收比例，取值 `[0, 1]` ，未被吸收的部分直接渗透到下一层（如奥术护盾吸收 70% ，30% 渗透到生命）
+///
+/// 生命值作为底层总是完全吸收
+#[derive(Debug, Clone)]
+pub struct SurvivalLayerAbsorbs {
+    pub shield_sub: f64,
+    pub shield_def: f64,
+    pub shield_arc: f64,
+}
+
+impl Default for SurvivalLayerAbsorbs {
+    fn default() -> Self {
+        Self {
+            shield_sub: 1.0,
+            shield_def: 1.0,
+            shield_arc: 1.0,
+        }
+    }
+}
+
+impl SurvivalLayerAbsorbs {
+    pub fn get_ratio(&self, layer: SurvivalAttrLayer) -> f64 {
+        match layer {
+            SurvivalAttrLayer::Health => 1.0,
+            SurvivalAttrLayer::ShieldSubstitute => self.shield_sub,
+            SurvivalAttrLayer::ShieldDefence => self.shield_def,
+            SurvivalAttrLayer::ShieldArcane => self.shield_arc,
+        }
+    }
+}
+
+/// 各资源上下限变化时当前值的处理策略，刷新上限时经
+/// [`super::combat_unit_systems::try_refresh_combat_bounds`] 应用
+///
+/// 默认只钳制：护盾到期时超出的护盾值随之清除
+#[derive(Debug, Clone, Default)]
+pub struct ResourceReboundPolicies {
+    pub shield_sub: ReboundPolicy,
+    pub shield_def: ReboundPolicy,
+    pub shield_arc: ReboundPolicy,
+    pub health: ReboundPolicy,
+    pub stamina: ReboundPolicy,
+    pub magicka: ReboundPolicy,
+}
+
+impl ResourceReboundPolicies {
+    pub fn get_survival_policy(&self, layer: SurvivalAttrLayer) -> ReboundPolicy {
+        match layer {
+            SurvivalAttrLayer::Health => self.health,
+            SurvivalAttrLayer::ShieldSubstitute => self.shield_sub,
+            SurvivalAttrLayer::ShieldDefence => self.shield_def,
+            SurvivalAttrLayer::ShieldArcane => self.shield_arc,
+        }
+    }
+}
+
 // region: 批量定义
 
 pub struct ShieldSubstituteUpper(pub BoundAttr);
@@ -122,6 +236,8 @@ pub struct HealthUpper(pub BoundAttr);
 pub struct StaminaUpper(pub BoundAttr);
 pub struct MagickaUpper(pub BoundAttr);
 pub struct HealthLower(pub BoundAttr);
+pub struct EntropyUpper(pub BoundAttr);
+pub struct ElectricUpper(pub BoundAttr);
 
 /// 类型别名 资源上下限效果的容器
 type BoundAttrEffs<S> = UpsertContainer<BoundAttrEff<S, StaticTimer>>;
@@ -133,9 +249,35 @@ pub struct HealthUpperEffs<S: FixedName>(pub BoundAttrEffs<S>);
 pub struct StaminaUpperEffs<S: FixedName>(pub BoundAttrEffs<S>);
 pub struct MagickaUpperEffs<S: FixedName>(pub BoundAttrEffs<S>);
 pub struct HealthLowerEffs<S: FixedName>(pub BoundAttrEffs<S>);
+pub struct EntropyUpperEffs<S: FixedName>(pub BoundAttrEffs<S>);
+pub struct ElectricUpperEffs<S: FixedName>(pub BoundAttrEffs<S>);
 
 // endregion
 
+/// 生命值自然恢复 一般以最大值的百分比恢复
+pub struct HealthRegen(pub BoundedAttrRegen<StaticTimer>);
+
+/// 平衡自然恢复 一般受击后重置延迟
+pub struct StaminaRegen(pub BoundedAttrRegen<StaticTimer>);
+
+/// 能量自然衰减 一般获得能量后重置延迟
+pub struct MagickaDecay(pub BoundedAttrRegen<StaticTimer>);
+
+/// 熵积攒的自然消退 以固定值向上界恢复 一般受到积攒后重置延迟
+pub struct EntropyDecay(pub BoundedAttrRegen<StaticTimer>);
+
+/// 电势能积攒的自然消退
+pub struct ElectricDecay(pub BoundedAttrRegen<StaticTimer>);
+
+/// 血量的界面显示值 一般受伤时延迟消退、治疗时立即跟上
+pub struct HealthDisplay(pub BoundedAttrDisplay);
+
+/// 平衡的界面显示值
+pub struct StaminaDisplay(pub BoundedAttrDisplay);
+
+/// 能量的界面显示值
+pub struct MagickaDisplay(pub BoundedAttrDisplay);
+
 #[cfg(test)]
 mod tests {
     use strum::IntoEnumIterator;
@@ -2,11 +2,24 @@ use strum_macros::EnumIter;
 
 use crate::{
     base_lib::{
-        cores::unify_types::FixedName,
+        cores::{
+            timers::{
+                charge_counters::ChargeEvent,
+                static_timer::{StaticTimeline, StaticTimer},
+            },
+            unify_types::FixedName,
+        },
         eff_attr::{
             attr_layers::AttrLayerEffTarget,
             bounded_attr_effs::{AttrAlterEff, AttrAlterEffType},
+            bounded_attr_regens::RegenEvent,
+            bounded_attr_stacks::StackAbsorption,
+            effect_specs::{EffectSpec, LevelCurveSource, SpecMagnitudeError},
             effects::Effect,
+            interceptors::{
+                EffInterceptors, InterceptCharge, InterceptHits, InterceptPhase,
+                consume_intercept_charges, intercept_eff_value_recorded,
+            },
         },
     },
     common_impl::combats::{
@@ -14,6 +27,7 @@ use crate::{
         combat_inherents::{Belief, Strength},
         combat_units::{
             Health, Magicka, ShieldArcane, ShieldDefence, ShieldSubstitute, SurvivalAttrLayer,
+            SurvivalShieldStacks,
         },
     },
 };
@@ -22,6 +36,74 @@ use crate::{
 #[derive(Debug)]
 pub struct SurvivalEffBuffer<S: FixedName>(Vec<SurvivalAttrEff<S>>);
 
+/// 类型别名 作用于生存类效果的拦截器容器（如“受到的治疗 +10%”），按 [`SurvivalEffTargets`] 匹配目标
+pub type SurvivalInterceptors<S, Timer> = EffInterceptors<S, SurvivalEffTargets, Timer>;
+
+/// 伤害管线的可选扩展，默认均不启用，见 [`damage_system::merge_damages_with`] [`damage_system::apply_damages_with`]
+pub struct DamageHooks<'a, S: FixedName, Timer = StaticTimer> {
+    /// 拦截器，不启用时效果值原样作用
+    pub interceptors: Option<&'a mut SurvivalInterceptors<S, Timer>>,
+    /// 护盾的来源明细及其计时所依赖的时间线，不启用时不记录护盾的吸收来源
+    pub shield_stacks: Option<(&'a mut SurvivalShieldStacks<S>, &'a StaticTimeline)>,
+}
+
+impl<S: FixedName, Timer> Default for DamageHooks<'_, S, Timer> {
+    fn default() -> Self {
+        Self {
+            interceptors: None,
+            shield_stacks: None,
+        }
+    }
+}
+
+impl<'a, S: FixedName, Timer> DamageHooks<'a, S, Timer> {
+    pub fn with_interceptors(
+        mut self,
+        interceptors: &'a mut SurvivalInterceptors<S, Timer>,
+    ) -> Self {
+        self.interceptors = Some(interceptors);
+        self
+    }
+
+    pub fn with_shield_stacks(
+        mut self,
+        shield_stacks: &'a mut SurvivalShieldStacks<S>,
+        timeline: &'a StaticTimeline,
+    ) -> Self {
+        self.shield_stacks = Some((shield_stacks, timeline));
+        self
+    }
+}
+
+impl<S: FixedName, Timer: InterceptCharge> DamageHooks<'_, S, Timer> {
+    /// 未启用拦截器时返回效果值本身
+    fn intercept(
+        &self,
+        phase: InterceptPhase,
+        target: SurvivalEffTargets,
+        eff: &Effect<S>,
+        hits: &mut InterceptHits<S>,
+    ) -> f64 {
+        match self.interceptors.as_deref() {
+            Some(interceptors) => intercept_eff_value_recorded(
+                interceptors,
+                phase,
+                target,
+                eff,
+                ChargeEvent::OnDamageTaken,
+                hits,
+            ),
+            None => eff.get_effect_value(),
+        }
+    }
+
+    fn consume_charges(&mut self, hits: &InterceptHits<S>) {
+        if let Some(interceptors) = self.interceptors.as_deref_mut() {
+            consume_intercept_charges(interceptors, hits, ChargeEvent::OnDamageTaken);
+        }
+    }
+}
+
 /// 伤害信息，表示每次伤害造成的影响
 ///
 /// 这里不自动判断血量是否为零，因为还在 pending 阶段，管线后续可能还会修改
@@ -29,6 +111,61 @@ pub struct SurvivalEffBuffer<S: FixedName>(Vec<SurvivalAttrEff<S>>);
 pub struct DamageInfo<S: FixedName> {
     /// 对生命造成的最大伤害的效果，用于统计死因
     pub max_hurt_heal_eff: Option<Effect<S>>,
+    /// 各层护盾每个来源吸收的量，按结算顺序
+    pub shield_absorptions: Vec<(SurvivalAttrLayer, StackAbsorption<S>)>,
+    /// 本次实际造成伤害的来源（去重），被拦截器完全抵消或未削减任何一层的伤害不算命中
+    pub hit_from: Vec<S>,
+}
+
+impl<S: FixedName> DamageInfo<S> {
+    /// 受击方的次数事件，有来源命中时抛出 [`ChargeEvent::OnDamageTaken`]
+    pub fn get_taken_event(&self) -> Option<ChargeEvent> {
+        (!self.hit_from.is_empty()).then_some(ChargeEvent::OnDamageTaken)
+    }
+
+    /// 打断受击方恢复的事件，有来源命中时抛出 [`RegenEvent::Damaged`] ，如重置 [`HealthRegen`] 的延迟
+    ///
+    /// [`HealthRegen`]: crate::common_impl::combats::combat_units::HealthRegen
+    pub fn get_regen_event(&self) -> Option<RegenEvent> {
+        (!self.hit_from.is_empty()).then_some(RegenEvent::Damaged)
+    }
+
+    /// 攻击方的次数事件，每个命中的来源抛出一次 [`ChargeEvent::OnHit`]
+    pub fn iter_hit_events(&self) -> impl Iterator<Item = (&S, ChargeEvent)> {
+        self.hit_from.iter().map(|from| (from, ChargeEvent::OnHit))
+    }
+}
+
+/// 效果对各层属性的穿透比例，取值 `[0, 1]` ，如破甲攻击忽略 50% 的防护护盾
+///
+/// 被忽略的部分直接传递到下一层，见 [`attr_layer_system::cascade_layer_val_penetrated`]
+///
+/// [`attr_layer_system::cascade_layer_val_penetrated`]: crate::base_lib::eff_attr::attr_layers::attr_layer_system::cascade_layer_val_penetrated
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SurvivalPenetration(Vec<(SurvivalAttrLayer, f64)>);
+
+impl SurvivalPenetration {
+    /// 同层覆盖
+    pub fn with(mut self, layer: SurvivalAttrLayer, ratio: f64) -> Self {
+        let ratio = ratio.clamp(0.0, 1.0);
+        if let Some((_, old)) = self.0.iter_mut().find(|(l, _)| *l == layer) {
+            *old = ratio;
+        } else {
+            self.0.push((layer, ratio));
+        }
+        self
+    }
+
+    pub fn get_ratio(&self, layer: SurvivalAttrLayer) -> f64 {
+        self.0
+            .iter()
+            .find(|(l, _)| *l == layer)
+            .map_or(0.0, |(_, ratio)| *ratio)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 /// 生存类效果（伤害、治疗、护盾）
@@ -39,6 +176,8 @@ pub struct SurvivalAttrEff<S: FixedName> {
     /// 伤害生效方式（绝对值或是百分比）
     alter_type: AttrAlterEffType,
     eff: Effect<S>,
+    /// 对各层的穿透，默认无穿透
+    penetration: SurvivalPenetration,
 }
 
 impl<S: FixedName> SurvivalAttrEff<S> {
@@ -55,6 +194,7 @@ impl<S: FixedName> SurvivalAttrEff<S> {
             target_type,
             alter_type,
             eff,
+            penetration: SurvivalPenetration::default(),
         }
     }
 
@@ -63,8 +203,44 @@ impl<S: FixedName> SurvivalAttrEff<S> {
             target_type,
             alter_type: eff.get_type(),
             eff: eff.take_eff(),
+            penetration: SurvivalPenetration::default(),
         }
     }
+
+    /// 由效果规格计算效果值后构造
+    pub fn from_spec(
+        target_type: SurvivalEffTargets,
+        alter_type: AttrAlterEffType,
+        spec: &EffectSpec<S>,
+        curves: &impl LevelCurveSource<S>,
+    ) -> Result<Self, SpecMagnitudeError<S>> {
+        Ok(Self::new(target_type, alter_type, spec.to_effect(curves)?))
+    }
+
+    pub fn with_penetration(mut self, penetration: SurvivalPenetration) -> Self {
+        self.penetration = penetration;
+        self
+    }
+
+    pub fn get_penetration(&self) -> &SurvivalPenetration {
+        &self.penetration
+    }
+
+    pub fn get_target_type(&self) -> SurvivalEffTargets {
+        self.target_type
+    }
+
+    pub fn get_alter_type(&self) -> AttrAlterEffType {
+        self.alter_type
+    }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    pub fn get_effect_mut(&mut self) -> &mut Effect<S> {
+        &mut self.eff
+    }
 }
 
 impl<S: FixedName> Default for SurvivalEffBuffer<S> {
@@ -96,6 +272,11 @@ impl<S: FixedName> SurvivalEffBuffer<S> {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// 只读遍历缓冲内的伤害效果
+    pub fn iter(&self) -> impl Iterator<Item = &SurvivalAttrEff<S>> {
+        self.0.iter()
+    }
 }
 
 /// 生存效果生效目标
@@ -169,6 +350,8 @@ impl<S: FixedName> Default for DamageInfo<S> {
     fn default() -> Self {
         Self {
             max_hurt_heal_eff: None,
+            shield_absorptions: Vec::new(),
+            hit_from: Vec::new(),
         }
     }
 }
@@ -227,12 +410,16 @@ impl<S: FixedName> Default for DamageInfo<S> {
 pub mod damage_system {
     use crate::{
         base_lib::{
-            cores::unify_types::FLOAT_DEAD_ZONE, eff_attr::attr_layers::AttrLayerEffTargetIter,
+            cores::{timers::static_timer::StaticTimer, unify_types::FLOAT_DEAD_ZONE},
+            eff_attr::{
+                attr_layers::{AttrLayerEffTargetIter, AttrLayerPool, attr_layer_system},
+                bounded_attrs::BoundedAttr,
+            },
         },
         common_impl::combats::{
             combat_units::{
                 HealthLower, HealthUpper, ShieldArcaneUpper, ShieldDefenceUpper,
-                ShieldSubstituteUpper, SurvivalAttrLayer,
+                ShieldSubstituteUpper, SurvivalAttrLayer, SurvivalLayerAbsorbs,
             },
             energies::MagickaEnergyLevel,
         },
@@ -252,6 +439,41 @@ pub mod damage_system {
         phy_imp: Option<Effect<S>>,
         phy_she: Option<Effect<S>>,
         mgk_arc: Option<Effect<S>>,
+        /// 同类效果合并后的穿透
+        penetrations: Vec<(SurvivalEffTargets, MergedPenetration)>,
+        /// 合并前的单个效果（已经过原始值阶段的拦截），缩放后阶段的拦截器按单个效果匹配
+        parts: Vec<(SurvivalEffTargets, Effect<S>)>,
+    }
+
+    /// 同类效果的穿透按效果值的绝对值加权平均，与合并顺序无关
+    #[derive(Debug, Default)]
+    struct MergedPenetration {
+        abs_sum: f64,
+        weighted: Vec<(SurvivalAttrLayer, f64)>,
+    }
+
+    impl MergedPenetration {
+        fn add(&mut self, eff_val: f64, penetration: &SurvivalPenetration) {
+            let weight = eff_val.abs();
+            self.abs_sum += weight;
+            for (layer, ratio) in penetration.0.iter() {
+                if let Some((_, sum)) = self.weighted.iter_mut().find(|(l, _)| l == layer) {
+                    *sum += weight * ratio;
+                } else {
+                    self.weighted.push((*layer, weight * ratio));
+                }
+            }
+        }
+
+        fn get_ratio(&self, layer: SurvivalAttrLayer) -> f64 {
+            if self.abs_sum < FLOAT_DEAD_ZONE {
+                return 0.0;
+            }
+            self.weighted
+                .iter()
+                .find(|(l, _)| *l == layer)
+                .map_or(0.0, |(_, sum)| sum / self.abs_sum)
+        }
     }
 
     impl<S: FixedName> Default for MergedSurvivalEffs<S> {
@@ -264,6 +486,8 @@ pub mod damage_system {
                 phy_imp: None,
                 phy_she: None,
                 mgk_arc: None,
+                penetrations: Vec::new(),
+                parts: Vec::new(),
             }
         }
     }
@@ -308,6 +532,25 @@ pub mod damage_system {
     pub fn merge_damages<S: FixedName>(
         survival_eff_buffer: &mut SurvivalEffBuffer<S>,
         damage_target_attrs: DamageTargetAttrs,
+    ) -> MergedSurvivalEffs<S> {
+        merge_damages_with(
+            survival_eff_buffer,
+            damage_target_attrs,
+            &mut DamageHooks::<S, StaticTimer>::default(),
+        )
+    }
+
+    /// 同 [`merge_damages`] ，附带可选扩展 [`DamageHooks`]
+    ///
+    /// 每个效果折算为绝对值后、合并前，经过 [`InterceptPhase::Raw`] 阶段的拦截器；
+    /// 按次数生效的拦截器（如“抵挡下一次攻击”）在本次合并中命中即消耗一次 [`ChargeEvent::OnDamageTaken`]，
+    /// 与拦截的效果个数无关；治疗同样会消耗，需用 [`EffInterceptor::with_nature`] 收窄
+    ///
+    /// [`EffInterceptor::with_nature`]: crate::base_lib::eff_attr::interceptors::EffInterceptor::with_nature
+    pub fn merge_damages_with<S: FixedName, Timer: InterceptCharge>(
+        survival_eff_buffer: &mut SurvivalEffBuffer<S>,
+        damage_target_attrs: DamageTargetAttrs,
+        hooks: &mut DamageHooks<S, Timer>,
     ) -> MergedSurvivalEffs<S> {
         let DamageTargetAttrs {
             target_heal,
@@ -321,6 +564,7 @@ pub mod damage_system {
         } = damage_target_attrs;
 
         let mut merged_svv_effs = MergedSurvivalEffs::<S>::default();
+        let mut hits = InterceptHits::new();
 
         // get the ownership
         for dmg_eff in survival_eff_buffer.0.drain(0..) {
@@ -328,6 +572,7 @@ pub mod damage_system {
                 target_type,
                 alter_type,
                 eff,
+                penetration,
             } = dmg_eff;
 
             // 根据伤害类型找到百分比参照物
@@ -345,8 +590,27 @@ pub mod damage_system {
             };
 
             // 根据伤害算法计算伤害绝对值
-            let abs_eff_val =
-                alter_type.calc_alter_val(eff.get_effect_value(), base_bounded, base_bound);
+            let mut eff = eff;
+            eff.set_effect_value(alter_type.calc_alter_val(
+                eff.get_effect_value(),
+                base_bounded,
+                base_bound,
+            ));
+            // 拦截器按单个效果生效（如“受到的火焰伤害 -30%”），而非合并后的效果
+            let abs_eff_val = hooks.intercept(InterceptPhase::Raw, target_type, &eff, &mut hits);
+
+            // 同类的所有效果都参与穿透的加权，无穿透的效果拉低平均值
+            if let Some((_, merged_pen)) = merged_svv_effs
+                .penetrations
+                .iter_mut()
+                .find(|(t, _)| *t == target_type)
+            {
+                merged_pen.add(abs_eff_val, &penetration);
+            } else {
+                let mut merged_pen = MergedPenetration::default();
+                merged_pen.add(abs_eff_val, &penetration);
+                merged_svv_effs.penetrations.push((target_type, merged_pen));
+            }
 
             // 根据伤害类型找到聚合对象
             let merged_dmg = match target_type {
@@ -360,14 +624,15 @@ pub mod damage_system {
             };
 
             // 累加绝对值
+            eff.set_effect_value(abs_eff_val);
             if let Some(merged_dmg) = merged_dmg {
                 merged_dmg.set_effect_value(merged_dmg.get_effect_value() + abs_eff_val);
             } else {
-                let mut eff = eff;
-                eff.set_effect_value(abs_eff_val);
-                *merged_dmg = Some(eff);
+                *merged_dmg = Some(eff.clone());
             }
+            merged_svv_effs.parts.push((target_type, eff));
         }
+        hooks.consume_charges(&hits);
 
         merged_svv_effs
     }
@@ -391,27 +656,75 @@ pub mod damage_system {
         pub target_shield_sub_upper: &'a ShieldSubstituteUpper,
         pub target_shield_def_upper: &'a ShieldDefenceUpper,
         pub target_shield_arc_upper: &'a ShieldArcaneUpper,
+        pub target_layer_absorbs: &'a SurvivalLayerAbsorbs,
+    }
+
+    impl AttrLayerPool<SurvivalAttrLayer> for DamageTargetMutAttrs<'_> {
+        fn get_absorb_ratio(&self, layer: SurvivalAttrLayer) -> f64 {
+            self.target_layer_absorbs.get_ratio(layer)
+        }
+
+        fn get_layer_attr(&mut self, layer: SurvivalAttrLayer) -> (&mut BoundedAttr, f64, f64) {
+            match layer {
+                SurvivalAttrLayer::Health => (
+                    &mut self.target_heal.0,
+                    self.target_heal_lower.0.get_current(),
+                    self.target_heal_upper.0.get_current(),
+                ),
+                SurvivalAttrLayer::ShieldSubstitute => (
+                    &mut self.target_shield_sub.0,
+                    BOUNDED_ATTR_LOWER,
+                    self.target_shield_sub_upper.0.get_current(),
+                ),
+                SurvivalAttrLayer::ShieldDefence => (
+                    &mut self.target_shield_def.0,
+                    BOUNDED_ATTR_LOWER,
+                    self.target_shield_def_upper.0.get_current(),
+                ),
+                SurvivalAttrLayer::ShieldArcane => (
+                    &mut self.target_shield_arc.0,
+                    BOUNDED_ATTR_LOWER,
+                    self.target_shield_arc_upper.0.get_current(),
+                ),
+            }
+        }
     }
 
     /// 对合并后的伤害效果计算伤害
+    ///
+    /// 按 [`MergedSurvivalEffs::into_slice`] 的顺序逐类结算（见 `docs/adr/0001`），
+    /// 护盾的吸收比例与效果的穿透只改变每层的作用量，不改变该顺序
     pub fn apply_damages<S: FixedName>(
         merged_svv_effs: MergedSurvivalEffs<S>,
         damage_calc_attrs: DamageCalcAttrs,
         damage_target_attrs: DamageTargetMutAttrs,
     ) -> DamageInfo<S> {
-        let DamageTargetMutAttrs {
-            target_heal,
-            target_shield_sub,
-            target_shield_def,
-            target_shield_arc,
-            target_heal_upper,
-            target_heal_lower,
-            target_shield_sub_upper,
-            target_shield_def_upper,
-            target_shield_arc_upper,
-        } = damage_target_attrs;
+        apply_damages_with(
+            merged_svv_effs,
+            damage_calc_attrs,
+            damage_target_attrs,
+            &mut DamageHooks::<S, StaticTimer>::default(),
+        )
+    }
 
+    /// 同 [`apply_damages`] ，附带可选扩展 [`DamageHooks`]
+    ///
+    /// 伤害缩放后、作用到属性前，同类中的每个效果分别经过 [`InterceptPhase::Scaled`] 阶段的拦截器
+    /// 再累加，按名称匹配的结果与效果的推入顺序无关；次数拦截器在本次结算中只消耗一次
+    ///
+    /// 命中来源由实际作用的伤害得出：同类的合并伤害削减了任意一层时，其中拦截后仍为伤害的效果来源算作命中
+    ///
+    /// 启用护盾明细时，护盾层减少的量按该层明细的消耗顺序分摊到各个来源，记录于 [`DamageInfo::shield_absorptions`]
+    pub fn apply_damages_with<S: FixedName, Timer: InterceptCharge>(
+        mut merged_svv_effs: MergedSurvivalEffs<S>,
+        damage_calc_attrs: DamageCalcAttrs,
+        mut damage_target_attrs: DamageTargetMutAttrs,
+        hooks: &mut DamageHooks<S, Timer>,
+    ) -> DamageInfo<S> {
         let mut dmg_info: DamageInfo<S> = DamageInfo::default();
+        let mut hits = InterceptHits::new();
+        let penetrations = std::mem::take(&mut merged_svv_effs.penetrations);
+        let parts = std::mem::take(&mut merged_svv_effs.parts);
         let svv_effs = merged_svv_effs.into_slice();
         for (svv_eff_target, dmg_eff) in svv_effs {
             if let Some(mut dmg_eff) = dmg_eff {
@@ -419,46 +732,59 @@ pub mod damage_system {
                 let dmg_scale =
                     damage_system::calc_damage_scale(svv_eff_target, &damage_calc_attrs);
 
-                let mut real_dmg = dmg_scale * dmg_eff.get_effect_value();
+                let mut real_dmg = 0.0;
+                let mut hurt_from = Vec::new();
+                for (_, part) in parts.iter().filter(|(t, _)| *t == svv_eff_target) {
+                    let mut part = part.clone();
+                    part.set_effect_value(dmg_scale * part.get_effect_value());
+                    let part_val =
+                        hooks.intercept(InterceptPhase::Scaled, svv_eff_target, &part, &mut hits);
+                    if part_val < -FLOAT_DEAD_ZONE {
+                        hurt_from.push(part.get_from_name().clone());
+                    }
+                    real_dmg += part_val;
+                }
                 dmg_eff.set_effect_value(real_dmg); // 更新为实际伤害
+                let merged_pen = penetrations
+                    .iter()
+                    .find(|(t, _)| *t == svv_eff_target)
+                    .map(|(_, merged_pen)| merged_pen);
                 let mut is_hurt_heal = false;
-                let targets_iter = AttrLayerEffTargetIter::from(svv_eff_target);
-                for svv_layer in targets_iter {
-                    let (attr, upper, lower) = match svv_layer {
-                        SurvivalAttrLayer::Health => (
-                            &mut target_heal.0,
-                            target_heal_upper.0.get_current(),
-                            target_heal_lower.0.get_current(),
-                        ),
-                        SurvivalAttrLayer::ShieldSubstitute => (
-                            &mut target_shield_sub.0,
-                            target_shield_sub_upper.0.get_current(),
-                            BOUNDED_ATTR_LOWER,
-                        ),
-                        SurvivalAttrLayer::ShieldDefence => (
-                            &mut target_shield_def.0,
-                            target_shield_def_upper.0.get_current(),
-                            BOUNDED_ATTR_LOWER,
-                        ),
-                        SurvivalAttrLayer::ShieldArcane => (
-                            &mut target_shield_arc.0,
-                            target_shield_arc_upper.0.get_current(),
-                            BOUNDED_ATTR_LOWER,
-                        ),
-                    };
-                    let old_val = attr.get_pending_value();
-                    attr.apply_eff(real_dmg);
-                    attr.clamp_by(lower, upper);
-                    let diff_val = attr.get_pending_value() - old_val;
-                    real_dmg -= diff_val;
-
-                    // 实际伤害到了生命值
-                    if matches!(svv_layer, SurvivalAttrLayer::Health) && diff_val < -FLOAT_DEAD_ZONE
-                    {
-                        is_hurt_heal = true;
+                let mut is_hurt = false;
+                let shield_absorptions = &mut dmg_info.shield_absorptions;
+                let shield_stacks = &mut hooks.shield_stacks;
+                attr_layer_system::cascade_layer_val_penetrated(
+                    &mut damage_target_attrs,
+                    AttrLayerEffTargetIter::from(svv_eff_target),
+                    real_dmg,
+                    |svv_layer| merged_pen.map_or(0.0, |p| p.get_ratio(svv_layer)),
+                    |svv_layer, diff_val| {
+                        is_hurt |= diff_val < -FLOAT_DEAD_ZONE;
+                        // 实际伤害到了生命值
+                        if matches!(svv_layer, SurvivalAttrLayer::Health)
+                            && diff_val < -FLOAT_DEAD_ZONE
+                        {
+                            is_hurt_heal = true;
+                        }
+                        // 护盾被削减，分摊到来源
+                        if diff_val < -FLOAT_DEAD_ZONE
+                            && let Some((stacks, timeline)) = shield_stacks.as_mut()
+                            && let Some(stack) = stacks.get_stack_mut(svv_layer)
+                        {
+                            let absorptions = stack.consume(-diff_val, timeline);
+                            shield_absorptions
+                                .extend(absorptions.into_iter().map(|a| (svv_layer, a)));
+                        }
+                    },
+                );
+
+                if is_hurt {
+                    for from in hurt_from {
+                        if !dmg_info.hit_from.contains(&from) {
+                            dmg_info.hit_from.push(from);
+                        }
                     }
                 }
-
                 if is_hurt_heal {
                     if let Some(hurt_by) = dmg_info.max_hurt_heal_eff.as_mut() {
                         // 伤害是负数 取最小值
@@ -472,6 +798,8 @@ pub mod damage_system {
             }
         }
 
+        hooks.consume_charges(&hits);
+
         dmg_info
     }
 
@@ -560,18 +888,43 @@ mod tests {
     use strum::IntoEnumIterator;
 
     use super::damage_system::{
-        DamageCalcAttrs, MergedSurvivalEffs, apply_damages, calc_damage_scale, calc_defence_shield,
-        calc_health_max, calc_magicka_max, calc_magicka_value, merge_damages,
+        DamageCalcAttrs, MergedSurvivalEffs, apply_damages, apply_damages_with, calc_damage_scale,
+        calc_defence_shield, calc_health_max, calc_magicka_max, calc_magicka_value, merge_damages,
+        merge_damages_with,
+    };
+    use super::{
+        DamageHooks, DamageInfo, SurvivalAttrEff, SurvivalEffBuffer, SurvivalEffTargets,
+        SurvivalInterceptors, SurvivalPenetration,
+    };
+    use crate::base_lib::cores::timers::{
+        charge_counters::{ChargeCounter, ChargeEvent},
+        static_timer::{StaticTimeline, StaticTimer},
+        tick_timer::TickTimer,
+        tiny_timer::{HasTimer, Tickable},
     };
-    use super::{DamageInfo, SurvivalAttrEff, SurvivalEffBuffer, SurvivalEffTargets};
+    use crate::base_lib::cores::unify_types::time_type;
     use crate::base_lib::eff_attr::attr_layers::{AttrLayerEffTargetIter, attr_layer_system};
+    use crate::base_lib::eff_attr::attr_systems::clean_expired_element;
+    use crate::base_lib::eff_attr::bound_attr_effs::BoundAttrEffType;
     use crate::base_lib::eff_attr::bound_attrs::BoundAttr;
+    use crate::base_lib::eff_attr::bounded_attr_regens::{
+        BoundedAttrRegen, RegenDirection, RegenEvent,
+    };
+    use crate::base_lib::eff_attr::bounded_attr_stacks::{StackConsumeOrder, StackEntry};
+    use crate::base_lib::eff_attr::effects::EffectMean;
+    use crate::base_lib::eff_attr::interceptors::{
+        EffInterceptor, InterceptCharge, InterceptKind, InterceptPhase,
+    };
     use crate::base_lib::eff_attr::{
         bounded_attr_effs::AttrAlterEffType, bounded_attrs::BoundedAttr, effects::Effect,
         stat_attrs::StatAttr,
     };
+    use crate::common_impl::combats::combat_unit_systems::{
+        load_shield_with_stack, sync_shield_with_stack,
+    };
     use crate::common_impl::combats::combat_units::{
-        HealthLower, HealthUpper, ShieldArcaneUpper, ShieldDefenceUpper, ShieldSubstituteUpper,
+        HealthLower, HealthRegen, HealthUpper, ShieldArcaneUpper, ShieldDefenceUpper,
+        ShieldSubstituteUpper, SurvivalLayerAbsorbs, SurvivalShieldStacks,
     };
     use crate::common_impl::combats::damages::damage_system::{
         DamageTargetAttrs, DamageTargetMutAttrs,
@@ -618,6 +971,7 @@ mod tests {
         sub_upper: ShieldSubstituteUpper,
         def_upper: ShieldDefenceUpper,
         arc_upper: ShieldArcaneUpper,
+        absorbs: SurvivalLayerAbsorbs,
     }
 
     impl Targets {
@@ -633,6 +987,7 @@ mod tests {
                 sub_upper: ShieldSubstituteUpper(BoundAttr::new(100.0)),
                 def_upper: ShieldDefenceUpper(BoundAttr::new(100.0)),
                 arc_upper: ShieldArcaneUpper(BoundAttr::new(100.0)),
+                absorbs: SurvivalLayerAbsorbs::default(),
             }
         }
 
@@ -660,6 +1015,7 @@ mod tests {
                 target_shield_sub_upper: &self.sub_upper,
                 target_shield_def_upper: &self.def_upper,
                 target_shield_arc_upper: &self.arc_upper,
+                target_layer_absorbs: &self.absorbs,
             }
         }
     }
@@ -715,6 +1071,40 @@ mod tests {
         )
     }
 
+    fn run_damage_intercepted<Timer: InterceptCharge>(
+        buffer: &mut SurvivalEffBuffer<&'static str>,
+        targets: &mut Targets,
+        attrs: &TestAttrs,
+        interceptors: &mut SurvivalInterceptors<&'static str, Timer>,
+    ) -> DamageInfo<&'static str> {
+        let mut hooks = DamageHooks::default().with_interceptors(interceptors);
+        let merged = merge_damages_with(buffer, targets.as_dmg_target_attrs(), &mut hooks);
+        apply_damages_with(
+            merged,
+            attrs.as_dmg_calc_attrs(),
+            targets.as_dmg_target_mut_attrs(),
+            &mut hooks,
+        )
+    }
+
+    fn run_damage_stacked(
+        buffer: &mut SurvivalEffBuffer<&'static str>,
+        targets: &mut Targets,
+        attrs: &TestAttrs,
+        shield_stacks: &mut SurvivalShieldStacks<&'static str>,
+        timeline: &StaticTimeline,
+    ) -> DamageInfo<&'static str> {
+        let mut hooks =
+            DamageHooks::<_, TickTimer>::default().with_shield_stacks(shield_stacks, timeline);
+        let merged = merge_damages_with(buffer, targets.as_dmg_target_attrs(), &mut hooks);
+        apply_damages_with(
+            merged,
+            attrs.as_dmg_calc_attrs(),
+            targets.as_dmg_target_mut_attrs(),
+            &mut hooks,
+        )
+    }
+
     // endregion
 
     // region: SurvivalEffTargets 方法（基于文档注释）
@@ -968,6 +1358,7 @@ mod tests {
             sub_upper: ShieldSubstituteUpper(BoundAttr::new(100.0)),
             def_upper: ShieldDefenceUpper(BoundAttr::new(100.0)),
             arc_upper: ShieldArcaneUpper(BoundAttr::new(100.0)),
+            absorbs: SurvivalLayerAbsorbs::default(),
         };
         let mut buffer = SurvivalEffBuffer::new();
         buffer.push(SurvivalAttrEff::new(
@@ -995,6 +1386,7 @@ mod tests {
             sub_upper: ShieldSubstituteUpper(BoundAttr::new(100.0)),
             def_upper: ShieldDefenceUpper(BoundAttr::new(100.0)),
             arc_upper: ShieldArcaneUpper(BoundAttr::new(100.0)),
+            absorbs: SurvivalLayerAbsorbs::default(),
         };
         let mut buffer = SurvivalEffBuffer::new();
         buffer.push(SurvivalAttrEff::new(
@@ -1021,6 +1413,7 @@ mod tests {
             sub_upper: ShieldSubstituteUpper(BoundAttr::new(100.0)),
             def_upper: ShieldDefenceUpper(BoundAttr::new(100.0)),
             arc_upper: ShieldArcaneUpper(BoundAttr::new(100.0)),
+            absorbs: SurvivalLayerAbsorbs::default(),
         };
         let mut buffer = SurvivalEffBuffer::new();
         buffer.push(SurvivalAttrEff::new(
@@ -1239,5 +1632,467 @@ mod tests {
         );
     }
 
+    /// 受到伤害抛出 [`RegenEvent::Damaged`] ，重置生命恢复的延迟；治疗不打断
+    #[test]
+    fn damage_resets_health_regen_delay() {
+        let mut timeline = StaticTimeline::new();
+        let mut regen = HealthRegen(
+            BoundedAttrRegen::new(
+                AttrAlterEffType::MaxPer,
+                0.01,
+                RegenDirection::Recover,
+                StaticTimer::new(&timeline, time_type::unit::<2>()),
+            )
+            .reset_on(RegenEvent::Damaged),
+        );
+        timeline.0.tick(time_type::unit::<3>());
+        assert!(regen.0.is_regenerating(&timeline));
+
+        let mut targets = Targets::full();
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("c", "heal", 10.0),
+        ));
+        let info = run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!(info.get_regen_event().is_none());
+
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::PhysicsImpact,
+            AttrAlterEffType::Val,
+            Effect::new("a", "impact", -10.0),
+        ));
+        let info = run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        let event = info.get_regen_event().unwrap();
+        assert!(regen.0.notify(event, &timeline));
+        assert!(!regen.0.is_regenerating(&timeline));
+    }
+
+    // endregion
+
+    // region: 拦截器
+
+    /// 拦截器：原始值阶段按单个效果的名称匹配，缩放后阶段按目标匹配
+    #[test]
+    fn interceptors_scale_by_name_and_target() {
+        let mut interceptors = SurvivalInterceptors::default();
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Scale,
+                InterceptPhase::Raw,
+                Effect::new("ring", "fire_resist", 0.7),
+                TickTimer::inf(),
+            )
+            .with_eff_name("fireball"),
+        );
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Scale,
+                InterceptPhase::Scaled,
+                Effect::new("blessing", "heal_up", 1.1),
+                TickTimer::inf(),
+            )
+            .with_target(SurvivalEffTargets::OnlyHealth)
+            .with_nature(EffectMean::Good),
+        );
+
+        // 火球被减免 30%，同类的其他伤害不受影响
+        let mut targets = Targets::full();
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("a", "fireball", -50.0),
+        ));
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("a", "slash", -10.0),
+        ));
+        run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert!((targets.heal.0.get_pending_value() - 55.0).abs() < 1e-9);
+
+        // 受到的治疗 +10%
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("b", "renew", 20.0),
+        ));
+        run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert!((targets.heal.0.get_pending_value() - 77.0).abs() < 1e-9);
+    }
+
+    /// 拦截器：替换伤害值后仍按护盾层级顺序结算
+    #[test]
+    fn interceptors_replace_then_cascade() {
+        let mut interceptors = SurvivalInterceptors::default();
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Replace,
+                InterceptPhase::Scaled,
+                Effect::new("boss", "fixed_hit", -1.0),
+                TickTimer::inf(),
+            )
+            .with_target(SurvivalEffTargets::PhysicsShears),
+        );
+
+        let mut targets = Targets::full();
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::PhysicsShears,
+            AttrAlterEffType::Val,
+            Effect::new("a", "shear", -60.0),
+        ));
+        let info = run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert_eq!(targets.heal.0.get_pending_value(), 100.0);
+        assert!(info.max_hurt_heal_eff.is_none());
+    }
+
+    /// 拦截器：缩放后阶段按单个效果的名称匹配，与推入顺序无关
+    #[test]
+    fn interceptors_scaled_match_each_eff() {
+        let mut interceptors = SurvivalInterceptors::default();
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Scale,
+                InterceptPhase::Scaled,
+                Effect::new("ring", "fire_resist", 0.5),
+                TickTimer::inf(),
+            )
+            .with_eff_name("fireball"),
+        );
+
+        let fireball = Effect::new("a", "fireball", -40.0);
+        let slash = Effect::new("b", "slash", -10.0);
+        for pushed in [[&fireball, &slash], [&slash, &fireball]] {
+            let mut targets = Targets::full();
+            let mut buffer = SurvivalEffBuffer::new();
+            for eff in pushed {
+                buffer.push(SurvivalAttrEff::new(
+                    SurvivalEffTargets::OnlyHealth,
+                    AttrAlterEffType::Val,
+                    eff.clone(),
+                ));
+            }
+            let info = run_damage_intercepted(
+                &mut buffer,
+                &mut targets,
+                &TestAttrs::scale_one(),
+                &mut interceptors,
+            );
+            assert!((targets.heal.0.get_pending_value() - 70.0).abs() < 1e-9);
+            let hurt_by = info.max_hurt_heal_eff.unwrap();
+            assert!((hurt_by.get_effect_value() + 30.0).abs() < 1e-9);
+        }
+    }
+
+    /// 抵挡下一次攻击：抵消同一批的所有伤害且只消耗一次，被抵消的伤害不抛出受击与命中事件
+    #[test]
+    fn interceptors_block_next_hit_by_charge() {
+        let mut interceptors = SurvivalInterceptors::default();
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Replace,
+                InterceptPhase::Raw,
+                Effect::new("self", "parry", 0.0),
+                ChargeCounter::new(2, ChargeEvent::OnDamageTaken),
+            )
+            .with_nature(EffectMean::Bad),
+        );
+
+        let mut targets = Targets::full();
+        let mut buffer = SurvivalEffBuffer::new();
+        let push_hits = |buffer: &mut SurvivalEffBuffer<&'static str>| {
+            for (from_name, eff_name) in [("a", "slash"), ("b", "stab"), ("a", "kick")] {
+                buffer.push(SurvivalAttrEff::new(
+                    SurvivalEffTargets::OnlyHealth,
+                    AttrAlterEffType::Val,
+                    Effect::new(from_name, eff_name, -10.0),
+                ));
+            }
+        };
+        push_hits(&mut buffer);
+        let info = run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert_eq!(targets.heal.0.get_pending_value(), 100.0);
+        assert!(info.hit_from.is_empty());
+        assert!(info.get_taken_event().is_none());
+        let counter = interceptors.iter_ele().next().unwrap().get_timer();
+        assert_eq!(counter.get_remaining(), 1);
+
+        // 第二批同样整批抵消，次数耗尽
+        push_hits(&mut buffer);
+        run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert_eq!(targets.heal.0.get_pending_value(), 100.0);
+        clean_expired_element(&mut interceptors, ());
+        assert!(interceptors.ele_empty());
+
+        // 实际造成伤害的来源才算命中
+        push_hits(&mut buffer);
+        let info = run_damage_intercepted(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut interceptors,
+        );
+        assert_eq!(targets.heal.0.get_pending_value(), 70.0);
+        assert_eq!(info.hit_from, vec!["a", "b"]);
+        assert_eq!(
+            info.iter_hit_events().collect::<Vec<_>>(),
+            vec![(&"a", ChargeEvent::OnHit), (&"b", ChargeEvent::OnHit)]
+        );
+        assert_eq!(info.get_taken_event(), Some(ChargeEvent::OnDamageTaken));
+
+        // 治疗不是命中
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("c", "heal", 10.0),
+        ));
+        let info = run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!(info.get_taken_event().is_none());
+    }
+
+    /// 打在空护盾上的伤害没有削减任何一层，不算命中
+    #[test]
+    fn empty_shield_hit_is_not_hit() {
+        let mut targets = Targets::full();
+        targets.def = ShieldDefence(BoundedAttr::new(0.0));
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyShieldDefence,
+            AttrAlterEffType::Val,
+            Effect::new("a", "break_def", -10.0),
+        ));
+        let info = run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!(info.hit_from.is_empty());
+    }
+
+    // endregion
+
+    // region: 吸收比例与穿透
+
+    /// 奥术护盾吸收 70%，30% 渗透到下层
+    #[test]
+    fn layer_absorb_ratio_bleeds_through() {
+        let mut targets = Targets::full();
+        targets.sub = ShieldSubstitute(BoundedAttr::new(0.0));
+        targets.absorbs.shield_arc = 0.7;
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::MagickaArcane,
+            AttrAlterEffType::Val,
+            Effect::new("a", "arcane", -100.0),
+        ));
+        let info = run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!((targets.arc.0.get_pending_value() - 30.0).abs() < 1e-9);
+        assert!((targets.heal.0.get_pending_value() - 70.0).abs() < 1e-9);
+        assert!(info.max_hurt_heal_eff.is_some());
+
+        // 仅护盾的效果无处渗透，完全作用于护盾
+        let mut targets = Targets::full();
+        targets.absorbs.shield_arc = 0.7;
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyShieldArcane,
+            AttrAlterEffType::Val,
+            Effect::new("a", "break_arc", -100.0),
+        ));
+        run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert_eq!(targets.arc.0.get_pending_value(), 0.0);
+        assert_eq!(targets.heal.0.get_pending_value(), 100.0);
+    }
+
+    /// 破甲穿透：忽略部分防护护盾；同类合并时按效果值加权
+    #[test]
+    fn penetration_ignores_part_of_layer() {
+        let mut targets = Targets::full();
+        targets.sub = ShieldSubstitute(BoundedAttr::new(0.0));
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(
+            SurvivalAttrEff::new(
+                SurvivalEffTargets::PhysicsShears,
+                AttrAlterEffType::Val,
+                Effect::new("a", "pierce", -60.0),
+            )
+            .with_penetration(
+                SurvivalPenetration::default().with(SurvivalAttrLayer::ShieldDefence, 0.5),
+            ),
+        );
+        run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!((targets.def.0.get_pending_value() - 70.0).abs() < 1e-9);
+        assert!((targets.heal.0.get_pending_value() - 70.0).abs() < 1e-9);
+
+        // -60(50%) 与 -60(0%) 合并为 -120(25%)
+        let mut targets = Targets::full();
+        targets.sub = ShieldSubstitute(BoundedAttr::new(0.0));
+        buffer.push(
+            SurvivalAttrEff::new(
+                SurvivalEffTargets::PhysicsShears,
+                AttrAlterEffType::Val,
+                Effect::new("a", "pierce", -60.0),
+            )
+            .with_penetration(
+                SurvivalPenetration::default().with(SurvivalAttrLayer::ShieldDefence, 0.5),
+            ),
+        );
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::PhysicsShears,
+            AttrAlterEffType::Val,
+            Effect::new("a", "shear", -60.0),
+        ));
+        run_damage(&mut buffer, &mut targets, &TestAttrs::scale_one());
+        assert!((targets.def.0.get_pending_value() - 10.0).abs() < 1e-9);
+        assert!((targets.heal.0.get_pending_value() - 70.0).abs() < 1e-9);
+    }
+
+    // endregion
+
+    // region: 多来源护盾
+
+    /// 同层多个来源按消耗顺序吸收，并在伤害信息中报告
+    #[test]
+    fn shield_stack_reports_absorption_per_source() {
+        let timeline = StaticTimeline::new();
+        let mut targets = Targets::full();
+        targets.def = ShieldDefence(BoundedAttr::new(50.0));
+        targets.sub = ShieldSubstitute(BoundedAttr::new(0.0));
+        let mut stacks = SurvivalShieldStacks::default();
+        stacks.shield_def = stacks
+            .shield_def
+            .with_order(StackConsumeOrder::ExpiringFirst);
+        stacks.shield_def.push(
+            Effect::new("alice", "barrier", 30.0),
+            StaticTimer::new(&timeline, time_type::unit::<10>()),
+        );
+        stacks.shield_def.push(
+            Effect::new("bob", "barrier", 20.0),
+            StaticTimer::new(&timeline, time_type::unit::<3>()),
+        );
+
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::PhysicsShears,
+            AttrAlterEffType::Val,
+            Effect::new("enemy", "shear", -40.0),
+        ));
+        let info = run_damage_stacked(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut stacks,
+            &timeline,
+        );
+        assert_eq!(targets.def.0.get_pending_value(), 10.0);
+        let absorbed: Vec<_> = info
+            .shield_absorptions
+            .iter()
+            .map(|(layer, a)| (*layer, a.from_name, a.absorbed))
+            .collect();
+        assert_eq!(
+            absorbed,
+            vec![
+                (SurvivalAttrLayer::ShieldDefence, "bob", 20.0),
+                (SurvivalAttrLayer::ShieldDefence, "alice", 20.0),
+            ]
+        );
+        assert_eq!(stacks.shield_def.get_total(), 10.0);
+        assert!(info.max_hurt_heal_eff.is_none());
+    }
+
+    /// 明细为准：消耗与到期都恰好作用于来源的剩余量，不留下无来源的护盾
+    #[test]
+    fn shield_stack_is_source_of_truth() {
+        let mut timeline = StaticTimeline::new();
+        let mut targets = Targets::full();
+        targets.def = ShieldDefence(BoundedAttr::new(0.0));
+        targets.def_upper = ShieldDefenceUpper(BoundAttr::new(0.0));
+        let mut stacks = SurvivalShieldStacks::default();
+        stacks.shield_def = stacks
+            .shield_def
+            .with_order(StackConsumeOrder::WeakestFirst);
+        for (from, amount, duration) in [
+            ("alice", 30.0, time_type::unit::<2>()),
+            ("bob", 20.0, time_type::unit::<10>()),
+        ] {
+            load_shield_with_stack(
+                &mut stacks.shield_def,
+                &targets.def_upper.0,
+                BoundAttrEffType::BasicAdd,
+                Effect::new(from, "barrier", amount),
+                StaticTimer::new(&timeline, duration),
+            );
+        }
+        fn sync(
+            targets: &mut Targets,
+            stacks: &mut SurvivalShieldStacks<&'static str>,
+            timeline: &StaticTimeline,
+        ) -> Vec<StackEntry<&'static str, StaticTimer>> {
+            sync_shield_with_stack(
+                &mut targets.def.0,
+                &mut targets.def_upper.0,
+                &mut stacks.shield_def,
+                timeline,
+            )
+        }
+        assert!(sync(&mut targets, &mut stacks, &timeline).is_empty());
+        assert_eq!(targets.def.0.get_pending_value(), 50.0);
+        assert_eq!(targets.def_upper.0.get_current(), 50.0);
+
+        let mut buffer = SurvivalEffBuffer::new();
+        buffer.push(SurvivalAttrEff::new(
+            SurvivalEffTargets::OnlyShieldDefence,
+            AttrAlterEffType::Val,
+            Effect::new("enemy", "break", -20.0),
+        ));
+        let info = run_damage_stacked(
+            &mut buffer,
+            &mut targets,
+            &TestAttrs::scale_one(),
+            &mut stacks,
+            &timeline,
+        );
+        let absorbed: Vec<_> = info
+            .shield_absorptions
+            .iter()
+            .map(|(_, a)| (a.from_name, a.absorbed))
+            .collect();
+        assert_eq!(absorbed, vec![("bob", 20.0)]);
+        assert_eq!(targets.def.0.get_pending_value(), 30.0);
+
+        timeline.0.tick(time_type::unit::<3>());
+        let expired = sync(&mut targets, &mut stacks, &timeline);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(*expired[0].get_effect().get_from_name(), "alice");
+        assert_eq!(expired[0].get_amount(), 30.0);
+        assert!(stacks.shield_def.is_empty());
+        assert_eq!(targets.def.0.get_pending_value(), 0.0);
+        assert_eq!(targets.def_upper.0.get_current(), 0.0);
+    }
+
     // endregion
 }
@@ -0,0 +1,453 @@
+//! 延迟效果与链式效果
+//!
+//! 延迟效果持有一个延迟计时器和一个载荷，到期（或被提前引爆）时将载荷施加到对应的容器或 buffer
+//!
+//! - 载荷可以是属性效果 [`StatAttrEff`] 、上下限效果 [`BoundAttrEff`] 或生存效果 [`SurvivalAttrEff`]
+//! - 属性效果的持续时间从生效时开始计算，而不是从施加延迟效果时
+//! - 链式：生效后自动施加下一个延迟效果（A 到期 → 施加 B），延迟从上一个生效时开始计算
+//! - 重置延迟：如“每次受击重置延迟并叠加伤害，最后一次性爆发”
+//!
+//! 载荷应施加到哪个容器由业务决定（各属性的效果容器分散在实体上），通过 [`DelayedEffSink`] 分发
+//!
+//! 【注意】不要对本容器使用 [`crate::base_lib::eff_attr::attr_systems::clean_expired_element`] ，
+//! 到期元素会被直接丢弃而不生效，应使用 [`delayed_system::fire_delayed_effs`]
+
+use crate::{
+    base_lib::{
+        cores::{
+            timers::{
+                static_timer::{StaticTimeline, StaticTimer},
+                tiny_timer::{HasTimer, TimerControl, TimerView},
+            },
+            unify_types::{FixedName, time_type},
+        },
+        eff_attr::{
+            bound_attr_effs::{BoundAttrEff, BoundAttrEffType},
+            effects::Effect,
+            stat_attr_effs::{StatAttrEff, StatAttrEffType},
+            upsert_container::{Upsert, UpsertContainer},
+        },
+    },
+    common_impl::combats::damages::SurvivalAttrEff,
+};
+
+/// 延迟效果的载荷
+#[derive(Debug, Clone)]
+pub enum DelayedPayload<S: FixedName> {
+    /// 属性效果，持续时间为 None 表示永久
+    Stat {
+        eff_type: StatAttrEffType,
+        eff: Effect<S>,
+        duration: Option<time_type::T>,
+    },
+    /// 上下限效果，持续时间为 None 表示永久
+    Bound {
+        eff_type: BoundAttrEffType,
+        eff: Effect<S>,
+        duration: Option<time_type::T>,
+    },
+    /// 单次生效的生存效果（伤害、治疗、护盾）
+    Survival(SurvivalAttrEff<S>),
+}
+
+impl<S: FixedName> DelayedPayload<S> {
+    pub fn get_effect(&self) -> &Effect<S> {
+        match self {
+            DelayedPayload::Stat { eff, .. } | DelayedPayload::Bound { eff, .. } => eff,
+            DelayedPayload::Survival(svv_eff) => svv_eff.get_effect(),
+        }
+    }
+
+    pub fn get_effect_mut(&mut self) -> &mut Effect<S> {
+        match self {
+            DelayedPayload::Stat { eff, .. } | DelayedPayload::Bound { eff, .. } => eff,
+            DelayedPayload::Survival(svv_eff) => svv_eff.get_effect_mut(),
+        }
+    }
+}
+
+/// 载荷的分发目标，由业务实现，将载荷放入实体上对应的容器或 buffer
+pub trait DelayedEffSink<S: FixedName> {
+    /// 业务定义的属性标识，如区分【气力】【信念】的枚举
+    type Target;
+
+    fn emit_stat(&mut self, target: &Self::Target, eff: StatAttrEff<S, StaticTimer>);
+    fn emit_bound(&mut self, target: &Self::Target, eff: BoundAttrEff<S, StaticTimer>);
+    fn emit_survival(&mut self, eff: SurvivalAttrEff<S>);
+}
+
+/// 延迟效果
+#[derive(Debug, Clone)]
+pub struct DelayedEff<S: FixedName, Target> {
+    /// 载荷作用的属性，生存效果可忽略
+    target: Target,
+    delay: StaticTimer,
+    payload: DelayedPayload<S>,
+    /// 生效后施加的下一个延迟效果
+    next: Option<Box<DelayedEff<S, Target>>>,
+}
+
+impl<S: FixedName, Target> DelayedEff<S, Target> {
+    pub fn new(
+        timeline: &StaticTimeline,
+        target: Target,
+        delay: time_type::T,
+        payload: DelayedPayload<S>,
+    ) -> Self {
+        Self {
+            target,
+            delay: StaticTimer::new(timeline, delay),
+            payload,
+            next: None,
+        }
+    }
+
+    /// 链式：本效果生效后施加 `next` ，其延迟在那时重新开始计算
+    pub fn with_next(mut self, next: DelayedEff<S, Target>) -> Self {
+        self.next = Some(Box::new(next));
+        self
+    }
+
+    pub fn get_target(&self) -> &Target {
+        &self.target
+    }
+
+    pub fn get_payload(&self) -> &DelayedPayload<S> {
+        &self.payload
+    }
+
+    pub fn get_next(&self) -> Option<&DelayedEff<S, Target>> {
+        self.next.as_deref()
+    }
+}
+
+impl<S: FixedName, Target> HasTimer for DelayedEff<S, Target> {
+    type Timer = StaticTimer;
+
+    fn get_timer(&self) -> &Self::Timer {
+        &self.delay
+    }
+
+    fn get_timer_mut(&mut self) -> &mut Self::Timer {
+        &mut self.delay
+    }
+}
+
+/// 以载荷的效果名称和来源作为标识
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DelayedEffId<S: FixedName> {
+    pub eff: S,
+    pub from: S,
+}
+
+impl<S: FixedName, Target> Upsert for DelayedEff<S, Target> {
+    type Id = DelayedEffId<S>;
+
+    fn gen_id(&self) -> Self::Id {
+        let eff = self.payload.get_effect();
+        DelayedEffId {
+            eff: eff.get_effect_name().clone(),
+            from: eff.get_from_name().clone(),
+        }
+    }
+
+    fn matched_id(&self, id: &Self::Id) -> bool {
+        let eff = self.payload.get_effect();
+        *eff.get_effect_name() == id.eff && *eff.get_from_name() == id.from
+    }
+
+    fn has_same_id(&self, other: &Self) -> bool {
+        let (eff, other) = (self.payload.get_effect(), other.payload.get_effect());
+        eff.get_effect_name() == other.get_effect_name()
+            && eff.get_from_name() == other.get_from_name()
+    }
+}
+
+/// 类型别名 延迟效果的容器
+pub type DelayedEffs<S, Target> = UpsertContainer<DelayedEff<S, Target>>;
+
+pub mod delayed_system {
+    use super::*;
+
+    /// 载荷生效，并施加链上的下一个效果
+    fn emit<S, Target, Sink>(
+        delayed_eff: DelayedEff<S, Target>,
+        effs: &mut DelayedEffs<S, Target>,
+        timeline: &StaticTimeline,
+        sink: &mut Sink,
+    ) where
+        S: FixedName,
+        Sink: DelayedEffSink<S, Target = Target>,
+    {
+        let DelayedEff {
+            target,
+            payload,
+            next,
+            ..
+        } = delayed_eff;
+
+        let gen_timer = |duration: Option<time_type::T>| match duration {
+            Some(duration) => StaticTimer::new(timeline, duration),
+            None => StaticTimer::inf(),
+        };
+        match payload {
+            DelayedPayload::Stat {
+                eff_type,
+                eff,
+                duration,
+            } => sink.emit_stat(
+                &target,
+                StatAttrEff::new(eff_type, eff, gen_timer(duration)),
+            ),
+            DelayedPayload::Bound {
+                eff_type,
+                eff,
+                duration,
+            } => sink.emit_bound(
+                &target,
+                BoundAttrEff::new(eff_type, eff, gen_timer(duration)),
+            ),
+            DelayedPayload::Survival(svv_eff) => sink.emit_survival(svv_eff),
+        }
+
+        if let Some(mut next) = next {
+            next.delay.reset(timeline);
+            effs.upsert_replace(*next);
+        }
+    }
+
+    /// 到期的延迟效果生效，返回生效个数
+    ///
+    /// 链上的下一个效果若延迟为零，则在本次调用中一并生效
+    pub fn fire_delayed_effs<S, Target, Sink>(
+        effs: &mut DelayedEffs<S, Target>,
+        timeline: &StaticTimeline,
+        sink: &mut Sink,
+    ) -> usize
+    where
+        S: FixedName,
+        Sink: DelayedEffSink<S, Target = Target>,
+    {
+        let mut fired = 0;
+        while let Some(delayed_eff) = effs.take_ele(|e| e.delay.is_completed(timeline)) {
+            emit(delayed_eff, effs, timeline, sink);
+            fired += 1;
+        }
+        fired
+    }
+
+    /// 提前引爆：立即生效，链式效果照常施加
+    pub fn detonate_delayed_eff<S, Target, Sink>(
+        effs: &mut DelayedEffs<S, Target>,
+        id: &DelayedEffId<S>,
+        timeline: &StaticTimeline,
+        sink: &mut Sink,
+    ) -> bool
+    where
+        S: FixedName,
+        Sink: DelayedEffSink<S, Target = Target>,
+    {
+        match effs.take_ele(|e| e.matched_id(id)) {
+            Some(delayed_eff) => {
+                emit(delayed_eff, effs, timeline, sink);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 重置延迟，同时将 `extra_value` 叠加到载荷的效果值上（仅重置时传 0 ）
+    pub fn refresh_delay<S: FixedName, Target>(
+        effs: &mut DelayedEffs<S, Target>,
+        id: &DelayedEffId<S>,
+        timeline: &StaticTimeline,
+        extra_value: f64,
+    ) -> bool {
+        match effs.select_mut_ele(|e| e.matched_id(id)) {
+            Some(delayed_eff) => {
+                delayed_eff.delay.reset(timeline);
+                let eff = delayed_eff.payload.get_effect_mut();
+                eff.set_effect_value(eff.get_effect_value() + extra_value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        base_lib::{
+            cores::timers::tiny_timer::Tickable, eff_attr::bounded_attr_effs::AttrAlterEffType,
+        },
+        common_impl::combats::damages::{SurvivalEffBuffer, SurvivalEffTargets},
+    };
+
+    use super::{
+        delayed_system::{detonate_delayed_eff, fire_delayed_effs, refresh_delay},
+        *,
+    };
+
+    type S = &'static str;
+
+    #[derive(Debug, PartialEq)]
+    enum Attr {
+        Strength,
+        HealthUpper,
+    }
+
+    #[derive(Default)]
+    struct TestSink {
+        strength_effs: UpsertContainer<StatAttrEff<S, StaticTimer>>,
+        health_upper_effs: UpsertContainer<BoundAttrEff<S, StaticTimer>>,
+        svv_buffer: SurvivalEffBuffer<S>,
+    }
+
+    impl DelayedEffSink<S> for TestSink {
+        type Target = Attr;
+
+        fn emit_stat(&mut self, target: &Attr, eff: StatAttrEff<S, StaticTimer>) {
+            assert_eq!(*target, Attr::Strength);
+            self.strength_effs.upsert_replace(eff);
+        }
+
+        fn emit_bound(&mut self, target: &Attr, eff: BoundAttrEff<S, StaticTimer>) {
+            assert_eq!(*target, Attr::HealthUpper);
+            self.health_upper_effs.upsert_replace(eff);
+        }
+
+        fn emit_survival(&mut self, eff: SurvivalAttrEff<S>) {
+            self.svv_buffer.push(eff);
+        }
+    }
+
+    fn bomb(timeline: &StaticTimeline, value: f64) -> DelayedEff<S, Attr> {
+        DelayedEff::new(
+            timeline,
+            Attr::HealthUpper, // 生存效果忽略
+            time_type::unit::<3>(),
+            DelayedPayload::Survival(SurvivalAttrEff::new(
+                SurvivalEffTargets::OnlyHealth,
+                AttrAlterEffType::Val,
+                Effect::new("rogue", "bomb", value),
+            )),
+        )
+    }
+
+    fn bomb_id() -> DelayedEffId<S> {
+        DelayedEffId {
+            eff: "bomb",
+            from: "rogue",
+        }
+    }
+
+    /// 到期生效，属性效果的持续时间从生效时开始计算
+    #[test]
+    fn test_fire_on_expiry() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = DelayedEffs::default();
+        let mut sink = TestSink::default();
+        effs.upsert_replace(DelayedEff::new(
+            &timeline,
+            Attr::Strength,
+            time_type::unit::<2>(),
+            DelayedPayload::Stat {
+                eff_type: StatAttrEffType::BasicAdd,
+                eff: Effect::new("ally", "war_cry", 5.0),
+                duration: Some(time_type::unit::<2>()),
+            },
+        ));
+
+        timeline.0.tick(time_type::unit::<1>());
+        assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 0);
+        timeline.0.tick(time_type::unit::<1>());
+        assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 1);
+        assert!(effs.ele_empty());
+
+        let stat_eff = sink.strength_effs.iter_ele().next().unwrap();
+        assert_eq!(stat_eff.get_effect().get_effect_value(), 5.0);
+        timeline.0.tick(time_type::unit::<1>());
+        assert!(!stat_eff.get_timer().is_completed(&timeline));
+        timeline.0.tick(time_type::unit::<1>());
+        assert!(stat_eff.get_timer().is_completed(&timeline));
+    }
+
+    /// 链式：A 生效后施加 B ， B 的延迟从 A 生效时开始计算
+    #[test]
+    fn test_chain() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = DelayedEffs::default();
+        let mut sink = TestSink::default();
+
+        let second = DelayedEff::new(
+            &timeline,
+            Attr::HealthUpper,
+            time_type::unit::<1>(),
+            DelayedPayload::Bound {
+                eff_type: BoundAttrEffType::BasicAdd,
+                eff: Effect::new("rogue", "aftershock", -10.0),
+                duration: None,
+            },
+        );
+        effs.upsert_replace(bomb(&timeline, -30.0).with_next(second));
+        assert!(effs.iter_ele().next().unwrap().get_next().is_some());
+
+        timeline.0.tick(time_type::unit::<3>());
+        assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 1);
+        assert_eq!(sink.svv_buffer.len(), 1);
+        assert_eq!(effs.ele_len(), 1);
+        assert!(sink.health_upper_effs.ele_empty());
+
+        timeline.0.tick(time_type::unit::<1>());
+        assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 1);
+        assert!(effs.ele_empty());
+        assert_eq!(sink.health_upper_effs.ele_len(), 1);
+    }
+
+    /// 提前引爆
+    #[test]
+    fn test_detonate_early() {
+        let timeline = StaticTimeline::new();
+        let mut effs = DelayedEffs::default();
+        let mut sink = TestSink::default();
+        effs.upsert_replace(bomb(&timeline, -30.0));
+
+        assert!(detonate_delayed_eff(
+            &mut effs,
+            &bomb_id(),
+            &timeline,
+            &mut sink
+        ));
+        assert!(!detonate_delayed_eff(
+            &mut effs,
+            &bomb_id(),
+            &timeline,
+            &mut sink
+        ));
+        assert!(effs.ele_empty());
+        let svv_eff = sink.svv_buffer.iter().next().unwrap();
+        assert_eq!(svv_eff.get_effect().get_effect_value(), -30.0);
+    }
+
+    /// 重置延迟并叠加伤害，最后一次性爆发
+    #[test]
+    fn test_refresh_delay_and_stack() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = DelayedEffs::default();
+        let mut sink = TestSink::default();
+        effs.upsert_replace(bomb(&timeline, -10.0));
+
+        for _ in 0..3 {
+            timeline.0.tick(time_type::unit::<2>());
+            assert!(refresh_delay(&mut effs, &bomb_id(), &timeline, -10.0));
+            assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 0);
+        }
+
+        timeline.0.tick(time_type::unit::<3>());
+        assert_eq!(fire_delayed_effs(&mut effs, &timeline, &mut sink), 1);
+        let svv_eff = sink.svv_buffer.iter().next().unwrap();
+        assert_eq!(svv_eff.get_effect().get_effect_value(), -40.0);
+        assert!(!refresh_delay(&mut effs, &bomb_id(), &timeline, 0.0));
+    }
+}
@@ -0,0 +1,631 @@
+//! 元素积攒
+//!
+//! - 积攒槽 [`Entropy`] [`Electric`] 满时正常，元素效果使其减少，自然消退见 [`EntropyDecay`] [`ElectricDecay`]
+//! - 元素效果推入 [`ElementEffBuffer`] ，每帧由 [`element_system::apply_elements`] 消费，
+//!   积攒量按 [`EntropyResist`] [`ElectricResist`] 减免
+//! - 积攒槽穿越阈值时触发异常状态，阈值名称即异常状态名称（如“燃烧”“冻结”“麻痹”）
+//! - 目标已有积攒的元素与新元素组合时，按 [`ElementReactionTable`] 触发反应
+//!
+//! 熵槽同时承载炎热与寒冷， [`EntropyAura`] 记录当前的极性：
+//! - 相反极性的积攒先相互抵消，剩余部分按新极性积攒
+//! - 炎热与寒冷各自注册阈值，只报告当前极性的穿越，见 [`EntropyThresholds`]
+//!
+//! [`Entropy`]: super::combat_units::Entropy
+//! [`Electric`]: super::combat_units::Electric
+//! [`EntropyDecay`]: super::combat_units::EntropyDecay
+//! [`ElectricDecay`]: super::combat_units::ElectricDecay
+//! [`EntropyResist`]: super::combat_additions::EntropyResist
+//! [`ElectricResist`]: super::combat_additions::ElectricResist
+
+use strum_macros::EnumIter;
+
+use crate::base_lib::{
+    cores::unify_types::FixedName,
+    eff_attr::{
+        bounded_attr_effs::{AttrAlterEff, AttrAlterEffType},
+        bounded_attr_thresholds::AttrThresholds,
+        effects::Effect,
+    },
+};
+
+/// 元素效果生效目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum ElementEffTargets {
+    /// 炎热 积攒到熵槽
+    Heat,
+    /// 寒冷 积攒到熵槽
+    Cold,
+    /// 电 积攒到电势能槽
+    Electric,
+}
+
+impl ElementEffTargets {
+    pub fn get_bar(&self) -> ElementBar {
+        match self {
+            Self::Heat | Self::Cold => ElementBar::Entropy,
+            Self::Electric => ElementBar::Electric,
+        }
+    }
+}
+
+/// 元素积攒槽
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum ElementBar {
+    Entropy,
+    Electric,
+}
+
+/// 元素积攒效果，负值为积攒，正值为净化
+#[derive(Debug, Clone)]
+pub struct ElementAttrEff<S: FixedName> {
+    target_type: ElementEffTargets,
+    /// 生效方式（绝对值或是百分比），百分比参照物为对应的积攒槽
+    alter_type: AttrAlterEffType,
+    eff: Effect<S>,
+}
+
+impl<S: FixedName> ElementAttrEff<S> {
+    /// 构造单次元素效果
+    ///
+    /// 推入 [`ElementEffBuffer`] 后由元素系统消费。
+    #[must_use]
+    pub fn new(
+        target_type: ElementEffTargets,
+        alter_type: AttrAlterEffType,
+        eff: Effect<S>,
+    ) -> Self {
+        Self {
+            target_type,
+            alter_type,
+            eff,
+        }
+    }
+
+    pub fn new_from_alter_eff(target_type: ElementEffTargets, eff: AttrAlterEff<S>) -> Self {
+        Self {
+            target_type,
+            alter_type: eff.get_type(),
+            eff: eff.take_eff(),
+        }
+    }
+
+    pub fn get_target_type(&self) -> ElementEffTargets {
+        self.target_type
+    }
+
+    pub fn get_alter_type(&self) -> AttrAlterEffType {
+        self.alter_type
+    }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+}
+
+/// 存放元素效果的 buffer
+#[derive(Debug)]
+pub struct ElementEffBuffer<S: FixedName>(Vec<ElementAttrEff<S>>);
+
+impl<S: FixedName> Default for ElementEffBuffer<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: FixedName> ElementEffBuffer<S> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, eff: ElementAttrEff<S>) {
+        self.0.push(eff);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ElementAttrEff<S>> {
+        self.0.iter()
+    }
+}
+
+/// 熵槽当前积攒的极性，槽满时为 None
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EntropyAura(pub Option<ElementEffTargets>);
+
+/// 熵槽的阈值，炎热与寒冷分别注册（如“燃烧”“冻结”）
+#[derive(Debug, Clone)]
+pub struct EntropyThresholds<S: FixedName> {
+    pub heat: AttrThresholds<S>,
+    pub cold: AttrThresholds<S>,
+}
+
+impl<S: FixedName> Default for EntropyThresholds<S> {
+    fn default() -> Self {
+        Self {
+            heat: AttrThresholds::default(),
+            cold: AttrThresholds::default(),
+        }
+    }
+}
+
+/// 电势能槽的阈值（如“麻痹”）
+#[derive(Debug, Clone)]
+pub struct ElectricThresholds<S: FixedName>(pub AttrThresholds<S>);
+
+impl<S: FixedName> Default for ElectricThresholds<S> {
+    fn default() -> Self {
+        Self(AttrThresholds::default())
+    }
+}
+
+/// 元素反应
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementReaction<S: FixedName> {
+    pub name: S,
+    /// 反应清除已有积攒的比例，取值 `[0, 1]`
+    pub clear_ratio: f64,
+}
+
+impl<S: FixedName> ElementReaction<S> {
+    pub fn new(name: S, clear_ratio: f64) -> Self {
+        Self {
+            name,
+            clear_ratio: clear_ratio.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// 元素反应表，两种元素的组合与顺序无关
+#[derive(Debug, Clone)]
+pub struct ElementReactionTable<S: FixedName>(
+    Vec<((ElementEffTargets, ElementEffTargets), ElementReaction<S>)>,
+);
+
+impl<S: FixedName> Default for ElementReactionTable<S> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<S: FixedName> ElementReactionTable<S> {
+    fn matched(
+        pair: (ElementEffTargets, ElementEffTargets),
+        a: ElementEffTargets,
+        b: ElementEffTargets,
+    ) -> bool {
+        pair == (a, b) || pair == (b, a)
+    }
+
+    /// 同一组合覆盖
+    pub fn register(
+        &mut self,
+        a: ElementEffTargets,
+        b: ElementEffTargets,
+        reaction: ElementReaction<S>,
+    ) {
+        if let Some((_, old)) = self
+            .0
+            .iter_mut()
+            .find(|(pair, _)| Self::matched(*pair, a, b))
+        {
+            *old = reaction;
+        } else {
+            self.0.push(((a, b), reaction));
+        }
+    }
+
+    pub fn with(
+        mut self,
+        a: ElementEffTargets,
+        b: ElementEffTargets,
+        reaction: ElementReaction<S>,
+    ) -> Self {
+        self.register(a, b, reaction);
+        self
+    }
+
+    pub fn get_reaction(
+        &self,
+        a: ElementEffTargets,
+        b: ElementEffTargets,
+    ) -> Option<&ElementReaction<S>> {
+        self.0
+            .iter()
+            .find(|(pair, _)| Self::matched(*pair, a, b))
+            .map(|(_, reaction)| reaction)
+    }
+}
+
+/// 触发的元素反应
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementReactionEvent<S: FixedName> {
+    pub name: S,
+    /// 新元素的来源
+    pub from_name: S,
+    /// 已有积攒的元素
+    pub aura: ElementEffTargets,
+    /// 新元素
+    pub incoming: ElementEffTargets,
+}
+
+/// 元素信息，表示每帧元素效果造成的影响
+#[derive(Debug)]
+pub struct ElementInfo<S: FixedName> {
+    /// 按结算顺序
+    pub reactions: Vec<ElementReactionEvent<S>>,
+    /// 本帧有积攒的槽，用于重置自然消退的延迟
+    pub accumulated: Vec<ElementBar>,
+}
+
+impl<S: FixedName> Default for ElementInfo<S> {
+    fn default() -> Self {
+        Self {
+            reactions: Vec::new(),
+            accumulated: Vec::new(),
+        }
+    }
+}
+
+pub mod element_system {
+    use strum::IntoEnumIterator;
+
+    use crate::{
+        base_lib::{
+            cores::unify_types::{FLOAT_DEAD_ZONE, FixedName},
+            eff_attr::{
+                attr_systems::do_commit_bounded_attr_watched, bound_attrs::BoundAttr,
+                bounded_attr_thresholds::ThresholdCrossing, bounded_attrs::BoundedAttr,
+            },
+        },
+        common_impl::combats::{
+            combat_additions::{ElectricResist, EntropyResist},
+            combat_units::{Electric, ElectricUpper, Entropy, EntropyUpper},
+            elements::{
+                ElectricThresholds, ElementAttrEff, ElementBar, ElementEffBuffer,
+                ElementEffTargets, ElementInfo, ElementReactionEvent, ElementReactionTable,
+                EntropyAura, EntropyThresholds,
+            },
+        },
+    };
+
+    const BOUNDED_ATTR_LOWER: f64 = 0.0;
+
+    pub struct ElementTargetMutAttrs<'a> {
+        pub target_entropy: &'a mut Entropy,
+        pub target_electric: &'a mut Electric,
+        pub target_entropy_aura: &'a mut EntropyAura,
+        pub target_entropy_upper: &'a EntropyUpper,
+        pub target_electric_upper: &'a ElectricUpper,
+        pub target_entropy_resist: &'a EntropyResist,
+        pub target_electric_resist: &'a ElectricResist,
+    }
+
+    impl ElementTargetMutAttrs<'_> {
+        fn get_bar_attr(&mut self, bar: ElementBar) -> (&mut BoundedAttr, &BoundAttr) {
+            match bar {
+                ElementBar::Entropy => (&mut self.target_entropy.0, &self.target_entropy_upper.0),
+                ElementBar::Electric => {
+                    (&mut self.target_electric.0, &self.target_electric_upper.0)
+                }
+            }
+        }
+
+        fn get_resist(&self, bar: ElementBar) -> f64 {
+            match bar {
+                ElementBar::Entropy => self.target_entropy_resist.0.get_current(),
+                ElementBar::Electric => self.target_electric_resist.0.get_current(),
+            }
+        }
+
+        /// 已积攒的量，按 pending 计算
+        fn get_buildup(&self, bar: ElementBar) -> f64 {
+            let (attr, upper) = match bar {
+                ElementBar::Entropy => (&self.target_entropy.0, &self.target_entropy_upper.0),
+                ElementBar::Electric => (&self.target_electric.0, &self.target_electric_upper.0),
+            };
+            (upper.get_current() - attr.get_pending_value()).max(0.0)
+        }
+
+        /// 槽内有积攒时的元素
+        fn get_aura(&self, bar: ElementBar) -> Option<ElementEffTargets> {
+            if self.get_buildup(bar) < FLOAT_DEAD_ZONE {
+                return None;
+            }
+            match bar {
+                ElementBar::Entropy => self.target_entropy_aura.0,
+                ElementBar::Electric => Some(ElementEffTargets::Electric),
+            }
+        }
+    }
+
+    /// 抗性对积攒量的缩放，抗性不低于 1 时免疫
+    pub fn calc_resist_scale(resist: f64) -> f64 {
+        (1.0 - resist).max(0.0)
+    }
+
+    /// 消费元素效果，写入积攒槽的 pending
+    ///
+    /// 每个积攒效果依次：
+    /// - 按抗性减免（净化效果不受抗性影响，也不触发反应）
+    /// - 与各槽已有积攒的其他元素按反应表反应，清除对应比例的积攒
+    /// - 熵槽上相反极性的积攒先相互抵消，剩余部分按新极性积攒
+    pub fn apply_elements<S: FixedName>(
+        eff_buffer: &mut ElementEffBuffer<S>,
+        mut target_attrs: ElementTargetMutAttrs,
+        reactions: &ElementReactionTable<S>,
+    ) -> ElementInfo<S> {
+        let mut info = ElementInfo::default();
+
+        for eff in eff_buffer.0.drain(0..) {
+            let ElementAttrEff {
+                target_type,
+                alter_type,
+                eff,
+            } = eff;
+            let bar = target_type.get_bar();
+
+            let (attr, upper) = target_attrs.get_bar_attr(bar);
+            let val = alter_type.calc_alter_val(eff.get_effect_value(), attr, upper);
+            if val >= -FLOAT_DEAD_ZONE {
+                attr.apply_eff(val);
+                attr.clamp_by(BOUNDED_ATTR_LOWER, upper);
+                continue;
+            }
+            let val = val * calc_resist_scale(target_attrs.get_resist(bar));
+
+            for aura_bar in ElementBar::iter() {
+                let Some(aura) = target_attrs.get_aura(aura_bar) else {
+                    continue;
+                };
+                if aura == target_type {
+                    continue;
+                }
+                let Some(reaction) = reactions.get_reaction(aura, target_type) else {
+                    continue;
+                };
+                let cleared = target_attrs.get_buildup(aura_bar) * reaction.clear_ratio;
+                target_attrs.get_bar_attr(aura_bar).0.apply_eff(cleared);
+                info.reactions.push(ElementReactionEvent {
+                    name: reaction.name.clone(),
+                    from_name: eff.get_from_name().clone(),
+                    aura,
+                    incoming: target_type,
+                });
+            }
+
+            let opposite_buildup = match target_attrs.get_aura(bar) {
+                Some(aura) if aura != target_type => target_attrs.get_buildup(bar),
+                _ => 0.0,
+            };
+            let cancelled = opposite_buildup.min(-val);
+            let remaining = val + cancelled;
+            let (attr, upper) = target_attrs.get_bar_attr(bar);
+            attr.apply_eff(cancelled + remaining);
+            attr.clamp_by(BOUNDED_ATTR_LOWER, upper);
+
+            if remaining < -FLOAT_DEAD_ZONE {
+                if bar == ElementBar::Entropy {
+                    target_attrs.target_entropy_aura.0 = Some(target_type);
+                }
+                if !info.accumulated.contains(&bar) {
+                    info.accumulated.push(bar);
+                }
+            }
+        }
+
+        info
+    }
+
+    /// 提交熵槽，返回当前极性的阈值穿越；槽满时清除极性
+    ///
+    /// 两种极性的阈值都参与检测，保持各自的滞回状态
+    pub fn commit_entropy<S: FixedName>(
+        entropy: &mut Entropy,
+        entropy_upper: &EntropyUpper,
+        entropy_aura: &mut EntropyAura,
+        thresholds: &mut EntropyThresholds<S>,
+    ) -> Vec<ThresholdCrossing<S>> {
+        let old_val = entropy.0.get_snapshot_value();
+        entropy.0.commit_pending_value();
+        let new_val = entropy.0.get_snapshot_value();
+        let upper = entropy_upper.0.get_current();
+
+        let heat = thresholds.heat.detect(old_val, new_val, upper);
+        let cold = thresholds.cold.detect(old_val, new_val, upper);
+        let crossings = match entropy_aura.0 {
+            Some(ElementEffTargets::Heat) => heat,
+            Some(ElementEffTargets::Cold) => cold,
+            _ => Vec::new(),
+        };
+
+        if new_val >= upper - FLOAT_DEAD_ZONE {
+            entropy_aura.0 = None;
+        }
+        crossings
+    }
+
+    /// 提交电势能槽，返回阈值穿越
+    #[inline]
+    pub fn commit_electric<S: FixedName>(
+        electric: &mut Electric,
+        electric_upper: &ElectricUpper,
+        thresholds: &mut ElectricThresholds<S>,
+    ) -> Vec<ThresholdCrossing<S>> {
+        do_commit_bounded_attr_watched(&mut electric.0, &mut thresholds.0, &electric_upper.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::element_system::{
+        ElementTargetMutAttrs, apply_elements, commit_electric, commit_entropy,
+    };
+    use super::*;
+    use crate::base_lib::eff_attr::{
+        bound_attrs::BoundAttr,
+        bounded_attr_thresholds::{AttrThreshold, ThresholdEdge, ThresholdValue, ThresholdWatch},
+        bounded_attrs::BoundedAttr,
+        stat_attrs::StatAttr,
+    };
+    use crate::common_impl::combats::{
+        combat_additions::{ElectricResist, EntropyResist},
+        combat_units::{Electric, ElectricUpper, Entropy, EntropyUpper},
+    };
+
+    struct Targets {
+        entropy: Entropy,
+        electric: Electric,
+        aura: EntropyAura,
+        entropy_upper: EntropyUpper,
+        electric_upper: ElectricUpper,
+        entropy_resist: EntropyResist,
+        electric_resist: ElectricResist,
+    }
+
+    impl Targets {
+        fn full(entropy_resist: f64) -> Self {
+            Self {
+                entropy: Entropy(BoundedAttr::new(100.0)),
+                electric: Electric(BoundedAttr::new(100.0)),
+                aura: EntropyAura::default(),
+                entropy_upper: EntropyUpper(BoundAttr::new(100.0)),
+                electric_upper: ElectricUpper(BoundAttr::new(100.0)),
+                entropy_resist: EntropyResist(StatAttr::new(entropy_resist)),
+                electric_resist: ElectricResist(StatAttr::new(0.0)),
+            }
+        }
+
+        fn as_mut_attrs(&mut self) -> ElementTargetMutAttrs<'_> {
+            ElementTargetMutAttrs {
+                target_entropy: &mut self.entropy,
+                target_electric: &mut self.electric,
+                target_entropy_aura: &mut self.aura,
+                target_entropy_upper: &self.entropy_upper,
+                target_electric_upper: &self.electric_upper,
+                target_entropy_resist: &self.entropy_resist,
+                target_electric_resist: &self.electric_resist,
+            }
+        }
+    }
+
+    fn push(buffer: &mut ElementEffBuffer<&'static str>, target: ElementEffTargets, val: f64) {
+        buffer.push(ElementAttrEff::new(
+            target,
+            AttrAlterEffType::Val,
+            Effect::new("mage", "spell", val),
+        ));
+    }
+
+    /// 抗性减免积攒；只报告当前极性的阈值，槽满后清除极性
+    #[test]
+    fn test_accumulate_with_resist_and_thresholds() {
+        let mut targets = Targets::full(0.5);
+        let mut thresholds = EntropyThresholds::default();
+        thresholds.heat.register(AttrThreshold::new(
+            "burning",
+            ThresholdValue::UpperPer(0.5),
+            ThresholdWatch::Both,
+        ));
+        thresholds.cold.register(AttrThreshold::new(
+            "frozen",
+            ThresholdValue::UpperPer(0.5),
+            ThresholdWatch::Falling,
+        ));
+        let mut reactions = ElementReactionTable::default();
+
+        let mut buffer = ElementEffBuffer::new();
+        push(&mut buffer, ElementEffTargets::Heat, -40.0);
+        let info = apply_elements(&mut buffer, targets.as_mut_attrs(), &reactions);
+        assert!(buffer.is_empty());
+        assert_eq!(info.accumulated, vec![ElementBar::Entropy]);
+        assert_eq!(targets.entropy.0.get_pending_value(), 80.0);
+        assert_eq!(targets.aura.0, Some(ElementEffTargets::Heat));
+
+        let mut commit = |targets: &mut Targets| {
+            commit_entropy(
+                &mut targets.entropy,
+                &targets.entropy_upper,
+                &mut targets.aura,
+                &mut thresholds,
+            )
+        };
+        assert!(commit(&mut targets).is_empty());
+
+        push(&mut buffer, ElementEffTargets::Heat, -80.0);
+        apply_elements(&mut buffer, targets.as_mut_attrs(), &reactions);
+        let crossings = commit(&mut targets);
+        assert_eq!(crossings.len(), 1);
+        assert_eq!(crossings[0].name, "burning");
+        assert_eq!(crossings[0].edge, ThresholdEdge::Falling);
+
+        // 净化回满，不触发反应，报告上穿后清除极性
+        reactions.register(
+            ElementEffTargets::Heat,
+            ElementEffTargets::Cold,
+            ElementReaction::new("steam", 0.5),
+        );
+        push(&mut buffer, ElementEffTargets::Cold, 100.0);
+        let info = apply_elements(&mut buffer, targets.as_mut_attrs(), &reactions);
+        assert!(info.reactions.is_empty());
+        let crossings = commit(&mut targets);
+        assert_eq!(crossings[0].edge, ThresholdEdge::Rising);
+        assert_eq!(targets.aura.0, None);
+    }
+
+    /// 反应清除已有积攒；熵槽上相反极性先抵消
+    #[test]
+    fn test_reactions_and_polarity() {
+        let mut targets = Targets::full(0.0);
+        let reactions = ElementReactionTable::default().with(
+            ElementEffTargets::Electric,
+            ElementEffTargets::Heat,
+            ElementReaction::new("overload", 1.0),
+        );
+        assert!(
+            reactions
+                .get_reaction(ElementEffTargets::Heat, ElementEffTargets::Electric)
+                .is_some()
+        );
+
+        let mut buffer = ElementEffBuffer::new();
+        push(&mut buffer, ElementEffTargets::Electric, -30.0);
+        push(&mut buffer, ElementEffTargets::Heat, -20.0);
+        let info = apply_elements(&mut buffer, targets.as_mut_attrs(), &reactions);
+        assert_eq!(
+            info.reactions,
+            vec![ElementReactionEvent {
+                name: "overload",
+                from_name: "mage",
+                aura: ElementEffTargets::Electric,
+                incoming: ElementEffTargets::Heat,
+            }]
+        );
+        assert_eq!(targets.electric.0.get_pending_value(), 100.0);
+        assert_eq!(targets.entropy.0.get_pending_value(), 80.0);
+
+        push(&mut buffer, ElementEffTargets::Cold, -30.0);
+        apply_elements(&mut buffer, targets.as_mut_attrs(), &reactions);
+        // 炎热 20 被抵消，剩余 10 积攒为寒冷
+        assert_eq!(targets.entropy.0.get_pending_value(), 90.0);
+        assert_eq!(targets.aura.0, Some(ElementEffTargets::Cold));
+
+        let mut electric_thresholds = ElectricThresholds::<&str>::default();
+        assert!(
+            commit_electric(
+                &mut targets.electric,
+                &targets.electric_upper,
+                &mut electric_thresholds,
+            )
+            .is_empty()
+        );
+    }
+}
@@ -1,6 +1,18 @@
 use std::ops::{Deref, DerefMut};
 
-use crate::base_lib::{cores::unify_types::FixedName, eff_attr::bounded_attr_effs::AttrAlterEff};
+use crate::{
+    base_lib::{
+        cores::{
+            timers::{charge_counters::ChargeEvent, static_timer::StaticTimer},
+            unify_types::{FLOAT_DEAD_ZONE, FixedName},
+        },
+        eff_attr::{
+            bounded_attr_effs::AttrAlterEff, bounded_attr_regens::RegenEvent,
+            interceptors::EffInterceptors,
+        },
+    },
+    common_impl::combats::combat_units::EnergyAttrLayer,
+};
 
 /// 能量消耗统一路径，因此无需自定义效果类型
 #[derive(Debug)]
@@ -26,6 +38,53 @@ impl<S: FixedName> DerefMut for EnergyEffBuffer<S> {
     }
 }
 
+/// 能量结算信息，表示本帧能量的合计变化
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EnergyInfo {
+    /// 拦截后作用到能量各层的合计值，负数为消耗
+    pub sum_val: f64,
+}
+
+impl EnergyInfo {
+    /// 施法者的次数事件，实际消耗了能量时抛出 [`ChargeEvent::OnCast`]
+    pub fn get_cast_event(&self) -> Option<ChargeEvent> {
+        (self.sum_val < -FLOAT_DEAD_ZONE).then_some(ChargeEvent::OnCast)
+    }
+
+    /// 打断能量恢复或衰减的事件，消耗为 [`RegenEvent::Spent`] ，获得为 [`RegenEvent::Gained`]
+    pub fn get_regen_event(&self) -> Option<RegenEvent> {
+        if self.sum_val < -FLOAT_DEAD_ZONE {
+            Some(RegenEvent::Spent)
+        } else if self.sum_val > FLOAT_DEAD_ZONE {
+            Some(RegenEvent::Gained)
+        } else {
+            None
+        }
+    }
+}
+
+/// 类型别名 作用于能量消耗的拦截器容器（如“法术消耗 -20%”），目标为能量消耗的起始层
+pub type EnergyInterceptors<S, Timer> = EffInterceptors<S, EnergyAttrLayer, Timer>;
+
+/// 能量消耗的可选扩展，默认均不启用，见 [`energy_system::cost_magicka_energy_with`]
+pub struct EnergyHooks<'a, S: FixedName, Timer = StaticTimer> {
+    /// 拦截器，不启用时效果值原样作用
+    pub interceptors: Option<&'a EnergyInterceptors<S, Timer>>,
+}
+
+impl<S: FixedName, Timer> Default for EnergyHooks<'_, S, Timer> {
+    fn default() -> Self {
+        Self { interceptors: None }
+    }
+}
+
+impl<'a, S: FixedName, Timer> EnergyHooks<'a, S, Timer> {
+    pub fn with_interceptors(mut self, interceptors: &'a EnergyInterceptors<S, Timer>) -> Self {
+        self.interceptors = Some(interceptors);
+        self
+    }
+}
+
 /// 魔法能级划分
 pub struct MagickaEnergyLevel(f64, f64, f64);
 
@@ -34,6 +93,10 @@ impl MagickaEnergyLevel {
         MagickaEnergyLevel(l0, l1, l2)
     }
 
+    pub fn get_levels(&self) -> [f64; 3] {
+        [self.0, self.1, self.2]
+    }
+
     pub fn max_energy(&self, v: f64) -> f64 {
         if v <= self.0 {
             self.0
@@ -47,44 +110,185 @@ impl MagickaEnergyLevel {
 
 pub mod energy_system {
     use crate::{
-        base_lib::{cores::unify_types::FixedName, eff_attr::attr_layers::AttrLayerTypeIter},
+        base_lib::{
+            cores::{timers::static_timer::StaticTimer, unify_types::FixedName},
+            eff_attr::{
+                attr_layers::{AttrLayerPool, AttrLayerTypeIter, attr_layer_system},
+                bounded_attrs::BoundedAttr,
+                interceptors::{InterceptPhase, intercept_eff_value},
+            },
+        },
         common_impl::combats::{
             combat_units::{EnergyAttrLayer, ExternalEnergy, Magicka, MagickaUpper},
-            energies::EnergyEffBuffer,
+            energies::{EnergyEffBuffer, EnergyHooks, EnergyInfo},
         },
     };
 
     const BOUNDED_ATTR_LOWER: f64 = 0.0;
 
+    /// 能量各层共用 [`MagickaUpper`] 作为上限
+    struct EnergyPool<'a> {
+        magicka: &'a mut Magicka,
+        ex_energy: &'a mut ExternalEnergy,
+        magicka_upper: &'a MagickaUpper,
+    }
+
+    impl AttrLayerPool<EnergyAttrLayer> for EnergyPool<'_> {
+        fn get_layer_attr(&mut self, layer: EnergyAttrLayer) -> (&mut BoundedAttr, f64, f64) {
+            let attr = match layer {
+                EnergyAttrLayer::Magicka => &mut self.magicka.0,
+                EnergyAttrLayer::ExternalEnergy => &mut self.ex_energy.0,
+            };
+            (attr, BOUNDED_ATTR_LOWER, self.magicka_upper.0.get_current())
+        }
+    }
+
+    /// 消耗能量，先扣外部能量再扣魔力
     pub fn cost_magicka_energy<S: FixedName>(
         magicka_upper: &MagickaUpper,
         magicka: &mut Magicka,
         ex_energy: &mut ExternalEnergy,
         eff_buffer: &mut EnergyEffBuffer<S>,
     ) {
+        cost_magicka_energy_with(
+            magicka_upper,
+            magicka,
+            ex_energy,
+            eff_buffer,
+            &EnergyHooks::<S, StaticTimer>::default(),
+        );
+    }
+
+    /// 同 [`cost_magicka_energy`] ，附带可选扩展 [`EnergyHooks`]
+    ///
+    /// 每个效果折算为绝对值后，经过 [`InterceptPhase::Raw`] 阶段的拦截器
+    ///
+    /// 返回的 [`EnergyInfo`] 提供施法者的次数事件与打断能量恢复的事件
+    pub fn cost_magicka_energy_with<S: FixedName, Timer>(
+        magicka_upper: &MagickaUpper,
+        magicka: &mut Magicka,
+        ex_energy: &mut ExternalEnergy,
+        eff_buffer: &mut EnergyEffBuffer<S>,
+        hooks: &EnergyHooks<S, Timer>,
+    ) -> EnergyInfo {
         if eff_buffer.is_empty() {
-            return;
+            return EnergyInfo::default();
         }
 
         let mut sum_val = 0.0;
         for eff in eff_buffer.drain(0..) {
             let real_val = eff.calc_alter_val(&magicka.0, &magicka_upper.0);
-            sum_val += real_val;
+            let mut eff = eff.take_eff();
+            eff.set_effect_value(real_val);
+            sum_val += match hooks.interceptors {
+                Some(interceptors) => intercept_eff_value(
+                    interceptors,
+                    InterceptPhase::Raw,
+                    EnergyAttrLayer::start_at(),
+                    &eff,
+                ),
+                None => eff.get_effect_value(),
+            };
         }
 
-        let energy_layers = AttrLayerTypeIter::from(EnergyAttrLayer::start_at());
-        for energy_layer in energy_layers {
-            let attr = match energy_layer {
-                EnergyAttrLayer::Magicka => &mut magicka.0,
-                EnergyAttrLayer::ExternalEnergy => &mut ex_energy.0,
-            };
+        let mut pool = EnergyPool {
+            magicka,
+            ex_energy,
+            magicka_upper,
+        };
+        attr_layer_system::cascade_layer_val(
+            &mut pool,
+            AttrLayerTypeIter::from(EnergyAttrLayer::start_at()),
+            sum_val,
+            |_, _| {},
+        );
+
+        EnergyInfo { sum_val }
+    }
+}
 
-            let old_val = attr.get_pending_value();
-            attr.apply_eff(sum_val);
-            attr.clamp_by(BOUNDED_ATTR_LOWER, &magicka_upper.0);
-            let diff_val = attr.get_pending_value() - old_val;
+#[cfg(test)]
+mod tests {
+    use crate::base_lib::{
+        cores::timers::tick_timer::TickTimer,
+        eff_attr::{
+            bound_attrs::BoundAttr,
+            bounded_attr_effs::AttrAlterEffType,
+            bounded_attrs::BoundedAttr,
+            effects::Effect,
+            interceptors::{EffInterceptor, InterceptKind, InterceptPhase},
+        },
+    };
 
-            sum_val -= diff_val;
-        }
+    use super::{
+        energy_system::{cost_magicka_energy, cost_magicka_energy_with},
+        *,
+    };
+    use crate::common_impl::combats::combat_units::{ExternalEnergy, Magicka, MagickaUpper};
+
+    /// 消耗先扣外部能量再扣魔力，拦截器按效果名称减免消耗
+    #[test]
+    fn test_cost_with_interceptor() {
+        let upper = MagickaUpper(BoundAttr::new(100.0));
+        let mut magicka = Magicka(BoundedAttr::new(50.0));
+        let mut ex_energy = ExternalEnergy(BoundedAttr::new(10.0));
+        let mut interceptors = EnergyInterceptors::default();
+        interceptors.upsert_replace(
+            EffInterceptor::new(
+                InterceptKind::Scale,
+                InterceptPhase::Raw,
+                Effect::new("staff", "cheap_fireball", 0.5),
+                TickTimer::inf(),
+            )
+            .with_eff_name("fireball"),
+        );
+        let hooks = EnergyHooks::default().with_interceptors(&interceptors);
+
+        let mut buffer = EnergyEffBuffer::default();
+        buffer.push(AttrAlterEff::new(
+            AttrAlterEffType::Val,
+            Effect::new("self", "fireball", -20.0),
+        ));
+        cost_magicka_energy_with(&upper, &mut magicka, &mut ex_energy, &mut buffer, &hooks);
+        assert_eq!(ex_energy.0.get_pending_value(), 0.0);
+        assert_eq!(magicka.0.get_pending_value(), 50.0);
+
+        buffer.push(AttrAlterEff::new(
+            AttrAlterEffType::Val,
+            Effect::new("self", "lightning", -20.0),
+        ));
+        let info =
+            cost_magicka_energy_with(&upper, &mut magicka, &mut ex_energy, &mut buffer, &hooks);
+        assert_eq!(magicka.0.get_pending_value(), 30.0);
+        assert_eq!(info.get_cast_event(), Some(ChargeEvent::OnCast));
+        assert_eq!(info.get_regen_event(), Some(RegenEvent::Spent));
+
+        // 获得能量打断衰减，不是施法
+        buffer.push(AttrAlterEff::new(
+            AttrAlterEffType::Val,
+            Effect::new("ally", "infuse", 10.0),
+        ));
+        let info =
+            cost_magicka_energy_with(&upper, &mut magicka, &mut ex_energy, &mut buffer, &hooks);
+        assert_eq!(info.get_cast_event(), None);
+        assert_eq!(info.get_regen_event(), Some(RegenEvent::Gained));
+    }
+
+    /// 不启用扩展时效果值原样作用
+    #[test]
+    fn test_cost_without_hooks() {
+        let upper = MagickaUpper(BoundAttr::new(100.0));
+        let mut magicka = Magicka(BoundedAttr::new(50.0));
+        let mut ex_energy = ExternalEnergy(BoundedAttr::new(10.0));
+
+        let mut buffer = EnergyEffBuffer::default();
+        buffer.push(AttrAlterEff::new(
+            AttrAlterEffType::Val,
+            Effect::new("self", "fireball", -20.0),
+        ));
+        cost_magicka_energy(&upper, &mut magicka, &mut ex_energy, &mut buffer);
+        assert!(buffer.is_empty());
+        assert_eq!(ex_energy.0.get_pending_value(), 0.0);
+        assert_eq!(magicka.0.get_pending_value(), 40.0);
     }
 }
@@ -1,9 +1,16 @@
 //! 战斗相关的装备（武器、盔甲），直接影响【外赋属性】
 
 use crate::{
-    base_lib::{cores::unify_types::FixedName, eff_attr::upsert_container::Upsert},
-    common_impl::combats::combat_additions::{
-        ArmorHardEffs, ArmorMassEffs, ArmorSoftEffs, WeaponMassEffs, WeaponSharpEffs,
+    base_lib::{
+        cores::unify_types::FixedName,
+        eff_attr::{effect_specs::LevelCurveSource, upsert_container::Upsert},
+    },
+    common_impl::combats::{
+        combat_additions::{
+            ArmorHardEffs, ArmorMassEffs, ArmorSoftEffs, ElectricResistEffs, EntropyResistEffs,
+            WeaponMassEffs, WeaponSharpEffs,
+        },
+        elements::ElementBar,
     },
 };
 
@@ -23,6 +30,8 @@ pub struct EquipArmor<S: FixedName> {
     soft: f64,
     /// 盔甲质量
     mass: f64,
+    /// 元素抗性，按积攒槽
+    resists: Vec<(ElementBar, f64)>,
 }
 
 impl<S: FixedName> EquipWeapon<S> {
@@ -35,6 +44,20 @@ impl<S: FixedName> EquipWeapon<S> {
         Self { name, sharp, mass }
     }
 
+    /// 按装备等级从曲线取值，任一曲线不存在时返回 None
+    pub fn new_at_level(
+        name: S,
+        curves: &impl LevelCurveSource<S>,
+        [sharp_curve, mass_curve]: [&S; 2],
+        level: f64,
+    ) -> Option<Self> {
+        Some(Self {
+            name,
+            sharp: curves.eval_curve(sharp_curve, level)?,
+            mass: curves.eval_curve(mass_curve, level)?,
+        })
+    }
+
     pub fn equip(
         &self,
         char_name: &S,
@@ -64,6 +87,7 @@ impl<S: FixedName> EquipArmor<S> {
             hard,
             soft,
             mass,
+            resists: Vec::new(),
         }
     }
 
@@ -74,9 +98,43 @@ impl<S: FixedName> EquipArmor<S> {
             hard,
             soft,
             mass,
+            resists: Vec::new(),
         }
     }
 
+    /// 按装备等级从曲线取值，任一曲线不存在时返回 None
+    pub fn new_at_level(
+        name: S,
+        curves: &impl LevelCurveSource<S>,
+        [hard_curve, soft_curve, mass_curve]: [&S; 3],
+        level: f64,
+    ) -> Option<Self> {
+        Some(Self {
+            name,
+            hard: curves.eval_curve(hard_curve, level)?,
+            soft: curves.eval_curve(soft_curve, level)?,
+            mass: curves.eval_curve(mass_curve, level)?,
+            resists: Vec::new(),
+        })
+    }
+
+    /// 元素抗性，同一积攒槽覆盖
+    pub fn with_resist(mut self, bar: ElementBar, resist: f64) -> Self {
+        if let Some((_, old)) = self.resists.iter_mut().find(|(b, _)| *b == bar) {
+            *old = resist;
+        } else {
+            self.resists.push((bar, resist));
+        }
+        self
+    }
+
+    pub fn get_resist(&self, bar: ElementBar) -> f64 {
+        self.resists
+            .iter()
+            .find(|(b, _)| *b == bar)
+            .map_or(0.0, |(_, resist)| *resist)
+    }
+
     pub fn equip(
         &self,
         char_name: &S,
@@ -101,6 +159,37 @@ impl<S: FixedName> EquipArmor<S> {
         armor_soft_effs.0.delete_ele(|e| e.matched_id(&attr_eff_id));
         armor_mass_effs.0.delete_ele(|e| e.matched_id(&attr_eff_id));
     }
+
+    /// 元素抗性与 [`Self::equip`] 分开装载，没有元素抗性的单位无需持有抗性组件
+    pub fn equip_resists(
+        &self,
+        char_name: &S,
+        entropy_resist_effs: &mut EntropyResistEffs<S>,
+        electric_resist_effs: &mut ElectricResistEffs<S>,
+    ) {
+        for (bar, resist) in self.resists.iter() {
+            let effs = match bar {
+                ElementBar::Entropy => &mut entropy_resist_effs.0,
+                ElementBar::Electric => &mut electric_resist_effs.0,
+            };
+            equip_system::add_attr_eff(char_name, &self.name, *resist, effs);
+        }
+    }
+
+    pub fn take_off_resists(
+        &self,
+        char_name: &S,
+        entropy_resist_effs: &mut EntropyResistEffs<S>,
+        electric_resist_effs: &mut ElectricResistEffs<S>,
+    ) {
+        let attr_eff_id = equip_system::gen_attr_eff_id(char_name, &self.name);
+        entropy_resist_effs
+            .0
+            .delete_ele(|e| e.matched_id(&attr_eff_id));
+        electric_resist_effs
+            .0
+            .delete_ele(|e| e.matched_id(&attr_eff_id));
+    }
 }
 
 mod equip_system {
@@ -144,11 +233,13 @@ mod tests {
     use super::*;
     use crate::{
         base_lib::eff_attr::{
-            attr_systems::try_refresh_dirty_stat_attr, stat_attrs::StatAttr,
+            attr_systems::try_refresh_dirty_stat_attr,
+            curve_tables::{CurveExtrap, CurveInterp, CurveTable},
+            stat_attrs::StatAttr,
             upsert_container::UpsertContainer,
         },
         common_impl::combats::combat_additions::{
-            ArmorHard, ArmorMass, ArmorSoft, WeaponMass, WeaponSharp,
+            ArmorHard, ArmorMass, ArmorSoft, EntropyResist, WeaponMass, WeaponSharp,
         },
     };
 
@@ -214,4 +305,66 @@ mod tests {
         assert_eq!(soft_attr.0.get_current(), 10.0);
         assert_eq!(mass_attr.0.get_current(), 5.0);
     }
+
+    /// 盔甲的元素抗性单独装载，只影响对应积攒槽的抗性
+    #[test]
+    fn equip_armor_resists() {
+        let mut entropy_resist = EntropyResist(StatAttr::new(0.0));
+        let mut entropy_effs: EntropyResistEffs<String> =
+            EntropyResistEffs(UpsertContainer::default());
+        let mut electric_effs: ElectricResistEffs<String> =
+            ElectricResistEffs(UpsertContainer::default());
+
+        let armor = EquipArmor::new("fur".to_string(), 5.0, 5.0, 1.0)
+            .with_resist(ElementBar::Entropy, 0.2)
+            .with_resist(ElementBar::Entropy, 0.3);
+        assert_eq!(armor.get_resist(ElementBar::Electric), 0.0);
+        let char_name = "player".to_string();
+
+        armor.equip_resists(&char_name, &mut entropy_effs, &mut electric_effs);
+        try_refresh_dirty_stat_attr(&mut entropy_resist.0, &mut entropy_effs.0);
+        assert_eq!(entropy_resist.0.get_current(), 0.3);
+        assert!(electric_effs.0.ele_empty());
+
+        armor.take_off_resists(&char_name, &mut entropy_effs, &mut electric_effs);
+        try_refresh_dirty_stat_attr(&mut entropy_resist.0, &mut entropy_effs.0);
+        assert_eq!(entropy_resist.0.get_current(), 0.0);
+    }
+
+    /// 按等级从曲线表生成装备，曲线缺失时失败
+    #[test]
+    fn equip_from_curve_table() {
+        let table = CurveTable::<String>::from_csv(
+            "level,1,10
+sword_sharp,8,26
+sword_mass,3,3
+",
+            CurveInterp::Linear,
+            CurveExtrap::Clamp,
+        )
+        .unwrap();
+        let name = |s: &str| s.to_string();
+
+        let weapon = EquipWeapon::new_at_level(
+            name("iron_sword"),
+            &table,
+            [&name("sword_sharp"), &name("sword_mass")],
+            4.0,
+        )
+        .unwrap();
+        assert_eq!(weapon.sharp, 14.0);
+        assert_eq!(weapon.mass, 3.0);
+
+        let armor = EquipArmor::new_at_level(
+            name("plate"),
+            &table,
+            [
+                &name("plate_hard"),
+                &name("plate_soft"),
+                &name("plate_mass"),
+            ],
+            4.0,
+        );
+        assert!(armor.is_none());
+    }
 }
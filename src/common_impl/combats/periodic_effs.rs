@@ -0,0 +1,371 @@
+//! 周期性效果（持续伤害 DoT 、持续治疗 HoT）
+//!
+//! 每个效果由 [`Effect`] 与循环触发器组合而成，每次触发生成一个 [`SurvivalAttrEff`] 推入 [`SurvivalEffBuffer`] ，
+//! 之后与普通伤害一样经由 [`super::damages::damage_system`] 合并结算
+//!
+//! - 无限周期（光环、诅咒）使用 [`InfiniteStaticTrigger`] ，需由业务主动移除
+//! - 有限次数（燃烧 3 跳）使用 [`FewShotStaticTrigger`] ，次数耗尽后由 [`clean_expired_element`] 清理
+//!
+//! 施法者属性的读取时机由 [`PeriodicScaling`] 决定，逐效果可选：
+//! - 快照：施加时读取一次，之后施法者属性变化不影响（防止“先挂 DoT 再切装备”的收益）
+//! - 动态：每次触发时读取施法者当前属性
+//!
+//! 此处的缩放只代表施法者侧的系数（如法术强度），与 [`super::damages::damage_system::calc_damage_scale`] 相互独立，
+//! 若不希望重复缩放，应使用 [`SurvivalEffTargets::OnlyHealth`] 等不缩放的类型
+//!
+//! [`InfiniteStaticTrigger`]: crate::base_lib::cores::timers::static_trigger::InfiniteStaticTrigger
+//! [`FewShotStaticTrigger`]: crate::base_lib::cores::timers::static_trigger::FewShotStaticTrigger
+//! [`clean_expired_element`]: crate::base_lib::eff_attr::attr_systems::clean_expired_element
+
+use crate::{
+    base_lib::{
+        cores::{
+            timers::tiny_timer::{CyclicalTrigger, HasTimer},
+            unify_types::{FixedName, time_type},
+        },
+        eff_attr::{
+            bounded_attr_effs::AttrAlterEffType,
+            effects::{Effect, EffectMean, EffectMeaning},
+            upsert_container::{Upsert, UpsertContainer},
+        },
+    },
+    common_impl::combats::damages::{SurvivalAttrEff, SurvivalEffBuffer, SurvivalEffTargets},
+};
+
+/// 周期效果的缩放方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeriodicScaling {
+    /// 施加时快照的缩放系数
+    Snapshot(f64),
+    /// 每次触发时读取施法者当前属性
+    Dynamic,
+}
+
+/// 构造周期效果失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicEffError {
+    /// 周期不为正数，会在一次调用内无限触发
+    NonPositiveCycle,
+}
+
+/// 周期性生存效果，伤害为负数、治疗为正数
+#[derive(Debug, Clone)]
+pub struct PeriodicSurvivalEff<S: FixedName, Trigger> {
+    /// 每次触发时作用的目标
+    target_type: SurvivalEffTargets,
+    /// 每次触发时的生效方式（绝对值或是百分比）
+    alter_type: AttrAlterEffType,
+    /// 效果值为每次触发的基础值
+    eff: Effect<S>,
+    scaling: PeriodicScaling,
+    /// 循环触发器，同时决定效果的生命周期
+    trigger: Trigger,
+}
+
+impl<S: FixedName, Trigger: CyclicalTrigger> PeriodicSurvivalEff<S, Trigger> {
+    /// 触发器的周期必须为正数
+    pub fn new(
+        target_type: SurvivalEffTargets,
+        alter_type: AttrAlterEffType,
+        eff: Effect<S>,
+        scaling: PeriodicScaling,
+        trigger: Trigger,
+    ) -> Result<Self, PeriodicEffError> {
+        if trigger.get_cycle() <= time_type::ZERO {
+            return Err(PeriodicEffError::NonPositiveCycle);
+        }
+
+        Ok(Self {
+            target_type,
+            alter_type,
+            eff,
+            scaling,
+            trigger,
+        })
+    }
+}
+
+impl<S: FixedName, Trigger> PeriodicSurvivalEff<S, Trigger> {
+    pub fn get_target_type(&self) -> SurvivalEffTargets {
+        self.target_type
+    }
+
+    pub fn get_effect(&self) -> &Effect<S> {
+        &self.eff
+    }
+
+    pub fn get_scaling(&self) -> PeriodicScaling {
+        self.scaling
+    }
+
+    /// 生成单次触发的效果
+    ///
+    /// `live_scale` 为施法者当前的缩放系数，仅 [`PeriodicScaling::Dynamic`] 使用
+    pub fn gen_tick_eff(&self, live_scale: f64) -> SurvivalAttrEff<S> {
+        let scale = match self.scaling {
+            PeriodicScaling::Snapshot(scale) => scale,
+            PeriodicScaling::Dynamic => live_scale,
+        };
+        let mut eff = self.eff.clone();
+        eff.set_effect_value(self.eff.get_effect_value() * scale);
+        SurvivalAttrEff::new(self.target_type, self.alter_type, eff)
+    }
+}
+
+impl<S: FixedName, Trigger> HasTimer for PeriodicSurvivalEff<S, Trigger> {
+    type Timer = Trigger;
+
+    fn get_timer(&self) -> &Self::Timer {
+        &self.trigger
+    }
+
+    fn get_timer_mut(&mut self) -> &mut Self::Timer {
+        &mut self.trigger
+    }
+}
+
+/// 同一来源的同名效果视为同一个，再次施加时按 [`Upsert`] 策略合并（默认替换即刷新）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PeriodicSurvivalEffId<S: FixedName> {
+    pub eff: S,
+    pub from: S,
+}
+
+impl<S: FixedName, Trigger> Upsert for PeriodicSurvivalEff<S, Trigger> {
+    type Id = PeriodicSurvivalEffId<S>;
+
+    fn gen_id(&self) -> Self::Id {
+        PeriodicSurvivalEffId {
+            eff: self.eff.get_effect_name().clone(),
+            from: self.eff.get_from_name().clone(),
+        }
+    }
+
+    fn matched_id(&self, id: &Self::Id) -> bool {
+        *self.eff.get_effect_name() == id.eff && *self.eff.get_from_name() == id.from
+    }
+
+    fn has_same_id(&self, other: &Self) -> bool {
+        self.eff.get_effect_name() == other.eff.get_effect_name()
+            && self.eff.get_from_name() == other.eff.get_from_name()
+    }
+}
+
+impl<S: FixedName, Trigger> EffectMeaning for PeriodicSurvivalEff<S, Trigger> {
+    fn which_nature(&self) -> EffectMean {
+        EffectMean::which_nature(self.eff.get_effect_value(), 0.0)
+    }
+}
+
+/// 类型别名 周期效果的容器
+pub type PeriodicSurvivalEffs<S, Trigger> = UpsertContainer<PeriodicSurvivalEff<S, Trigger>>;
+
+pub mod periodic_system {
+    use super::*;
+
+    /// 单个效果一次调用最多补齐的触发次数，帧间隔过长时把补齐分摊到之后的调用
+    pub const MAX_FIRES_PER_CALL: u32 = 64;
+
+    /// 触发到期的周期效果，推入 buffer ，应在伤害系统消费 buffer 之前调用
+    ///
+    /// 返回触发次数达到上限的效果个数，这些效果可能仍有未补齐的触发
+    ///
+    /// - 一帧内可能触发多次（帧间隔大于周期时补齐），每次单独推入，最多 [`MAX_FIRES_PER_CALL`] 次
+    /// - 超出的触发保留在触发器中，下次调用继续补齐，不会丢失
+    /// - `live_scale` 根据效果（一般是其来源）读取施法者当前的缩放系数，仅 [`PeriodicScaling::Dynamic`] 调用
+    /// - 次数耗尽的效果不在此移除，交由 [`crate::base_lib::eff_attr::attr_systems::clean_expired_element`]
+    pub fn fire_periodic_effs<'a, S, Trigger, Ctx>(
+        effs: &mut PeriodicSurvivalEffs<S, Trigger>,
+        ctx: Ctx,
+        svv_eff_buffer: &mut SurvivalEffBuffer<S>,
+        live_scale: impl Fn(&Effect<S>) -> f64,
+    ) -> usize
+    where
+        S: FixedName,
+        Ctx: Copy,
+        Trigger: CyclicalTrigger<Ctx<'a> = Ctx>,
+    {
+        let mut pending = 0;
+        for periodic_eff in effs.iter_mut() {
+            let mut fires = 0;
+            while fires < MAX_FIRES_PER_CALL && periodic_eff.trigger.try_trigger_once(ctx) {
+                fires += 1;
+                let scale = match periodic_eff.scaling {
+                    PeriodicScaling::Snapshot(_) => 1.0, // 不读取，避免无谓的属性查询
+                    PeriodicScaling::Dynamic => live_scale(&periodic_eff.eff),
+                };
+                svv_eff_buffer.push(periodic_eff.gen_tick_eff(scale));
+            }
+            if fires == MAX_FIRES_PER_CALL {
+                pending += 1;
+            }
+        }
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::base_lib::{
+        cores::{
+            timers::{
+                static_timer::StaticTimeline,
+                static_trigger::{FewShotStaticTrigger, InfiniteStaticTrigger},
+                tiny_timer::Tickable,
+            },
+            unify_types::time_type,
+        },
+        eff_attr::attr_systems::clean_expired_element,
+    };
+
+    use super::{
+        periodic_system::{MAX_FIRES_PER_CALL, fire_periodic_effs},
+        *,
+    };
+
+    fn burn(
+        timeline: &StaticTimeline,
+        scaling: PeriodicScaling,
+    ) -> PeriodicSurvivalEff<&'static str, FewShotStaticTrigger> {
+        PeriodicSurvivalEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("mage", "burn", -10.0),
+            scaling,
+            FewShotStaticTrigger::new(timeline, time_type::unit::<1>(), 3),
+        )
+        .unwrap()
+    }
+
+    fn values(buffer: &SurvivalEffBuffer<&'static str>) -> Vec<f64> {
+        buffer
+            .iter()
+            .map(|e| e.get_effect().get_effect_value())
+            .collect()
+    }
+
+    /// 有限次数：每周期推入一次，耗尽后被清理
+    #[test]
+    fn test_few_shot_dot_fires_and_expires() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = PeriodicSurvivalEffs::default();
+        effs.upsert_replace(burn(&timeline, PeriodicScaling::Snapshot(1.0)));
+        let mut buffer = SurvivalEffBuffer::new();
+
+        fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| 1.0);
+        assert!(buffer.is_empty());
+
+        for _ in 0..4 {
+            timeline.0.tick(time_type::unit::<1>());
+            fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| 1.0);
+        }
+        assert_eq!(values(&buffer), vec![-10.0; 3]);
+        let first = buffer.iter().next().unwrap();
+        assert_eq!(first.get_target_type(), SurvivalEffTargets::OnlyHealth);
+        assert_eq!(*first.get_effect().get_effect_name(), "burn");
+
+        clean_expired_element(&mut effs, &timeline);
+        assert!(effs.ele_empty());
+    }
+
+    /// 快照与动态：施法者属性变化只影响动态效果
+    #[test]
+    fn test_snapshot_vs_dynamic_scaling() {
+        let mut timeline = StaticTimeline::new();
+        let caster_power = Cell::new(2.0);
+
+        let mut effs = PeriodicSurvivalEffs::default();
+        effs.upsert_replace(burn(
+            &timeline,
+            PeriodicScaling::Snapshot(caster_power.get()),
+        ));
+        let mut dynamic = burn(&timeline, PeriodicScaling::Dynamic);
+        dynamic.eff = Effect::new("mage", "poison", -10.0);
+        effs.upsert_replace(dynamic);
+
+        let mut buffer = SurvivalEffBuffer::new();
+        timeline.0.tick(time_type::unit::<1>());
+        fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| caster_power.get());
+        assert_eq!(values(&buffer), vec![-20.0, -20.0]);
+
+        caster_power.set(3.0);
+        let mut buffer = SurvivalEffBuffer::new();
+        timeline.0.tick(time_type::unit::<1>());
+        fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| caster_power.get());
+        assert_eq!(values(&buffer), vec![-20.0, -30.0]);
+    }
+
+    /// 无限周期：帧间隔超过周期时补齐触发次数
+    #[test]
+    fn test_infinite_hot_catch_up() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = PeriodicSurvivalEffs::default();
+        effs.upsert_replace(
+            PeriodicSurvivalEff::new(
+                SurvivalEffTargets::OnlyHealth,
+                AttrAlterEffType::MaxPer,
+                Effect::new("priest", "renew", 0.05),
+                PeriodicScaling::Snapshot(1.0),
+                InfiniteStaticTrigger::new(&timeline, time_type::unit::<1>()),
+            )
+            .unwrap(),
+        );
+        assert!(effs.iter_ele().next().unwrap().which_nature().is_good());
+
+        let mut buffer = SurvivalEffBuffer::new();
+        timeline.0.tick(time_type::unit::<3>());
+        fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| 1.0);
+        assert_eq!(buffer.len(), 3);
+
+        clean_expired_element(&mut effs, &timeline);
+        assert_eq!(effs.ele_len(), 1);
+    }
+
+    /// 周期为零：构造时拒绝
+    #[test]
+    fn test_zero_cycle_is_rejected() {
+        let timeline = StaticTimeline::new();
+        let spikes = PeriodicSurvivalEff::new(
+            SurvivalEffTargets::OnlyHealth,
+            AttrAlterEffType::Val,
+            Effect::new("trap", "spikes", -1.0),
+            PeriodicScaling::Snapshot(1.0),
+            InfiniteStaticTrigger::new(&timeline, time_type::ZERO),
+        );
+        assert_eq!(spikes.unwrap_err(), PeriodicEffError::NonPositiveCycle);
+    }
+
+    /// 帧间隔过长：超出上限的触发留到下次调用补齐，总次数不丢失
+    #[test]
+    fn test_excess_fires_carry_over() {
+        let mut timeline = StaticTimeline::new();
+        let mut effs = PeriodicSurvivalEffs::default();
+        effs.upsert_replace(
+            PeriodicSurvivalEff::new(
+                SurvivalEffTargets::OnlyHealth,
+                AttrAlterEffType::Val,
+                Effect::new("trap", "spikes", -1.0),
+                PeriodicScaling::Snapshot(1.0),
+                InfiniteStaticTrigger::new(&timeline, time_type::unit::<1>()),
+            )
+            .unwrap(),
+        );
+        timeline.0.tick(time_type::unit::<100>());
+
+        let mut buffer = SurvivalEffBuffer::new();
+        assert_eq!(
+            fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| 1.0),
+            1
+        );
+        assert_eq!(buffer.len(), MAX_FIRES_PER_CALL as usize);
+
+        let mut buffer = SurvivalEffBuffer::new();
+        assert_eq!(
+            fire_periodic_effs(&mut effs, &timeline, &mut buffer, |_| 1.0),
+            0
+        );
+        assert_eq!(buffer.len(), 100 - MAX_FIRES_PER_CALL as usize);
+    }
+}
@@ -1 +1,92 @@
+//! 属性相关的 Godot 类
 
+use godot::prelude::*;
+
+use crate::base_lib::{
+    cores::{timers::tiny_timer::Tickable, unify_types::time_type},
+    eff_attr::bounded_attr_displays::{BoundedAttrDisplay, DisplayCatchUp, DisplayEasing},
+};
+
+/// 资源条的界面显示值，见 [`BoundedAttrDisplay`]
+///
+/// 在 GDScript 中每帧 `set_target` 后 `tick(delta)` ，以 `get_display` 绘制滞后段
+#[derive(GodotClass)]
+#[class(no_init, base=RefCounted)]
+pub struct AttrDisplayTracker {
+    inner: BoundedAttrDisplay,
+}
+
+/// `easing` 取 `EASING_*` 常量，未知值按匀速处理；延迟与速度钳制到不小于 0 ，速度为 0 时立即到达
+fn catch_up_of(easing: i64, delay: f64, speed: f64) -> DisplayCatchUp {
+    let speed = speed.max(0.0);
+    let easing = match easing {
+        _ if speed <= 0.0 => DisplayEasing::Instant,
+        AttrDisplayTracker::EASING_INSTANT => DisplayEasing::Instant,
+        AttrDisplayTracker::EASING_EXPONENTIAL => DisplayEasing::Exponential(speed),
+        _ => DisplayEasing::Linear(speed),
+    };
+    DisplayCatchUp::new(time_type::from_f64(delay.max(0.0)), easing)
+}
+
+#[godot_api]
+impl AttrDisplayTracker {
+    /// 见 [`DisplayEasing::Instant`]
+    #[constant]
+    const EASING_INSTANT: i64 = 0;
+    /// 见 [`DisplayEasing::Linear`] ，速度为每秒变化的绝对值
+    #[constant]
+    const EASING_LINEAR: i64 = 1;
+    /// 见 [`DisplayEasing::Exponential`] ，速度为每秒缩小差距的速率
+    #[constant]
+    const EASING_EXPONENTIAL: i64 = 2;
+
+    /// 减少与增加分别指定追赶方式、延迟（秒）与速度
+    #[func]
+    fn create(
+        value: f64,
+        loss_easing: i64,
+        loss_delay: f64,
+        loss_speed: f64,
+        gain_easing: i64,
+        gain_delay: f64,
+        gain_speed: f64,
+    ) -> Gd<Self> {
+        Gd::from_object(Self {
+            inner: BoundedAttrDisplay::new(
+                value,
+                catch_up_of(loss_easing, loss_delay, loss_speed),
+                catch_up_of(gain_easing, gain_delay, gain_speed),
+            ),
+        })
+    }
+
+    #[func]
+    fn set_target(&mut self, value: f64) {
+        self.inner.set_target(value);
+    }
+
+    #[func]
+    fn snap(&mut self) {
+        self.inner.snap();
+    }
+
+    #[func]
+    fn tick(&mut self, delta: f64) {
+        self.inner.tick(time_type::from_f64(delta));
+    }
+
+    #[func]
+    fn get_display(&self) -> f64 {
+        self.inner.get_display()
+    }
+
+    #[func]
+    fn get_target(&self) -> f64 {
+        self.inner.get_target()
+    }
+
+    #[func]
+    fn is_settled(&self) -> bool {
+        self.inner.is_settled()
+    }
+}